@@ -1,5 +1,8 @@
 use criterion::{criterion_group, criterion_main, Criterion};
-use serial_perf::{counting::Counting, statistics::CountingStatistics};
+use serial_perf::{
+    counting::Counting,
+    statistics::{CountingStatistics, DummyStatistics},
+};
 use std::convert::Infallible;
 
 const BENCH_GROUP: &str = "counting cnt stats";
@@ -26,6 +29,58 @@ impl embedded_hal_nb::serial::Write for DummySerial {
     }
 }
 
+/// Endlessly replays valid packets, so receiving side verifies CRC of every packet
+pub struct ReplaySerial {
+    data: Vec<u8>,
+    pos: usize,
+}
+
+impl embedded_hal_nb::serial::ErrorType for ReplaySerial {
+    type Error = Infallible;
+}
+
+impl embedded_hal_nb::serial::Read for ReplaySerial {
+    fn read(&mut self) -> nb::Result<u8, Self::Error> {
+        let byte = self.data[self.pos];
+        self.pos = (self.pos + 1) % self.data.len();
+        Ok(byte)
+    }
+}
+
+fn verified_recv(c: &mut Criterion, name: &str, with_checksum: bool) {
+    let mut sender = Counting::<_, u64, _, _, _>::builder(
+        DummySerial,
+        DummyStatistics,
+        DummyStatistics,
+        DummyStatistics,
+    )
+    .checksum(with_checksum)
+    .build();
+    let mut data = vec![0; sender.packet_size() * 1024];
+    sender.fill_send_slice(&mut data);
+
+    let mut counter = Counting::<_, u64, _, _, _>::builder(
+        ReplaySerial { data, pos: 0 },
+        CountingStatistics::default(),
+        CountingStatistics::default(),
+        CountingStatistics::default(),
+    )
+    .checksum(with_checksum)
+    .build();
+
+    let mut rx_group = c.benchmark_group(BENCH_GROUP);
+    rx_group.throughput(criterion::Throughput::Bytes(1));
+    rx_group.bench_function(name, |b| b.iter(|| counter.recv_nb()));
+    rx_group.finish();
+
+    println!(
+        "{BENCH_GROUP}/{name}: {} packets received, {} crc errors",
+        counter.rx_packets(),
+        counter.crc_errors()
+    );
+    assert_eq!(counter.crc_errors(), 0);
+}
+
 fn criterion_benchmark(c: &mut Criterion) {
     let serial = DummySerial;
     let mut counter = Counting::<_, u64, _, _, _>::new(
@@ -62,6 +117,9 @@ fn criterion_benchmark(c: &mut Criterion) {
     rx_group.throughput(criterion::Throughput::Bytes(1));
     rx_group.bench_function("recv no crc", |b| b.iter(|| counter.recv_nb()));
     rx_group.finish();
+
+    verified_recv(c, "recv valid", true);
+    verified_recv(c, "recv valid no crc", false);
 }
 
 criterion_group!(benches, criterion_benchmark);
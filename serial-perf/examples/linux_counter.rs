@@ -9,6 +9,7 @@ use serial_perf::{
     },
     clock::StdClock,
     counting::Counting,
+    report::Report,
     statistics::CountingStatistics,
 };
 
@@ -57,34 +58,15 @@ fn main() -> anyhow::Result<()> {
         CountingStatistics::default(),
     );
 
-    let mut last_print = Instant::now();
+    let start = Instant::now();
+    let mut last_print = start;
 
     println!("Start loop");
     loop {
-        nb::block!(counter.loop_nb())?;
+        counter.run_loop()?;
 
         if Duration::from_millis(PRINT_INTERVAL_MS) < last_print.elapsed() {
-            println!(
-                "TX(bytes): sent: {}, errors: {}",
-                counter.tx_stats().successful(),
-                counter.tx_stats().failed()
-            );
-
-            println!(
-                "RX(bytes): total: {}, errors: {}",
-                counter.rx_stats().total(),
-                counter.rx_stats().failed()
-            );
-
-            if counter.loss_stats().total() != 0 {
-                println!(
-                    "RX(packet): loss: {}, total: {}, {:.02}%",
-                    counter.loss_stats().failed(),
-                    counter.loss_stats().total(),
-                    (counter.loss_stats().failed() * 10000 / counter.loss_stats().total()) as f64
-                        / 100.0
-                );
-            }
+            print!("{}", Report::from(&counter).with_duration(start.elapsed()));
 
             last_print = Instant::now();
         }
@@ -1,18 +1,14 @@
 //!
 //! The example shows how to use library for measure incoming byte rate without any check
 //!
-//! The example uses Loopback that can be used. If send_nb is never is called we can use it as sink
+//! The example uses Sink that only receives bytes and never sends anything back
 //!
 
 use std::time::{Duration, Instant};
 
 use clap::Parser;
 use linux_embedded_hal::Serial;
-use serial_perf::{
-    clock::StdClock,
-    loopback::Loopback,
-    statistics::{DummyStatistics, IntervalRateStatistics},
-};
+use serial_perf::{clock::StdClock, loopback::Sink, statistics::IntervalRateStatistics};
 
 const PRINT_INTERVAL_MS: u64 = 5000;
 
@@ -38,15 +34,15 @@ fn main() -> anyhow::Result<()> {
 
     let clock = StdClock;
     let rx_stats = IntervalRateStatistics::new(&clock, Duration::from_secs(5));
-    let mut loopback = Loopback::new(serial, DummyStatistics, rx_stats);
+    let mut sink = Sink::new(serial, rx_stats);
 
     let mut last_print = Instant::now();
 
     loop {
-        nb::block!(loopback.recv_nb())?;
+        nb::block!(sink.recv_nb())?;
 
         if Duration::from_millis(PRINT_INTERVAL_MS) < last_print.elapsed() {
-            let rx_stats = loopback.rx_stats();
+            let rx_stats = sink.rx_stats();
 
             let success = rx_stats
                 .success_rate()
@@ -58,7 +54,10 @@ fn main() -> anyhow::Result<()> {
                 .bytes_per_second_f64()
                 .unwrap_or_default();
 
-            let total = success + fail;
+            let total = rx_stats
+                .total_rate()
+                .bytes_per_second_f64()
+                .unwrap_or_default();
 
             println!(
                 "RX (Succ Fail Total): {:.02} {:.02} {:.02}",
@@ -0,0 +1,79 @@
+use std::time::{Duration, Instant};
+
+use clap::Parser;
+use serial_perf::{
+    byte_rate::{
+        limit::{ByteRateSerialLimiter, PollingByteRateLimiter},
+        rate::ByteRate,
+    },
+    clock::StdClock,
+    counting::Counting,
+    io_serial::IoSerial,
+    report::Report,
+    statistics::CountingStatistics,
+};
+
+const PRINT_INTERVAL_MS: u64 = 5000;
+
+#[derive(Parser)]
+pub struct CommonArgs {
+    /// The port to connect to.
+    port: String,
+
+    /// Baud rate for serial
+    #[clap(short, long, default_value_t = 115200)]
+    baud_rate: u32,
+
+    /// Byte rate limit per specified time
+    #[clap(long, default_value_t = 11520)]
+    byte_limit: usize,
+
+    /// Time for byte rate limit if zero - unlimited
+    #[clap(long, default_value_t = 0)]
+    byte_limit_interval_us: usize,
+}
+
+impl CommonArgs {
+    pub fn create_serial(&self) -> IoSerial<Box<dyn serialport::SerialPort>> {
+        let port = serialport::new(&self.port, self.baud_rate)
+            .timeout(Duration::from_millis(1))
+            .open()
+            .expect("failed to create serial");
+
+        IoSerial::new(port)
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = CommonArgs::parse();
+
+    let clock = StdClock;
+    let rate_limit = ByteRate::new(
+        args.byte_limit,
+        Duration::from_micros(args.byte_limit_interval_us as u64),
+    );
+    let rate_limiter = PollingByteRateLimiter::new(rate_limit, &clock);
+
+    let serial = args.create_serial();
+    let limited_serial = ByteRateSerialLimiter::new(serial, rate_limiter);
+    let mut counter = Counting::<_, u16>::new(
+        limited_serial,
+        CountingStatistics::default(),
+        CountingStatistics::default(),
+        CountingStatistics::default(),
+    );
+
+    let start = Instant::now();
+    let mut last_print = start;
+
+    println!("Start loop");
+    loop {
+        counter.run_loop()?;
+
+        if Duration::from_millis(PRINT_INTERVAL_MS) < last_print.elapsed() {
+            print!("{}", Report::from(&counter).with_duration(start.elapsed()));
+
+            last_print = Instant::now();
+        }
+    }
+}
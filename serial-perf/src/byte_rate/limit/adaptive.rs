@@ -0,0 +1,192 @@
+use core::time::Duration;
+
+use crate::byte_rate::rate::ByteRate;
+use crate::clock::{Clock, ClockExt};
+use crate::statistics::CountingStatistics;
+
+use super::PollingByteRateLimiter;
+
+/// Closed-loop controller seeking the highest byte rate with packet loss below target
+///
+/// Every evaluation interval the loss since the previous evaluation is compared with the target:
+/// below or equal to the target the rate is increased by `step` bytes, above it the rate is halved (AIMD).
+/// The interval of the rate is kept as is.
+///
+/// The loss statistics are cloned before the evaluation as they are owned by `Counting`,
+/// while the limiter is owned by the serial:
+/// `controller.evaluate(&counting.loss_stats().clone(), counting.serial_mut().limiter_mut())`
+///
+/// The loss since the previous evaluation is found as a difference of the statistics, so call `on_loss_reset`
+/// whenever the loss statistics are reset, e.g. by `Counting::reset`.
+pub struct AdaptiveRateController<'clk, Clk>
+where
+    Clk: Clock,
+{
+    clock: &'clk Clk,
+    evaluation_interval: Duration,
+    last_evaluation: Clk::Instant,
+
+    current_rate: ByteRate,
+    target_loss: f32,
+    step: usize,
+
+    /// Loss statistics at the previous evaluation
+    last_loss: CountingStatistics,
+}
+
+impl<'clk, Clk> AdaptiveRateController<'clk, Clk>
+where
+    Clk: Clock,
+{
+    /// Creates new controller starting from `initial_rate`
+    ///
+    /// `target_loss` is the ratio of lost packets in `[0.0, 1.0]`, e.g. 0.01 for 1%.
+    pub fn new(
+        clock: &'clk Clk,
+        initial_rate: ByteRate,
+        target_loss: f32,
+        step: usize,
+        evaluation_interval: Duration,
+    ) -> Self {
+        Self {
+            clock,
+            evaluation_interval,
+            last_evaluation: clock.now(),
+            current_rate: initial_rate,
+            target_loss,
+            step,
+            last_loss: CountingStatistics::default(),
+        }
+    }
+
+    /// Returns the rate currently applied to the limiter
+    pub fn current_rate(&self) -> &ByteRate {
+        &self.current_rate
+    }
+
+    /// Returns the maximum ratio of lost packets to keep
+    pub fn target_loss(&self) -> f32 {
+        self.target_loss
+    }
+
+    /// Forgets the loss statistics of the previous evaluation, call it right after resetting the loss statistics.
+    ///
+    /// The next evaluation then takes all the loss statistics as new ones.
+    pub fn on_loss_reset(&mut self) {
+        self.last_loss = CountingStatistics::default();
+    }
+
+    /// Adjusts rate of `limiter` by `loss` since the previous evaluation if the evaluation interval passed.
+    ///
+    /// Returns `true` if the rate changed. Nothing changes if no packets were received since the previous evaluation.
+    pub fn evaluate(
+        &mut self,
+        loss: &CountingStatistics,
+        limiter: &mut PollingByteRateLimiter<'_, Clk>,
+    ) -> bool {
+        let now = self.clock.now();
+        if self.clock.duration_between(self.last_evaluation, now) < self.evaluation_interval {
+            return false;
+        }
+
+        let last_loss = core::mem::replace(&mut self.last_loss, loss.clone());
+        self.last_evaluation = now;
+
+        // The statistics were reset without `on_loss_reset`, at least take all of them as new if noticed.
+        // A reset followed by more packets than before can't be told apart from the regular counting.
+        let last_loss = if loss.total() < last_loss.total() {
+            CountingStatistics::default()
+        } else {
+            last_loss
+        };
+
+        let total = loss.total() - last_loss.total();
+        if total == 0 {
+            return false;
+        }
+
+        let failed = loss.failed().saturating_sub(last_loss.failed());
+        let loss_ratio = (failed as f64 / total as f64) as f32;
+
+        let bytes = self.current_rate.bytes();
+        let new_bytes = if loss_ratio > self.target_loss {
+            (bytes / 2).max(1)
+        } else {
+            bytes.saturating_add(self.step)
+        };
+
+        if new_bytes == bytes {
+            return false;
+        }
+
+        self.current_rate.set_bytes(new_bytes);
+        limiter.set_byte_rate(self.current_rate.clone());
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use crate::byte_rate::limit::PollingByteRateLimiter;
+    use crate::byte_rate::rate::ByteRate;
+    use crate::clock::MockClock;
+    use crate::statistics::{CountingStatistics, Statistics};
+
+    use super::AdaptiveRateController;
+
+    #[test]
+    fn additive_increase_multiplicative_decrease() {
+        let clock = MockClock::new();
+        let rate = ByteRate::new(1000, Duration::from_secs(1));
+        let mut limiter = PollingByteRateLimiter::new(rate.clone(), &clock);
+        let mut controller =
+            AdaptiveRateController::new(&clock, rate, 0.01, 100, Duration::from_secs(5));
+        let mut loss = CountingStatistics::default();
+
+        loss.add_successful(1000);
+        assert!(!controller.evaluate(&loss, &mut limiter));
+
+        clock.advance(Duration::from_secs(5));
+        assert!(controller.evaluate(&loss, &mut limiter));
+        assert_eq!(controller.current_rate().bytes(), 1100);
+        assert_eq!(limiter.configured_rate().bytes(), 1100);
+
+        // 10% loss since the previous evaluation
+        loss.add_successful(90);
+        loss.add_failed(10);
+        clock.advance(Duration::from_secs(5));
+        assert!(controller.evaluate(&loss, &mut limiter));
+        assert_eq!(controller.current_rate().bytes(), 550);
+
+        // Nothing received
+        clock.advance(Duration::from_secs(5));
+        assert!(!controller.evaluate(&loss, &mut limiter));
+        assert_eq!(limiter.configured_rate().bytes(), 550);
+    }
+
+    #[test]
+    fn loss_reset() {
+        let clock = MockClock::new();
+        let rate = ByteRate::new(1000, Duration::from_secs(1));
+        let mut limiter = PollingByteRateLimiter::new(rate.clone(), &clock);
+        let mut controller =
+            AdaptiveRateController::new(&clock, rate, 0.01, 100, Duration::from_secs(5));
+        let mut loss = CountingStatistics::default();
+
+        loss.add_successful(1000);
+        clock.advance(Duration::from_secs(5));
+        assert!(controller.evaluate(&loss, &mut limiter));
+        assert_eq!(controller.current_rate().bytes(), 1100);
+
+        // 0.5% loss after the reset, more packets than before it
+        loss.reset();
+        controller.on_loss_reset();
+        loss.add_successful(1010);
+        loss.add_failed(5);
+        clock.advance(Duration::from_secs(5));
+        assert!(controller.evaluate(&loss, &mut limiter));
+        assert_eq!(controller.current_rate().bytes(), 1200);
+    }
+}
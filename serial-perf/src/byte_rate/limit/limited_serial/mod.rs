@@ -1,23 +1,113 @@
-use embedded_timers::clock::Clock;
+use core::time::Duration;
 
-use super::PollingByteRateLimiter;
+use crate::{
+    byte_rate::rate::ByteRate,
+    clock::{Clock, TimerError},
+};
+
+use super::{ByteRateLimiter, PollingByteRateLimiter};
 
 mod nb;
 
+/// Error of the serial wrapped into `ByteRateSerialLimiter`
+#[derive(Debug)]
+pub enum LimitedSerialError<E> {
+    /// Error of the underlying serial
+    Serial(E),
+    /// Error of the limiter, e.g. timer overflow
+    Limiter(TimerError),
+}
+
+impl<E> core::fmt::Display for LimitedSerialError<E>
+where
+    E: core::fmt::Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Serial(e) => write!(f, "serial error: {e:?}"),
+            Self::Limiter(e) => write!(f, "rate limiter error: {e:?}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E> std::error::Error for LimitedSerialError<E> where E: core::fmt::Debug {}
+
 /// A wrapper around embedded-hal serial that will stop sending data above specified byte rate limit
-pub struct ByteRateSerialLimiter<'clock, Clk, Serial>
+///
+/// `new` takes `PollingByteRateLimiter`, any other `ByteRateLimiter` can be used with `with_limiter`,
+/// e.g. `TokenBucketByteRateLimiter`. Reading is limited too only with `SharedByteRateLimiter`.
+pub struct ByteRateSerialLimiter<Serial, Limiter>
 where
-    Clk: Clock,
+    Limiter: ByteRateLimiter,
 {
-    rate_limit: PollingByteRateLimiter<'clock, Clk>,
+    rate_limit: Limiter,
     serial: Serial,
 }
 
-impl<'clock, Clk, Serial> ByteRateSerialLimiter<'clock, Clk, Serial>
+/// `ByteRateSerialLimiter` with the default `PollingByteRateLimiter`
+pub type PollingByteRateSerialLimiter<'clock, Clk, Serial> =
+    ByteRateSerialLimiter<Serial, PollingByteRateLimiter<'clock, Clk>>;
+
+impl<'clock, Clk, Serial> PollingByteRateSerialLimiter<'clock, Clk, Serial>
 where
     Clk: Clock,
 {
     pub fn new(serial: Serial, rate_limit: PollingByteRateLimiter<'clock, Clk>) -> Self {
+        Self::with_limiter(serial, rate_limit)
+    }
+}
+
+impl<Serial, Limiter> ByteRateSerialLimiter<Serial, Limiter>
+where
+    Limiter: ByteRateLimiter,
+{
+    /// Creates the wrapper limited by any `ByteRateLimiter`
+    pub fn with_limiter(serial: Serial, rate_limit: Limiter) -> Self {
         Self { rate_limit, serial }
     }
+
+    /// Gets time until a new byte can be written, so the caller can sleep instead of polling.
+    /// Returns None if writing is unlimited or duration cannot be found out
+    pub fn duration_until_reset(&self) -> Option<Duration> {
+        self.rate_limit.duration_until_reset()
+    }
+
+    /// Gets the maximum byte rate of writing.
+    /// Returns None if writing is unlimited or the rate is unknown
+    pub fn rate_limit(&self) -> Option<ByteRate> {
+        self.rate_limit.rate_limit()
+    }
+
+    /// Gets amount of bytes that can be written before the limit is reached, read bytes count too
+    /// if the limiter is `SharedByteRateLimiter`.
+    /// Returns None if writing is unlimited or the budget is unknown
+    pub fn remaining(&self) -> Option<usize> {
+        self.rate_limit.remaining()
+    }
+
+    /// Returns reference to the limiter
+    pub fn limiter(&self) -> &Limiter {
+        &self.rate_limit
+    }
+
+    /// Returns mutable reference to the limiter, e.g. to change the rate
+    pub fn limiter_mut(&mut self) -> &mut Limiter {
+        &mut self.rate_limit
+    }
+
+    /// Returns reference to the underlying serial
+    pub fn serial(&self) -> &Serial {
+        &self.serial
+    }
+
+    /// Returns mutable reference to the underlying serial
+    pub fn serial_mut(&mut self) -> &mut Serial {
+        &mut self.serial
+    }
+
+    /// Destroys the wrapper returning the underlying serial
+    pub fn into_inner(self) -> Serial {
+        self.serial
+    }
 }
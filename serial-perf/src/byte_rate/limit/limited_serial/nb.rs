@@ -1,47 +1,174 @@
 use embedded_hal_nb::nb::Error;
-use embedded_hal_nb::serial::{ErrorType, Read, Write};
-use embedded_timers::clock::Clock;
+use embedded_hal_nb::serial::{ErrorKind, ErrorType, Read, Write};
 
-use super::ByteRateSerialLimiter;
+use super::{ByteRateLimiter, ByteRateSerialLimiter, LimitedSerialError};
 
-impl<'clock, Clk, Serial> ErrorType for ByteRateSerialLimiter<'clock, Clk, Serial>
+impl<E> embedded_hal_nb::serial::Error for LimitedSerialError<E>
 where
-    Clk: Clock,
+    E: embedded_hal_nb::serial::Error,
+{
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Serial(e) => e.kind(),
+            Self::Limiter(_) => ErrorKind::Other,
+        }
+    }
+}
+
+impl<Serial, Limiter> ErrorType for ByteRateSerialLimiter<Serial, Limiter>
+where
+    Limiter: ByteRateLimiter,
     Serial: ErrorType,
 {
-    type Error = Serial::Error;
+    type Error = LimitedSerialError<Serial::Error>;
 }
 
-impl<'clock, Clk, Serial> Read for ByteRateSerialLimiter<'clock, Clk, Serial>
+impl<Serial, Limiter> Read for ByteRateSerialLimiter<Serial, Limiter>
 where
-    Clk: Clock,
+    Limiter: ByteRateLimiter,
     Serial: Read,
 {
+    /// Reads byte, if the limiter also limits reading it is done only if the limit is not reached.
+    ///
+    /// # Note
+    /// If the limiter fails after the byte is read, the error is returned and the byte is lost.
     fn read(&mut self) -> embedded_hal_nb::nb::Result<u8, Self::Error> {
-        self.serial.read()
+        let limits_read = self.rate_limit.limits_read();
+        if limits_read && !self.rate_limit.can_send() {
+            return Err(Error::WouldBlock);
+        }
+
+        let word = self
+            .serial
+            .read()
+            .map_err(|e| e.map(LimitedSerialError::Serial))?;
+
+        if limits_read {
+            self.rate_limit
+                .send()
+                .map_err(|e| Error::Other(LimitedSerialError::Limiter(e)))?;
+        }
+
+        Ok(word)
     }
 }
 
-impl<'clock, Clk, Serial> Write for ByteRateSerialLimiter<'clock, Clk, Serial>
+impl<Serial, Limiter> Write for ByteRateSerialLimiter<Serial, Limiter>
 where
-    Clk: Clock,
+    Limiter: ByteRateLimiter,
     Serial: Write,
 {
+    /// Writes byte if the limit is not reached.
+    ///
+    /// # Note
+    /// If the limiter fails after the byte is written, the error is returned even though the byte was sent.
     fn write(&mut self, word: u8) -> embedded_hal_nb::nb::Result<(), Self::Error> {
         if !self.rate_limit.can_send() {
             return Err(Error::WouldBlock);
         }
 
-        let result = self.serial.write(word);
-        if result.is_ok() {
-            // FIXME: handle error here
-            self.rate_limit.send().unwrap();
-        }
+        self.serial
+            .write(word)
+            .map_err(|e| e.map(LimitedSerialError::Serial))?;
 
-        result
+        self.rate_limit
+            .send()
+            .map_err(|e| Error::Other(LimitedSerialError::Limiter(e)))?;
+
+        Ok(())
     }
 
     fn flush(&mut self) -> embedded_hal_nb::nb::Result<(), Self::Error> {
-        self.serial.flush()
+        self.serial
+            .flush()
+            .map_err(|e| e.map(LimitedSerialError::Serial))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use embedded_hal_nb::nb::Error;
+    use embedded_hal_nb::serial::{Read, Write};
+
+    use crate::byte_rate::limit::{
+        ByteRateSerialLimiter, LimitedSerialError, PollingByteRateLimiter, SharedByteRateLimiter,
+    };
+    use crate::byte_rate::rate::ByteRate;
+    use crate::clock::{MockClock, TimerError};
+    use crate::test_serial::ScriptedSerial;
+
+    #[test]
+    fn duration_until_reset() {
+        let clock = MockClock::new();
+
+        let max_rate = ByteRate::new(1, Duration::from_secs(1));
+        let limiter = PollingByteRateLimiter::new(max_rate, &clock);
+        let mut serial = ByteRateSerialLimiter::new(ScriptedSerial::new().reading(0), limiter);
+
+        serial.write(0).unwrap();
+        clock.advance(Duration::from_millis(400));
+
+        assert!(matches!(serial.write(0), Err(Error::WouldBlock)));
+        assert_eq!(
+            serial.duration_until_reset(),
+            Some(Duration::from_millis(600))
+        );
+    }
+
+    #[test]
+    fn limiter_overflow_is_error() {
+        let clock = MockClock::new();
+        clock.advance(Duration::from_nanos(u64::MAX - 1));
+
+        let max_rate = ByteRate::new(10, Duration::from_secs(1));
+        let limiter = PollingByteRateLimiter::new(max_rate, &clock);
+        let mut serial = ByteRateSerialLimiter::new(ScriptedSerial::new().reading(0), limiter);
+
+        assert!(matches!(
+            serial.write(0),
+            Err(Error::Other(LimitedSerialError::Limiter(
+                TimerError::Overflow
+            )))
+        ));
+    }
+
+    #[test]
+    fn shared_budget() {
+        let clock = MockClock::new();
+
+        let max_rate = ByteRate::new(4, Duration::from_secs(1));
+        let limiter = SharedByteRateLimiter::new(PollingByteRateLimiter::new(max_rate, &clock));
+        let mut serial =
+            ByteRateSerialLimiter::with_limiter(ScriptedSerial::new().reading(0), limiter);
+
+        serial.write(0).unwrap();
+        serial.read().unwrap();
+        serial.read().unwrap();
+        assert_eq!(serial.remaining(), Some(1));
+
+        serial.write(0).unwrap();
+        assert_eq!(serial.remaining(), Some(0));
+        assert!(matches!(serial.read(), Err(Error::WouldBlock)));
+        assert!(matches!(serial.write(0), Err(Error::WouldBlock)));
+
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(serial.remaining(), Some(4));
+        serial.read().unwrap();
+    }
+
+    #[test]
+    fn read_unlimited_without_shared() {
+        let clock = MockClock::new();
+
+        let max_rate = ByteRate::new(1, Duration::from_secs(1));
+        let limiter = PollingByteRateLimiter::new(max_rate, &clock);
+        let mut serial = ByteRateSerialLimiter::new(ScriptedSerial::new().reading(0), limiter);
+
+        for _ in 0..10 {
+            serial.read().unwrap();
+        }
+        assert_eq!(serial.remaining(), Some(1));
     }
 }
@@ -2,8 +2,51 @@
 //! Structs for limiting the byte rate
 //!
 
+use core::time::Duration;
+
+use crate::{byte_rate::rate::ByteRate, clock::TimerError};
+
 mod polling;
 pub use polling::PollingByteRateLimiter;
 
+mod token_bucket;
+pub use token_bucket::TokenBucketByteRateLimiter;
+
+mod shared;
+pub use shared::SharedByteRateLimiter;
+
 mod limited_serial;
-pub use limited_serial::ByteRateSerialLimiter;
+pub use limited_serial::{ByteRateSerialLimiter, LimitedSerialError, PollingByteRateSerialLimiter};
+
+mod adaptive;
+pub use adaptive::AdaptiveRateController;
+
+/// Common interface of the byte rate limiters, allows to use them with `ByteRateSerialLimiter`
+pub trait ByteRateLimiter {
+    /// Check if sending is possible but doesn't assume you will send byte if it is true
+    fn can_send(&self) -> bool;
+
+    /// Notify that you have sent byte successfully, returns true if limit NOT reached yet or false otherwise
+    fn send(&mut self) -> Result<bool, TimerError>;
+
+    /// Gets time until new bytes can be sent.
+    /// Returns None if limiter is unlimited or duration cannot be found out
+    fn duration_until_reset(&self) -> Option<Duration>;
+
+    /// Gets the maximum byte rate allowed by the limiter.
+    /// Returns None if limiter is unlimited or the rate is unknown
+    fn rate_limit(&self) -> Option<ByteRate> {
+        None
+    }
+
+    /// Gets amount of bytes that can be sent before the limit is reached.
+    /// Returns None if limiter is unlimited or the budget is unknown
+    fn remaining(&self) -> Option<usize> {
+        None
+    }
+
+    /// Whether read bytes are taken from the budget too, see `SharedByteRateLimiter`
+    fn limits_read(&self) -> bool {
+        false
+    }
+}
@@ -1,9 +1,9 @@
 use core::time::Duration;
 
-use embedded_timers::instant::Instant;
-
 use crate::byte_rate::rate::ByteRate;
-use crate::clock::{Clock, Timer, TimerError};
+use crate::clock::{Clock, ClockExt, Instant, Timer, TimerError};
+
+use super::ByteRateLimiter;
 
 enum State {
     Idle,
@@ -14,6 +14,9 @@ enum State {
 
 /// Limiter that required cannot notify a client about the rate limit being reached so uses a polling approach.
 /// The client uses `can_send` to check if it can send. The client call `send` on successful byte sent
+///
+/// Rates below 1 byte per second are set with a single byte over a long interval, e.g.
+/// `ByteRate::new(1, Duration::from_millis(2500))` sends one byte and waits the full interval before the next one.
 pub struct PollingByteRateLimiter<'clk, Clk>
 where
     Clk: Clock,
@@ -87,6 +90,27 @@ where
         }
     }
 
+    /// Returns the rate the limiter is configured with
+    pub fn configured_rate(&self) -> &ByteRate {
+        &self.max_rate
+    }
+
+    /// Returns amount of bytes that can be sent in current interval
+    ///
+    /// Returns `None` if the limiter is unlimited
+    pub fn remaining(&self) -> Option<usize> {
+        match self.state {
+            State::Unlimited => None,
+            State::Idle => Some(self.max_rate.bytes()),
+            State::Limiting if self.max_rate.bytes() == 0 => Some(0),
+            State::Running(_) | State::Limiting if self.timer_expired() => {
+                Some(self.max_rate.bytes())
+            }
+            State::Running(n) => Some(n),
+            State::Limiting => Some(0),
+        }
+    }
+
     /// Forcefully restart the limiter from current time point
     pub fn restart(&mut self) -> Result<(), TimerError> {
         let new_duration = self.fit_timer_duration()?;
@@ -156,28 +180,57 @@ where
         let now = self.clock.now();
         let duration = *self.max_rate.interval();
 
-        while self.timer_end_time < now {
+        while self.timer_end_time <= now {
             self.timer_end_time = self
                 .timer_end_time
                 .checked_add(duration)
                 .ok_or(TimerError::Overflow)?;
         }
 
-        Ok(self.timer_end_time.duration_since(now))
+        Ok(self.clock.duration_between(now, self.timer_end_time))
     }
 }
 
-#[cfg(all(test, feature = "std"))]
+impl<'clk, Clk> ByteRateLimiter for PollingByteRateLimiter<'clk, Clk>
+where
+    Clk: Clock,
+{
+    fn can_send(&self) -> bool {
+        self.can_send()
+    }
+
+    fn send(&mut self) -> Result<bool, TimerError> {
+        self.send()
+    }
+
+    fn duration_until_reset(&self) -> Option<Duration> {
+        self.duration_until_reset()
+    }
+
+    fn rate_limit(&self) -> Option<ByteRate> {
+        if let State::Unlimited = self.state {
+            return None;
+        }
+
+        Some(self.max_rate.clone())
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        self.remaining()
+    }
+}
+
+#[cfg(test)]
 mod tests {
     use core::time::Duration;
 
-    use crate::{byte_rate::rate::ByteRate, clock::StdClock};
+    use crate::{byte_rate::rate::ByteRate, clock::MockClock};
 
     use super::PollingByteRateLimiter;
 
     #[test]
     fn unlimited() {
-        let clock = StdClock;
+        let clock = MockClock::new();
         let max_rate = ByteRate::new(10, Duration::ZERO);
         let mut limiter = PollingByteRateLimiter::new(max_rate, &clock);
 
@@ -189,7 +242,7 @@ mod tests {
 
     #[test]
     fn limited() {
-        let clock = StdClock;
+        let clock = MockClock::new();
         let max_rate = ByteRate::new(0, Duration::from_secs(1));
         let limiter = PollingByteRateLimiter::new(max_rate, &clock);
 
@@ -200,7 +253,7 @@ mod tests {
     fn limit_activated() {
         const LIMIT: usize = 10;
 
-        let clock = StdClock;
+        let clock = MockClock::new();
         let max_rate = ByteRate::new(LIMIT, Duration::from_secs(1));
         let mut limiter = PollingByteRateLimiter::new(max_rate, &clock);
 
@@ -215,11 +268,67 @@ mod tests {
         assert!(!limiter.can_send());
     }
 
+    #[test]
+    fn remaining_budget() {
+        const LIMIT: usize = 3;
+
+        let clock = MockClock::new();
+        let max_rate = ByteRate::new(LIMIT, Duration::from_secs(1));
+        let mut limiter = PollingByteRateLimiter::new(max_rate, &clock);
+        assert_eq!(limiter.configured_rate().bytes(), LIMIT);
+        assert_eq!(limiter.remaining(), Some(LIMIT));
+
+        limiter.send().unwrap();
+        assert_eq!(limiter.remaining(), Some(LIMIT - 1));
+
+        limiter.send().unwrap();
+        limiter.send().unwrap();
+        assert_eq!(limiter.remaining(), Some(0));
+
+        clock.advance(limiter.duration_until_reset().unwrap());
+        assert_eq!(limiter.remaining(), Some(LIMIT));
+
+        limiter.set_byte_rate(ByteRate::new(0, Duration::from_secs(1)));
+        assert_eq!(limiter.remaining(), Some(0));
+
+        limiter.set_byte_rate(ByteRate::new(LIMIT, Duration::ZERO));
+        assert_eq!(limiter.remaining(), None);
+    }
+
+    #[test]
+    fn single_byte_over_long_interval() {
+        let clock = MockClock::new();
+        let max_rate = ByteRate::new(1, Duration::from_millis(2500));
+        let mut limiter = PollingByteRateLimiter::new(max_rate, &clock);
+
+        assert!(!limiter.send().unwrap());
+        assert!(!limiter.can_send());
+        assert_eq!(
+            limiter.duration_until_reset(),
+            Some(Duration::from_millis(2500))
+        );
+
+        clock.advance(Duration::from_secs(2));
+        assert!(!limiter.can_send());
+        assert_eq!(
+            limiter.duration_until_reset(),
+            Some(Duration::from_millis(500))
+        );
+
+        clock.advance(Duration::from_millis(500));
+        assert!(limiter.can_send());
+        assert!(!limiter.send().unwrap());
+        assert_eq!(
+            limiter.duration_until_reset(),
+            Some(Duration::from_millis(2500))
+        );
+    }
+
     #[test]
     fn restart_resets_limit() {
         const LIMIT: usize = 10;
 
-        let clock = StdClock;
+        let clock = MockClock::new();
         let max_rate = ByteRate::new(LIMIT, Duration::from_secs(1));
         let mut limiter = PollingByteRateLimiter::new(max_rate, &clock);
 
@@ -246,7 +355,7 @@ mod tests {
     fn restart_on_timer() {
         const LIMIT: usize = 10;
 
-        let clock = StdClock;
+        let clock = MockClock::new();
         let max_rate = ByteRate::new(LIMIT, Duration::from_secs(1));
         let mut limiter = PollingByteRateLimiter::new(max_rate, &clock);
 
@@ -256,7 +365,7 @@ mod tests {
             assert!(limiter.can_send());
         }
 
-        std::thread::sleep(limiter.duration_until_reset().unwrap());
+        clock.advance(limiter.duration_until_reset().unwrap());
 
         // Limit reset, we should be able to send new bytes
         for _ in 0..(LIMIT - 1) {
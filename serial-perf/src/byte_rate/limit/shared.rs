@@ -0,0 +1,67 @@
+use core::time::Duration;
+
+use crate::{byte_rate::rate::ByteRate, clock::TimerError};
+
+use super::ByteRateLimiter;
+
+/// Limiter that makes `ByteRateSerialLimiter` draw both read and written bytes from a single budget of `Inner`
+///
+/// Models a shared medium, e.g. half-duplex bus, where receiving uses up the same capacity as sending.
+/// Reading is not done while the budget is exhausted, so bytes wait in the underlying serial.
+pub struct SharedByteRateLimiter<Inner> {
+    inner: Inner,
+}
+
+impl<Inner> SharedByteRateLimiter<Inner>
+where
+    Inner: ByteRateLimiter,
+{
+    pub fn new(inner: Inner) -> Self {
+        Self { inner }
+    }
+
+    /// Returns remaining budget of both directions combined.
+    /// Returns None if limiter is unlimited or the budget is unknown
+    pub fn remaining(&self) -> Option<usize> {
+        self.inner.remaining()
+    }
+
+    /// Returns reference to the wrapped limiter
+    pub fn inner(&self) -> &Inner {
+        &self.inner
+    }
+
+    /// Returns mutable reference to the wrapped limiter, e.g. to change the rate
+    pub fn inner_mut(&mut self) -> &mut Inner {
+        &mut self.inner
+    }
+}
+
+impl<Inner> ByteRateLimiter for SharedByteRateLimiter<Inner>
+where
+    Inner: ByteRateLimiter,
+{
+    fn can_send(&self) -> bool {
+        self.inner.can_send()
+    }
+
+    fn send(&mut self) -> Result<bool, TimerError> {
+        self.inner.send()
+    }
+
+    fn duration_until_reset(&self) -> Option<Duration> {
+        self.inner.duration_until_reset()
+    }
+
+    fn rate_limit(&self) -> Option<ByteRate> {
+        self.inner.rate_limit()
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        self.inner.remaining()
+    }
+
+    fn limits_read(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,297 @@
+use core::time::Duration;
+
+use crate::byte_rate::rate::ByteRate;
+use crate::clock::{Clock, ClockExt, Instant, TimerError};
+
+use super::ByteRateLimiter;
+
+/// Limiter that uses token bucket algorithm.
+///
+/// Tokens accrue continuously at `max_rate` up to the bucket capacity and each byte sent takes one token.
+/// That spreads sending evenly over the interval instead of sending whole budget at once and waiting
+/// for the next interval as `PollingByteRateLimiter` does. The capacity controls the allowed burst size.
+pub struct TokenBucketByteRateLimiter<'clk, Clk>
+where
+    Clk: Clock,
+{
+    max_rate: ByteRate,
+    capacity: usize,
+    /// Whether the capacity follows the bytes of `max_rate`, i.e. not set explicitly
+    rate_capacity: bool,
+    tokens: usize,
+
+    clock: &'clk Clk,
+    last_refill: Clk::Instant,
+}
+
+impl<'clk, Clk> TokenBucketByteRateLimiter<'clk, Clk>
+where
+    Clk: Clock,
+{
+    /// Creates new rate limiter with capacity equal to the bytes of `max_rate`
+    pub fn new(max_rate: ByteRate, clock: &'clk Clk) -> Self {
+        let capacity = max_rate.bytes();
+        Self {
+            rate_capacity: true,
+            ..Self::with_capacity(max_rate, capacity, clock)
+        }
+    }
+
+    /// Creates new rate limiter with specified burst capacity. The bucket is full initially
+    pub fn with_capacity(max_rate: ByteRate, capacity: usize, clock: &'clk Clk) -> Self {
+        Self {
+            max_rate,
+            capacity,
+            rate_capacity: false,
+            tokens: capacity,
+            clock,
+            last_refill: clock.now(),
+        }
+    }
+
+    /// Sets new byte rate and resets the limiter to initial state(full bucket)
+    ///
+    /// The capacity is changed to the bytes of `max_rate` too, unless it was set with `with_capacity`.
+    pub fn set_byte_rate(&mut self, max_rate: ByteRate) {
+        if self.rate_capacity {
+            self.capacity = max_rate.bytes();
+        }
+        self.max_rate = max_rate;
+        self.tokens = self.capacity;
+        self.last_refill = self.clock.now();
+    }
+
+    /// Returns burst capacity of the limiter
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Check if sending is possible now but doesn't assume you will send byte if it is true
+    ///
+    /// Use `send` to notify limiter about send
+    pub fn can_send(&self) -> bool {
+        if self.is_unlimited() {
+            return true;
+        }
+
+        self.available_tokens(self.clock.now()) > 0
+    }
+
+    /// Notify that you have sent byte successfully, returns true if limit NOT reached yet or false otherwise
+    ///
+    /// Always check for `can_send` before otherwise it send will do nothing if you try to send more than allowed.
+    /// Can return overflow error if clock instant cannot be advanced
+    pub fn send(&mut self) -> Result<bool, TimerError> {
+        if self.is_unlimited() {
+            return Ok(true);
+        }
+
+        self.refill()?;
+
+        if self.tokens == 0 {
+            return Ok(false);
+        }
+
+        self.tokens -= 1;
+        Ok(self.tokens > 0)
+    }
+
+    /// Gets time until a new byte can be sent
+    /// Returns None if limiter is unlimited or no bytes can be sent ever
+    pub fn duration_until_reset(&self) -> Option<Duration> {
+        if self.is_unlimited() || self.max_rate.bytes() == 0 {
+            return None;
+        }
+
+        let now = self.clock.now();
+        if self.available_tokens(now) > 0 {
+            return Some(Duration::ZERO);
+        }
+
+        let bytes = self.max_rate.bytes() as u128;
+        let token_ns = self.max_rate.interval().as_nanos().div_ceil(bytes);
+        let elapsed_ns = self
+            .clock
+            .duration_between(self.last_refill, now)
+            .as_nanos();
+        let left_ns = token_ns.saturating_sub(elapsed_ns);
+
+        Some(Duration::from_nanos(
+            u64::try_from(left_ns).unwrap_or(u64::MAX),
+        ))
+    }
+
+    /// Returns amount of bytes that can be sent now.
+    /// Returns None if limiter is unlimited
+    pub fn remaining(&self) -> Option<usize> {
+        if self.is_unlimited() {
+            return None;
+        }
+
+        Some(self.available_tokens(self.clock.now()))
+    }
+
+    fn is_unlimited(&self) -> bool {
+        self.max_rate.interval().is_zero()
+    }
+
+    /// Returns amount of tokens accrued since last refill
+    fn accrued_tokens(&self, now: Clk::Instant) -> u128 {
+        let elapsed_ns = self
+            .clock
+            .duration_between(self.last_refill, now)
+            .as_nanos();
+        let interval_ns = self.max_rate.interval().as_nanos();
+
+        elapsed_ns * self.max_rate.bytes() as u128 / interval_ns
+    }
+
+    fn available_tokens(&self, now: Clk::Instant) -> usize {
+        let accrued = usize::try_from(self.accrued_tokens(now)).unwrap_or(usize::MAX);
+        self.tokens.saturating_add(accrued).min(self.capacity)
+    }
+
+    fn refill(&mut self) -> Result<(), TimerError> {
+        let now = self.clock.now();
+        let accrued = self.accrued_tokens(now);
+        if accrued == 0 {
+            return Ok(());
+        }
+
+        let available = self.available_tokens(now);
+        if available == self.capacity {
+            // Tokens above capacity are lost, start accruing from now
+            self.last_refill = now;
+        } else {
+            // Keep the fraction of the next token
+            let interval_ns = self.max_rate.interval().as_nanos();
+            let spent_ns = accrued * interval_ns / self.max_rate.bytes() as u128;
+            let spent = Duration::from_nanos(u64::try_from(spent_ns).unwrap_or(u64::MAX));
+            self.last_refill = self
+                .last_refill
+                .checked_add(spent)
+                .ok_or(TimerError::Overflow)?;
+        }
+
+        self.tokens = available;
+        Ok(())
+    }
+}
+
+impl<'clk, Clk> ByteRateLimiter for TokenBucketByteRateLimiter<'clk, Clk>
+where
+    Clk: Clock,
+{
+    fn can_send(&self) -> bool {
+        self.can_send()
+    }
+
+    fn send(&mut self) -> Result<bool, TimerError> {
+        self.send()
+    }
+
+    fn duration_until_reset(&self) -> Option<Duration> {
+        self.duration_until_reset()
+    }
+
+    fn rate_limit(&self) -> Option<ByteRate> {
+        if self.is_unlimited() {
+            return None;
+        }
+
+        Some(self.max_rate.clone())
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        self.remaining()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use crate::{byte_rate::rate::ByteRate, clock::MockClock};
+
+    use super::TokenBucketByteRateLimiter;
+
+    #[test]
+    fn unlimited() {
+        let clock = MockClock::new();
+        let max_rate = ByteRate::new(10, Duration::ZERO);
+        let mut limiter = TokenBucketByteRateLimiter::new(max_rate, &clock);
+
+        const COUNT: usize = 1_000_000;
+        for _ in 0..COUNT {
+            assert!(limiter.send().unwrap())
+        }
+    }
+
+    #[test]
+    fn limited() {
+        let clock = MockClock::new();
+        let max_rate = ByteRate::new(0, Duration::from_secs(1));
+        let limiter = TokenBucketByteRateLimiter::new(max_rate, &clock);
+
+        assert!(!limiter.can_send());
+        assert!(limiter.duration_until_reset().is_none());
+    }
+
+    #[test]
+    fn burst_limited_by_capacity() {
+        const CAPACITY: usize = 5;
+
+        let clock = MockClock::new();
+        let max_rate = ByteRate::new(10, Duration::from_secs(10));
+        let mut limiter = TokenBucketByteRateLimiter::with_capacity(max_rate, CAPACITY, &clock);
+
+        for _ in 0..(CAPACITY - 1) {
+            assert!(limiter.send().unwrap());
+            assert!(limiter.can_send());
+        }
+
+        assert!(!limiter.send().unwrap());
+        assert!(!limiter.can_send());
+        assert!(limiter.duration_until_reset().unwrap() > Duration::ZERO);
+    }
+
+    #[test]
+    fn tokens_accrue() {
+        let clock = MockClock::new();
+        let max_rate = ByteRate::new(100, Duration::from_secs(1));
+        let mut limiter = TokenBucketByteRateLimiter::with_capacity(max_rate, 1, &clock);
+
+        assert!(!limiter.send().unwrap());
+        assert!(!limiter.can_send());
+
+        assert_eq!(
+            limiter.duration_until_reset(),
+            Some(Duration::from_millis(10))
+        );
+        clock.advance(Duration::from_millis(9));
+        assert!(!limiter.can_send());
+        clock.advance(Duration::from_millis(1));
+
+        assert!(limiter.can_send());
+        assert!(!limiter.send().unwrap());
+    }
+
+    #[test]
+    fn set_byte_rate_changes_capacity() {
+        let clock = MockClock::new();
+        let mut limiter =
+            TokenBucketByteRateLimiter::new(ByteRate::new(100, Duration::from_secs(1)), &clock);
+
+        limiter.set_byte_rate(ByteRate::new(10, Duration::from_secs(1)));
+        assert_eq!(limiter.capacity(), 10);
+        assert_eq!(limiter.remaining(), Some(10));
+
+        let mut limiter = TokenBucketByteRateLimiter::with_capacity(
+            ByteRate::new(100, Duration::from_secs(1)),
+            5,
+            &clock,
+        );
+        limiter.set_byte_rate(ByteRate::new(10, Duration::from_secs(1)));
+        assert_eq!(limiter.capacity(), 5);
+    }
+}
@@ -1,4 +1,9 @@
-use crate::{byte_rate::rate::ByteRate, clock::Clock};
+use core::time::Duration;
+
+use crate::{
+    byte_rate::rate::ByteRate,
+    clock::{Clock, ClockExt},
+};
 
 enum State<Clk>
 where
@@ -58,12 +63,27 @@ where
     pub fn byte_rate(&self) -> Option<ByteRate> {
         match self.state {
             State::Idle => None,
-            State::Measuring(start_time, bytes_sent) => {
-                Some(ByteRate::new(bytes_sent, self.clk.elapsed(start_time)))
-            }
+            State::Measuring(start_time, bytes_sent) => Some(ByteRate::new(
+                bytes_sent,
+                self.clk.elapsed_since(start_time),
+            )),
         }
     }
 
+    /// Returns the time elapsed since the measurer was started, `None` if the timer is not running
+    pub fn elapsed(&self) -> Option<Duration> {
+        match self.state {
+            State::Idle => None,
+            State::Measuring(start_time, _) => Some(self.clk.elapsed_since(start_time)),
+        }
+    }
+
+    /// Stops the measurer, resetting all results. The timer starts again on the next byte
+    pub fn reset(&mut self) {
+        self.state = State::Idle;
+    }
+
+    /// Returns true if the timer is running
     pub fn is_started(&self) -> bool {
         if let State::Idle = self.state {
             return false;
@@ -72,3 +92,40 @@ where
         true
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use super::AverageByteRateMeasurer;
+    use crate::clock::MockClock;
+
+    #[test]
+    fn idle() {
+        let clock = MockClock::new();
+        let measurer = AverageByteRateMeasurer::new(&clock);
+
+        assert!(!measurer.is_started());
+        assert_eq!(measurer.elapsed(), None);
+        assert!(measurer.byte_rate().is_none());
+    }
+
+    #[test]
+    fn measuring() {
+        let clock = MockClock::new();
+        let mut measurer = AverageByteRateMeasurer::new(&clock);
+
+        measurer.on_byte(10);
+        assert!(measurer.is_started());
+
+        clock.advance(Duration::from_millis(500));
+        measurer.on_byte(10);
+        assert_eq!(measurer.elapsed(), Some(Duration::from_millis(500)));
+        assert_eq!(measurer.byte_rate().unwrap().bytes_per_second(), Some(40));
+
+        measurer.reset();
+        assert!(!measurer.is_started());
+        assert_eq!(measurer.elapsed(), None);
+        assert!(measurer.byte_rate().is_none());
+    }
+}
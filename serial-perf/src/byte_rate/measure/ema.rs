@@ -0,0 +1,170 @@
+use core::time::Duration;
+
+use crate::byte_rate::rate::ByteRate;
+use crate::clock::{Clock, ClockExt, Instant, Timer, TimerError};
+
+/// Measures byte rate as an exponential moving average of the rates over sample intervals
+///
+/// Each time the sample interval passes, the rate over the interval is blended into the estimate:
+/// `estimate = alpha * rate + (1 - alpha) * estimate`. Intervals without any byte are blended as zero rate.
+///
+/// # Note
+/// Smaller `alpha` gives smoother output but reacts slower to changes.
+pub struct EmaByteRateMeasurer<'clk, Clk>
+where
+    Clk: Clock,
+{
+    alpha: f32,
+    /// Smoothed amount of bytes per interval, `None` until first interval passed
+    estimate: Option<f32>,
+    current_rate: ByteRate,
+
+    clock: &'clk Clk,
+    timer: Timer<'clk, Clk>,
+    timer_end_time: Clk::Instant,
+}
+
+impl<'clk, Clk> EmaByteRateMeasurer<'clk, Clk>
+where
+    Clk: Clock,
+{
+    /// Create a new measurer with the given clock, sample interval and smoothing factor.
+    ///
+    /// The `alpha` is clamped to `[0.0, 1.0]`, where 1.0 means no smoothing.
+    pub fn new(clk: &'clk Clk, interval: Duration, alpha: f32) -> Self {
+        let mut timer = Timer::new(clk);
+        timer.try_start(interval).ok();
+
+        let now = clk.now();
+
+        Self {
+            alpha: alpha.clamp(0.0, 1.0),
+            estimate: None,
+            current_rate: ByteRate::new(0, interval),
+            clock: clk,
+            timer,
+            timer_end_time: now.checked_add(interval).unwrap_or(now),
+        }
+    }
+
+    /// Starts or restarts the measurer, resetting all results
+    pub fn reset(&mut self) {
+        self.estimate = None;
+        self.current_rate.set_bytes(0);
+        self.timer_end_time = self.clock.now();
+        self.restart().ok();
+    }
+
+    /// Handles `amount` of bytes received/sent
+    pub fn on_byte(&mut self, amount: usize) {
+        self.update();
+
+        let current_bytes = self.current_rate.bytes();
+        self.current_rate
+            .set_bytes(current_bytes.saturating_add(amount));
+    }
+
+    /// Blends passed intervals into the estimate if the sample interval expired.
+    ///
+    /// Called automatically by `on_byte`, call it periodically if bytes may stop coming.
+    pub fn update(&mut self) {
+        if !self.timer.is_expired().unwrap_or(true) {
+            return;
+        }
+
+        let passed = self.restart().unwrap_or(1);
+
+        self.blend(self.current_rate.bytes() as f32);
+        for _ in 1..passed {
+            self.blend(0.0);
+
+            if self.estimate.unwrap_or_default() < f32::EPSILON {
+                self.estimate = Some(0.0);
+                break;
+            }
+        }
+
+        self.current_rate.set_bytes(0);
+    }
+
+    /// Returns the smoothed `ByteRate` over the sample interval if at least one interval passed
+    pub fn byte_rate(&self) -> Option<ByteRate> {
+        let estimate = self.estimate?;
+        let bytes = (estimate + 0.5) as usize;
+
+        Some(ByteRate::new(bytes, *self.current_rate.interval()))
+    }
+
+    /// Returns smoothing factor
+    pub fn alpha(&self) -> f32 {
+        self.alpha
+    }
+
+    fn blend(&mut self, bytes: f32) {
+        let estimate = match self.estimate {
+            Some(estimate) => self.alpha * bytes + (1.0 - self.alpha) * estimate,
+            None => bytes,
+        };
+
+        self.estimate = Some(estimate);
+    }
+
+    /// Restarts timer for next interval, returns amount of intervals passed
+    fn restart(&mut self) -> Result<usize, TimerError> {
+        let (new_duration, passed) = self.fit_timer_duration()?;
+        self.timer.try_start(new_duration)?;
+
+        Ok(passed)
+    }
+
+    fn fit_timer_duration(&mut self) -> Result<(Duration, usize), TimerError> {
+        let now = self.clock.now();
+        let duration = *self.current_rate.interval();
+        let mut passed = 0;
+
+        while self.timer_end_time <= now {
+            self.timer_end_time = self
+                .timer_end_time
+                .checked_add(duration)
+                .ok_or(TimerError::Overflow)?;
+            passed += 1;
+        }
+
+        Ok((
+            self.clock.duration_between(now, self.timer_end_time),
+            passed,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use crate::clock::MockClock;
+
+    use super::EmaByteRateMeasurer;
+
+    #[test]
+    fn no_rate_before_first_interval() {
+        let clock = MockClock::new();
+        let mut measurer = EmaByteRateMeasurer::new(&clock, Duration::from_secs(10), 0.5);
+
+        measurer.on_byte(100);
+        assert!(measurer.byte_rate().is_none());
+    }
+
+    #[test]
+    fn first_interval_sets_estimate() {
+        let clock = MockClock::new();
+        let mut measurer = EmaByteRateMeasurer::new(&clock, Duration::from_millis(50), 0.5);
+
+        measurer.on_byte(100);
+        clock.advance(Duration::from_millis(60));
+        measurer.update();
+
+        let rate = measurer.byte_rate().unwrap();
+        assert_eq!(rate.bytes(), 100);
+        assert_eq!(*rate.interval(), Duration::from_millis(50));
+    }
+}
@@ -1,8 +1,7 @@
 use core::time::Duration;
 
-use embedded_timers::instant::Instant;
-
-use crate::{byte_rate::rate::ByteRate, clock::Clock, clock::Timer, clock::TimerError};
+use crate::byte_rate::rate::ByteRate;
+use crate::clock::{Clock, ClockExt, Instant, TimerError};
 
 /// Measurers byte rate of a stream of bytes with specified intervals between resets and starts again
 ///
@@ -14,10 +13,18 @@ where
 {
     current_rate: ByteRate,
     output_rate: ByteRate,
+    /// Whether amount of bytes in `current_rate` saturated at `usize::MAX`
+    current_saturated: bool,
+    /// Whether amount of bytes in `output_rate` saturated at `usize::MAX`
+    output_saturated: bool,
 
     clock: &'clk Clk,
-    timer: Timer<'clk, Clk>,
-    timer_end_time: Clk::Instant,
+    /// End of the current interval
+    interval_end_time: Clk::Instant,
+    /// End of the interval `output_rate` is measured over
+    output_end_time: Clk::Instant,
+    /// Time point intervals are aligned to, `None` to align them to the reset
+    anchor: Option<Clk::Instant>,
 }
 
 impl<'clk, Clk> IntervalByteRateMeasurer<'clk, Clk>
@@ -27,40 +34,91 @@ where
     /// Create a new measurer with the given clock
     pub fn new(clk: &'clk Clk, interval: Duration) -> Self {
         let rate = ByteRate::new(0, interval);
-
-        let mut timer = Timer::new(clk);
-        timer.try_start(interval).ok();
+        let now = clk.now();
 
         Self {
             clock: clk,
             current_rate: rate.clone(),
             output_rate: rate,
-            timer,
-            timer_end_time: clk.now(),
+            current_saturated: false,
+            output_saturated: false,
+            interval_end_time: now.checked_add(interval).unwrap_or(now),
+            output_end_time: now,
+            anchor: None,
         }
     }
 
+    /// Create a new measurer with intervals ending at `anchor` plus multiples of `interval`, also after reset.
+    ///
+    /// Measurers created with the same `anchor` and `interval` roll over on the same boundaries, e.g. TX and RX
+    /// rates are measured over the same time windows. Usually `anchor` is a time point in the past, if it's
+    /// in the future the first interval ends at it.
+    pub fn new_anchored(clk: &'clk Clk, interval: Duration, anchor: Clk::Instant) -> Self {
+        let mut out = Self::new(clk, interval);
+        out.anchor = Some(anchor);
+        out.reset();
+        out
+    }
+
     /// Starts or restarts the measurer, resetting all results
     pub fn reset(&mut self) {
         self.current_rate.set_bytes(0);
         self.output_rate = self.current_rate.clone();
-        self.timer_end_time = self.clock.now();
+        self.current_saturated = false;
+        self.output_saturated = false;
+
+        let now = self.clock.now();
+        self.output_end_time = now;
+
+        match self.anchor {
+            Some(anchor) => {
+                self.interval_end_time = anchor;
+                self.fit_interval_end(now).ok();
+            }
+            None => {
+                self.interval_end_time = now
+                    .checked_add(*self.current_rate.interval())
+                    .unwrap_or(now);
+            }
+        }
+    }
+
+    /// Changes interval between resets, resetting all results
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.current_rate.set_interval(interval);
+        self.reset();
     }
 
-    /// Handles `amount` of bytes received/sent
+    /// Handles `amount` of bytes received/sent, returns `true` if the interval rolled over and `byte_rate` is updated
+    pub fn on_byte(&mut self, amount: usize) -> bool {
+        self.on_byte_at(amount, self.clock.now())
+    }
+
+    /// Same as `on_byte`, but uses `now` instead of reading the clock, e.g. a timestamp the caller already has.
     ///
-    /// # Note
-    /// Starts the timer if not started yet.
-    pub fn on_byte(&mut self, amount: usize) {
-        if self.timer.is_expired().unwrap_or(true) {
+    /// The timestamps must not go backwards.
+    pub fn on_byte_at(&mut self, amount: usize, now: Clk::Instant) -> bool {
+        let rolled_over = self.interval_end_time <= now;
+        if rolled_over {
             self.output_rate = self.current_rate.clone();
+            self.output_saturated = self.current_saturated;
             self.current_rate.set_bytes(0);
+            self.current_saturated = false;
+            self.output_end_time = self.interval_end_time;
 
-            self.restart().ok();
+            self.fit_interval_end(now).ok();
         }
 
         let current_bytes = self.current_rate.bytes();
-        self.current_rate.set_bytes(current_bytes + amount);
+        let (new_bytes, overflowed) = current_bytes.overflowing_add(amount);
+        if overflowed {
+            self.current_saturated = true;
+            self.current_rate.set_bytes(usize::MAX);
+        } else {
+            self.current_rate.set_bytes(new_bytes);
+        }
+
+        rolled_over
     }
 
     /// Returns the current `ByteRate` if the timer is running
@@ -68,25 +126,153 @@ where
         &self.output_rate
     }
 
+    /// Returns the time point where the interval of `byte_rate` ended, the time of the last reset if no interval ended yet.
+    ///
+    /// Intervals stay aligned to the reset, so after idle intervals it's not the time `byte_rate` was updated.
+    pub fn last_interval_end(&self) -> Clk::Instant {
+        self.output_end_time
+    }
+
+    /// Returns the time point where the current interval ends
+    pub fn current_interval_end(&self) -> Clk::Instant {
+        self.interval_end_time
+    }
+
+    /// Returns `true` if amount of bytes in `byte_rate` is pinned at `usize::MAX`, so the real rate is higher
+    pub fn is_saturated(&self) -> bool {
+        self.output_saturated
+    }
+
     /// Forcefully restart the measurer from current time point
     pub fn restart(&mut self) -> Result<(), TimerError> {
-        let new_duration = self.fit_timer_duration()?;
-        self.timer.try_start(new_duration)?;
+        let now = self.clock.now();
+        self.fit_interval_end(now)?;
 
         Ok(())
     }
 
-    fn fit_timer_duration(&mut self) -> Result<Duration, TimerError> {
-        let now = self.clock.now();
+    /// Moves the end of the current interval past `now` keeping intervals aligned
+    fn fit_interval_end(&mut self, now: Clk::Instant) -> Result<Duration, TimerError> {
         let duration = *self.current_rate.interval();
+        if duration.is_zero() {
+            self.interval_end_time = now;
+            return Ok(Duration::ZERO);
+        }
 
-        while self.timer_end_time < now {
-            self.timer_end_time = self
-                .timer_end_time
+        while self.interval_end_time <= now {
+            self.interval_end_time = self
+                .interval_end_time
                 .checked_add(duration)
                 .ok_or(TimerError::Overflow)?;
         }
 
-        Ok(self.timer_end_time.duration_since(now))
+        Ok(self.clock.duration_between(now, self.interval_end_time))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use crate::clock::{Clock, Instant, MockClock};
+
+    use super::IntervalByteRateMeasurer;
+
+    #[test]
+    fn interval_changed() {
+        let clock = MockClock::new();
+        let mut measurer = IntervalByteRateMeasurer::new(&clock, Duration::from_secs(1));
+        measurer.on_byte(10);
+
+        measurer.set_interval(Duration::from_secs(10));
+        assert_eq!(measurer.byte_rate().bytes(), 0);
+        assert_eq!(*measurer.byte_rate().interval(), Duration::from_secs(10));
+
+        measurer.on_byte(10);
+        clock.advance(Duration::from_secs(1));
+        assert!(!measurer.on_byte(10));
+
+        clock.advance(Duration::from_secs(9));
+        assert!(measurer.on_byte(0));
+        assert_eq!(measurer.byte_rate().bytes(), 20);
+    }
+
+    #[test]
+    fn saturates_at_max() {
+        let clock = MockClock::new();
+        let mut measurer = IntervalByteRateMeasurer::new(&clock, Duration::from_secs(1));
+
+        measurer.on_byte(usize::MAX);
+        measurer.on_byte(1);
+        assert!(!measurer.is_saturated());
+
+        clock.advance(Duration::from_secs(1));
+        assert!(measurer.on_byte(0));
+        assert_eq!(measurer.byte_rate().bytes(), usize::MAX);
+        assert!(measurer.is_saturated());
+
+        clock.advance(Duration::from_secs(1));
+        assert!(measurer.on_byte(0));
+        assert_eq!(measurer.byte_rate().bytes(), 0);
+        assert!(!measurer.is_saturated());
+    }
+
+    #[test]
+    fn supplied_timestamps() {
+        let clock = MockClock::new();
+        let mut measurer = IntervalByteRateMeasurer::new(&clock, Duration::from_secs(1));
+        let start = clock.now();
+        let at = |ms| start.checked_add(Duration::from_millis(ms)).unwrap();
+
+        assert!(!measurer.on_byte_at(10, at(500)));
+        assert!(!measurer.on_byte_at(10, at(999)));
+        assert!(measurer.on_byte_at(5, at(1000)));
+        assert_eq!(measurer.byte_rate().bytes(), 20);
+
+        // An idle interval is skipped, the next one starts aligned
+        assert!(measurer.on_byte_at(0, at(3500)));
+        assert_eq!(measurer.byte_rate().bytes(), 5);
+        assert!(!measurer.on_byte_at(1, at(3999)));
+        assert!(measurer.on_byte_at(0, at(4000)));
+        assert_eq!(measurer.byte_rate().bytes(), 1);
+    }
+
+    #[test]
+    fn interval_boundaries() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let at = |ms| start.checked_add(Duration::from_millis(ms)).unwrap();
+        let mut measurer = IntervalByteRateMeasurer::new(&clock, Duration::from_secs(1));
+
+        assert!(measurer.last_interval_end() == start);
+        assert!(measurer.current_interval_end() == at(1000));
+
+        // Rolled over late, after idle intervals
+        assert!(measurer.on_byte_at(1, at(2500)));
+        assert!(measurer.last_interval_end() == at(1000));
+        assert!(measurer.current_interval_end() == at(3000));
+
+        assert!(measurer.on_byte_at(0, at(3000)));
+        assert!(measurer.last_interval_end() == at(3000));
+        assert!(measurer.current_interval_end() == at(4000));
+    }
+
+    #[test]
+    fn anchored_measurers_aligned() {
+        let clock = MockClock::new();
+        let anchor = clock.now();
+        let at = |ms| anchor.checked_add(Duration::from_millis(ms)).unwrap();
+
+        let tx = IntervalByteRateMeasurer::new_anchored(&clock, Duration::from_secs(1), anchor);
+        clock.advance(Duration::from_millis(300));
+        let mut rx = IntervalByteRateMeasurer::new_anchored(&clock, Duration::from_secs(1), anchor);
+
+        assert!(tx.current_interval_end() == at(1000));
+        assert!(rx.current_interval_end() == at(1000));
+
+        // Reset keeps the alignment
+        clock.advance(Duration::from_millis(1500));
+        rx.reset();
+        assert!(rx.current_interval_end() == at(2000));
     }
 }
@@ -7,3 +7,9 @@ pub use avg::AverageByteRateMeasurer;
 
 mod interval;
 pub use interval::IntervalByteRateMeasurer;
+
+mod sliding_window;
+pub use sliding_window::SlidingWindowByteRateMeasurer;
+
+mod ema;
+pub use ema::EmaByteRateMeasurer;
@@ -0,0 +1,167 @@
+use core::time::Duration;
+
+use crate::byte_rate::rate::ByteRate;
+use crate::clock::{Clock, ClockExt, Instant};
+
+/// Measures byte rate over a sliding window divided into `N` buckets
+///
+/// The window moves forward by one bucket each time the bucket interval (`window / N`) passes,
+/// so the output changes smoothly instead of resetting completely as `IntervalByteRateMeasurer` does.
+///
+/// # Note
+/// The rate is always reported over the full window duration, even if the measurer was started less than a window ago.
+pub struct SlidingWindowByteRateMeasurer<'clk, Clk, const N: usize>
+where
+    Clk: Clock,
+{
+    buckets: [usize; N],
+    current_bucket: usize,
+
+    window: Duration,
+    bucket_interval: Duration,
+
+    clock: &'clk Clk,
+    bucket_end_time: Clk::Instant,
+}
+
+impl<'clk, Clk, const N: usize> SlidingWindowByteRateMeasurer<'clk, Clk, N>
+where
+    Clk: Clock,
+{
+    const NON_EMPTY: () = assert!(N > 0, "sliding window must have at least one bucket");
+
+    /// Create a new measurer with the given clock and window divided into `N` buckets
+    pub fn new(clk: &'clk Clk, window: Duration) -> Self {
+        let () = Self::NON_EMPTY;
+
+        let bucket_interval = window / N as u32;
+        let now = clk.now();
+
+        Self {
+            buckets: [0; N],
+            current_bucket: 0,
+            window,
+            bucket_interval,
+            clock: clk,
+            bucket_end_time: now.checked_add(bucket_interval).unwrap_or(now),
+        }
+    }
+
+    /// Restarts the measurer from current time point, resetting all results
+    pub fn reset(&mut self) {
+        let now = self.clock.now();
+
+        self.buckets = [0; N];
+        self.current_bucket = 0;
+        self.bucket_end_time = now.checked_add(self.bucket_interval).unwrap_or(now);
+    }
+
+    /// Handles `amount` of bytes received/sent
+    pub fn on_byte(&mut self, amount: usize) {
+        self.advance();
+
+        let bucket = &mut self.buckets[self.current_bucket];
+        *bucket = bucket.saturating_add(amount);
+    }
+
+    /// Returns the `ByteRate` over the whole window
+    pub fn byte_rate(&self) -> ByteRate {
+        let expired = self.expired_buckets(self.clock.now());
+
+        let bytes = if expired >= N {
+            0
+        } else {
+            // Offset `N` is the current bucket, the oldest expired buckets are dropped
+            (expired + 1..=N)
+                .map(|age| self.buckets[(self.current_bucket + age) % N])
+                .fold(0usize, |acc, bytes| acc.saturating_add(bytes))
+        };
+
+        ByteRate::new(bytes, self.window)
+    }
+
+    /// Returns the window the rate is measured over
+    pub fn window(&self) -> &Duration {
+        &self.window
+    }
+
+    /// Returns amount of buckets passed since the current bucket ended
+    fn expired_buckets(&self, now: Clk::Instant) -> usize {
+        if now < self.bucket_end_time {
+            return 0;
+        }
+
+        let interval_ns = self.bucket_interval.as_nanos();
+        if interval_ns == 0 {
+            return N;
+        }
+
+        let behind_ns = self
+            .clock
+            .duration_between(self.bucket_end_time, now)
+            .as_nanos();
+        usize::try_from(behind_ns / interval_ns + 1).unwrap_or(usize::MAX)
+    }
+
+    /// Moves the window forward to current time point, clearing buckets that went out of it
+    fn advance(&mut self) {
+        let now = self.clock.now();
+        let expired = self.expired_buckets(now);
+        if expired == 0 {
+            return;
+        }
+
+        for _ in 0..expired.min(N) {
+            self.current_bucket = (self.current_bucket + 1) % N;
+            self.buckets[self.current_bucket] = 0;
+        }
+
+        let skipped = u64::try_from(self.bucket_interval.as_nanos() * expired as u128)
+            .ok()
+            .map(Duration::from_nanos);
+
+        self.bucket_end_time = skipped
+            .and_then(|skipped| self.bucket_end_time.checked_add(skipped))
+            .filter(|end_time| now < *end_time)
+            .or_else(|| now.checked_add(self.bucket_interval))
+            .unwrap_or(now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use crate::clock::MockClock;
+
+    use super::SlidingWindowByteRateMeasurer;
+
+    #[test]
+    fn counts_bytes_in_window() {
+        let clock = MockClock::new();
+        let mut measurer =
+            SlidingWindowByteRateMeasurer::<_, 4>::new(&clock, Duration::from_secs(10));
+
+        measurer.on_byte(10);
+        measurer.on_byte(5);
+
+        let rate = measurer.byte_rate();
+        assert_eq!(rate.bytes(), 15);
+        assert_eq!(*rate.interval(), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn old_buckets_leave_window() {
+        let clock = MockClock::new();
+        let mut measurer =
+            SlidingWindowByteRateMeasurer::<_, 2>::new(&clock, Duration::from_millis(100));
+
+        measurer.on_byte(10);
+        clock.advance(Duration::from_millis(120));
+
+        assert_eq!(measurer.byte_rate().bytes(), 0);
+
+        measurer.on_byte(3);
+        assert_eq!(measurer.byte_rate().bytes(), 3);
+    }
+}
@@ -1,7 +1,30 @@
+use core::ops::{Add, Sub};
 use core::time::Duration;
 
+/// Accuracy of bytes per second calculation, see `ByteRate::bytes_per_second_with`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RateAccuracy {
+    /// Whole seconds, see `ByteRate::bytes_per_second_sec_accuracy`
+    Sec,
+    /// Milliseconds, see `ByteRate::bytes_per_second_ms_accuracy`
+    Milli,
+    /// Microseconds, see `ByteRate::bytes_per_second_us_accuracy`
+    Micro,
+    /// Nanoseconds, see `ByteRate::bytes_per_second_ns_accuracy`
+    Nano,
+    /// The most accurate one not overflowing, see `ByteRate::bytes_per_second`
+    #[default]
+    Auto,
+}
+
 /// Holds a data needed to calculate the byte rate.
-#[derive(Debug, Clone)]
+///
+/// Rates are equal if both bytes and interval are equal, e.g. 1 byte per second is not equal to 2 bytes per 2 seconds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct ByteRate {
     bytes: usize,
     interval: Duration,
@@ -68,6 +91,19 @@ impl ByteRate {
         self.bytes_per_second_sec_accuracy()
     }
 
+    /// Calculates amount of bytes passed over seconds with requested `accuracy`, floor value.
+    ///
+    /// Same as calling the method corresponding to `accuracy`, useful when accuracy is configured at runtime.
+    pub fn bytes_per_second_with(&self, accuracy: RateAccuracy) -> Option<usize> {
+        match accuracy {
+            RateAccuracy::Sec => self.bytes_per_second_sec_accuracy(),
+            RateAccuracy::Milli => self.bytes_per_second_ms_accuracy(),
+            RateAccuracy::Micro => self.bytes_per_second_us_accuracy(),
+            RateAccuracy::Nano => self.bytes_per_second_ns_accuracy(),
+            RateAccuracy::Auto => self.bytes_per_second(),
+        }
+    }
+
     /// Calculates amount of bytes passed over seconds, floor value.
     /// Returns `None` when interval is zero or below 1 second.
     pub fn bytes_per_second_sec_accuracy(&self) -> Option<usize> {
@@ -84,50 +120,64 @@ impl ByteRate {
     }
 
     /// Calculates amount of bytes passed over milliseconds, floor value and converts to bytes per second
-    /// Returns `None` when interval is below 1 ms or calculation overflowed
+    /// Returns `None` when interval is below 1 ms or the result overflowed
     pub fn bytes_per_second_ms_accuracy(&self) -> Option<usize> {
         if self.interval.is_zero() {
             return None;
         }
 
-        let ms = usize::try_from(self.interval.as_millis()).ok()?;
-        if ms == 0 {
-            return None;
-        }
-
-        let bytes_ms = self.bytes.checked_mul(1_000)?;
-
-        Some(bytes_ms / ms)
+        self.per_second(self.interval.as_millis(), 1_000)
     }
 
     /// Calculates amount of bytes passed over microseconds, floor value and converts to bytes per second
-    /// Returns `None` when interval is below 1 us or calculation overflowed
+    /// Returns `None` when interval is below 1 us or the result overflowed
     pub fn bytes_per_second_us_accuracy(&self) -> Option<usize> {
         if self.interval.is_zero() {
             return None;
         }
 
-        let us = usize::try_from(self.interval.as_micros()).ok()?;
-        if us == 0 {
+        self.per_second(self.interval.as_micros(), 1_000_000)
+    }
+
+    /// Calculates amount of bytes passed over nanoseconds, floor value and converts to bytes per second
+    /// Returns `None` when interval is below 1 ns or the result overflowed
+    pub fn bytes_per_second_ns_accuracy(&self) -> Option<usize> {
+        if self.interval.is_zero() {
             return None;
         }
 
-        let bytes_us = self.bytes.checked_mul(1_000_000)?;
+        self.per_second(self.interval.as_nanos(), 1_000_000_000)
+    }
 
-        Some(bytes_us / us)
+    /// Converts bytes passed over `units` of interval to bytes per second, floor value.
+    ///
+    /// Calculated with 128 bit intermediate, so only a result not fitting `usize` is an overflow.
+    /// Returns `None` when `units` is zero or on overflow.
+    fn per_second(&self, units: u128, units_per_sec: u128) -> Option<usize> {
+        if units == 0 {
+            return None;
+        }
+
+        let bytes = self.bytes as u128 * units_per_sec;
+        usize::try_from(bytes / units).ok()
     }
 
-    /// Calculates amount of bytes passed over nanoseconds, floor value and converts to bytes per second
-    /// Returns `None` when interval is below 1 ns or calculation overflowed
-    pub fn bytes_per_second_ns_accuracy(&self) -> Option<usize> {
-        if self.interval.is_zero() {
+    /// Calculates bytes per second as fixed-point `(whole, thousandths)` using integer arithmetic only
+    ///
+    /// Suitable for targets without FPU, e.g. 3 bytes over 2 seconds gives `(1, 500)`.
+    /// The thousandths are floored, calculated with nanosecond accuracy.
+    /// Returns `None` when interval is below 1 ns or the whole part overflowed
+    pub fn bytes_per_second_fixed(&self) -> Option<(usize, u16)> {
+        let nanos = self.interval.as_nanos();
+        if nanos == 0 {
             return None;
         }
 
-        let ns = usize::try_from(self.interval.as_nanos()).ok()?;
-        let bytes_ns = self.bytes.checked_mul(1_000_000_000)?;
+        let millibytes = self.bytes as u128 * 1_000_000_000_000 / nanos;
+        let whole = usize::try_from(millibytes / 1000).ok()?;
+        let thousandths = (millibytes % 1000) as u16;
 
-        Some(bytes_ns / ns)
+        Some((whole, thousandths))
     }
 
     /// Calculate bytes per second using 32 bit float-point arithmetic
@@ -169,6 +219,81 @@ impl ByteRate {
     }
 }
 
+impl ByteRate {
+    /// Sums two byte rates, saturating the amount of bytes at `usize::MAX`.
+    ///
+    /// If intervals differ, rates are normalized to the larger interval before summing.
+    pub fn saturating_add(self, rhs: Self) -> Self {
+        let interval = self.interval.max(rhs.interval);
+        let bytes = self
+            .scaled_bytes(interval)
+            .saturating_add(rhs.scaled_bytes(interval));
+
+        ByteRate::new(bytes, interval)
+    }
+
+    /// Sums two byte rates, returns `None` if the amount of bytes overflows.
+    ///
+    /// If intervals differ, rates are normalized to the larger interval before summing.
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        let interval = self.interval.max(rhs.interval);
+        let bytes = self
+            .checked_scaled_bytes(interval)?
+            .checked_add(rhs.checked_scaled_bytes(interval)?)?;
+
+        Some(ByteRate::new(bytes, interval))
+    }
+
+    /// Subtracts `rhs` rate from this one, saturating the amount of bytes at 0.
+    ///
+    /// If intervals differ, rates are normalized to the larger interval before subtracting.
+    pub fn saturating_sub(self, rhs: Self) -> Self {
+        let interval = self.interval.max(rhs.interval);
+        let bytes = self
+            .scaled_bytes(interval)
+            .saturating_sub(rhs.scaled_bytes(interval));
+
+        ByteRate::new(bytes, interval)
+    }
+
+    /// Returns amount of bytes that would be passed over `interval` with the same rate, saturating at `usize::MAX`
+    ///
+    /// Zero interval rate is not scaled.
+    fn scaled_bytes(&self, interval: Duration) -> usize {
+        self.checked_scaled_bytes(interval).unwrap_or(usize::MAX)
+    }
+
+    /// Returns amount of bytes that would be passed over `interval` with the same rate, `None` on overflow
+    ///
+    /// Zero interval rate is not scaled.
+    fn checked_scaled_bytes(&self, interval: Duration) -> Option<usize> {
+        if self.interval == interval || self.interval.is_zero() {
+            return Some(self.bytes);
+        }
+
+        let scaled = self.bytes as u128 * interval.as_nanos() / self.interval.as_nanos();
+        usize::try_from(scaled).ok()
+    }
+}
+
+impl Add for ByteRate {
+    type Output = ByteRate;
+
+    /// Sums two byte rates, see `saturating_add`.
+    fn add(self, rhs: Self) -> Self::Output {
+        self.saturating_add(rhs)
+    }
+}
+
+impl Sub for ByteRate {
+    type Output = ByteRate;
+
+    /// Subtracts two byte rates, see `saturating_sub`.
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.saturating_sub(rhs)
+    }
+}
+
 impl Default for ByteRate {
     fn default() -> Self {
         Self {
@@ -189,6 +314,29 @@ mod tests {
         assert_eq!(rate.interval().as_secs(), 2);
     }
 
+    #[test]
+    fn equality() {
+        let rate = ByteRate::new(1, Duration::from_secs(1));
+        assert_eq!(rate, rate.clone());
+        assert_ne!(rate, ByteRate::new(2, Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn bytes_per_second_with_accuracy() {
+        let rate = ByteRate::new(1, Duration::from_millis(1500));
+        assert_eq!(rate.bytes_per_second_with(RateAccuracy::Sec), Some(1));
+        assert_eq!(rate.bytes_per_second_with(RateAccuracy::Milli), Some(0));
+        assert_eq!(rate.bytes_per_second_with(RateAccuracy::Nano), Some(0));
+
+        let rate = ByteRate::new(3, Duration::from_millis(1500));
+        assert_eq!(rate.bytes_per_second_with(RateAccuracy::Sec), Some(3));
+        assert_eq!(rate.bytes_per_second_with(RateAccuracy::Micro), Some(2));
+        assert_eq!(
+            rate.bytes_per_second_with(RateAccuracy::Auto),
+            rate.bytes_per_second()
+        );
+    }
+
     #[test]
     fn bytes_per_second_sec_accuracy_whole() {
         let rate = ByteRate::new(146, Duration::from_secs(2));
@@ -258,6 +406,40 @@ mod tests {
         assert!(rate_per_sec.is_none());
     }
 
+    /// Magnitudes where `bytes * 1e9` overflows 32-bit `usize`, but the rate fits
+    #[test]
+    fn bytes_per_second_ns_wide_intermediate() {
+        let rate = ByteRate::new(4_000_000_000, Duration::from_millis(1500));
+        assert_eq!(rate.bytes_per_second_ns_accuracy(), Some(2_666_666_666));
+
+        let rate = ByteRate::new(u32::MAX as usize, Duration::from_secs(3600));
+        assert_eq!(rate.bytes_per_second_ns_accuracy(), Some(1_193_046));
+        assert_eq!(rate.bytes_per_second_us_accuracy(), Some(1_193_046));
+        assert_eq!(rate.bytes_per_second(), Some(1_193_046));
+
+        let rate = ByteRate::new(115_200, Duration::from_nanos(999_999_999));
+        assert_eq!(rate.bytes_per_second_ns_accuracy(), Some(115_200));
+        assert_eq!(rate.bytes_per_second_ms_accuracy(), Some(115_315));
+    }
+
+    #[test]
+    fn bytes_per_second_fixed() {
+        let rate = ByteRate::new(3, Duration::from_secs(2));
+        assert_eq!(rate.bytes_per_second_fixed(), Some((1, 500)));
+
+        let rate = ByteRate::new(115_200, Duration::from_millis(1500));
+        assert_eq!(rate.bytes_per_second_fixed(), Some((76_800, 0)));
+
+        let rate = ByteRate::new(1, Duration::from_secs(3));
+        assert_eq!(rate.bytes_per_second_fixed(), Some((0, 333)));
+
+        let rate = ByteRate::new(10, Duration::ZERO);
+        assert_eq!(rate.bytes_per_second_fixed(), None);
+
+        let rate = ByteRate::new(usize::MAX, Duration::from_nanos(250));
+        assert_eq!(rate.bytes_per_second_fixed(), None);
+    }
+
     #[test]
     fn bytes_per_second_auto_overflow() {
         let rate = ByteRate::new(usize::MAX / 2, Duration::from_secs(2));
@@ -266,6 +448,51 @@ mod tests {
         assert_eq!(rate_per_sec.unwrap(), usize::MAX / 4);
     }
 
+    #[test]
+    fn add_same_interval() {
+        let rate =
+            ByteRate::new(100, Duration::from_secs(1)) + ByteRate::new(50, Duration::from_secs(1));
+        assert_eq!(rate.bytes(), 150);
+        assert_eq!(*rate.interval(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn add_different_intervals() {
+        let rate = ByteRate::new(100, Duration::from_millis(500))
+            + ByteRate::new(50, Duration::from_secs(2));
+        assert_eq!(rate.bytes(), 450);
+        assert_eq!(*rate.interval(), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn add_saturates() {
+        let rate = ByteRate::new(usize::MAX, Duration::from_secs(1))
+            + ByteRate::new(1, Duration::from_secs(1));
+        assert_eq!(rate.bytes(), usize::MAX);
+    }
+
+    #[test]
+    fn checked_add_overflow() {
+        let rate = ByteRate::new(usize::MAX, Duration::from_secs(1))
+            .checked_add(ByteRate::new(1, Duration::from_secs(1)));
+        assert!(rate.is_none());
+    }
+
+    #[test]
+    fn sub_different_intervals() {
+        let rate = ByteRate::new(100, Duration::from_secs(2))
+            - ByteRate::new(20, Duration::from_millis(500));
+        assert_eq!(rate.bytes(), 20);
+        assert_eq!(*rate.interval(), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn sub_saturates() {
+        let rate =
+            ByteRate::new(1, Duration::from_secs(1)) - ByteRate::new(5, Duration::from_secs(1));
+        assert_eq!(rate.bytes(), 0);
+    }
+
     #[test]
     fn bytes_per_second_f32() {
         let rate = ByteRate::new(147, Duration::from_secs(2));
@@ -288,4 +515,16 @@ mod tests {
         assert!(rate_per_sec.is_some());
         assert_eq!(rate_per_sec.unwrap(), u16::MAX as f64 / 2.0 + 0.5);
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_roundtrip() {
+        let rate = ByteRate::new(146, Duration::from_millis(1500));
+
+        let json = serde_json::to_string(&rate).unwrap();
+        let restored: ByteRate = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored.bytes(), 146);
+        assert_eq!(*restored.interval(), Duration::from_millis(1500));
+    }
 }
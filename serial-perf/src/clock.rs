@@ -2,9 +2,29 @@
 //! Definition of a clock, timers interfaces used in library
 //!
 
+use core::time::Duration;
+
 pub use embedded_timers::clock::Clock;
+pub use embedded_timers::instant::Instant;
 pub use embedded_timers::timer::{Timer, TimerError};
 
+/// Time measurements built on `Clock`, used by the library instead of calling `Instant` directly
+///
+/// Implemented for every `Clock`.
+pub trait ClockExt: Clock {
+    /// Returns time passed since `start`, zero if `start` is in the future
+    fn elapsed_since(&self, start: Self::Instant) -> Duration {
+        self.duration_between(start, self.now())
+    }
+
+    /// Returns time passed from `earlier` to `later`, zero if `earlier` is after `later`
+    fn duration_between(&self, earlier: Self::Instant, later: Self::Instant) -> Duration {
+        later.duration_since(earlier)
+    }
+}
+
+impl<Clk: Clock> ClockExt for Clk {}
+
 /// A clock based on std::time
 #[cfg(feature = "std")]
 pub struct StdClock;
@@ -16,3 +36,92 @@ impl Clock for StdClock {
         std::time::Instant::now()
     }
 }
+
+/// A clock based on a free-running counter, e.g. a hardware timer on bare metal
+///
+/// `now` returns current value of the counter incremented `TICKS_PER_SEC` times per second.
+/// The counter must be monotonic and must not wrap.
+#[derive(Debug, Clone, Copy)]
+pub struct CounterClock<const TICKS_PER_SEC: u32> {
+    now: fn() -> u64,
+}
+
+impl<const TICKS_PER_SEC: u32> CounterClock<TICKS_PER_SEC> {
+    /// Creates new clock reading the counter with `now`
+    pub fn new(now: fn() -> u64) -> Self {
+        Self { now }
+    }
+}
+
+impl<const TICKS_PER_SEC: u32> Clock for CounterClock<TICKS_PER_SEC> {
+    type Instant = embedded_timers::instant::Instant64<TICKS_PER_SEC>;
+    fn now(&self) -> Self::Instant {
+        embedded_timers::instant::Instant64::new((self.now)())
+    }
+}
+
+/// A clock that stands still until advanced manually, for deterministic tests
+///
+/// Starts at zero and counts nanoseconds.
+#[cfg(any(test, feature = "test-util"))]
+#[derive(Debug)]
+pub struct MockClock {
+    now: core::cell::Cell<embedded_timers::instant::Instant64<1_000_000_000>>,
+}
+
+#[cfg(any(test, feature = "test-util"))]
+impl MockClock {
+    /// Creates new clock at zero time point
+    pub fn new() -> Self {
+        Self {
+            now: core::cell::Cell::new(embedded_timers::instant::Instant64::new(0)),
+        }
+    }
+
+    /// Moves the clock forward by `duration`
+    ///
+    /// # Panics
+    /// Panics if the time point overflows
+    pub fn advance(&self, duration: core::time::Duration) {
+        self.now.set(self.now.get() + duration);
+    }
+}
+
+#[cfg(any(test, feature = "test-util"))]
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(any(test, feature = "test-util"))]
+impl Clock for MockClock {
+    type Instant = embedded_timers::instant::Instant64<1_000_000_000>;
+    fn now(&self) -> Self::Instant {
+        self.now.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::sync::atomic::{AtomicU64, Ordering};
+    use core::time::Duration;
+
+    use super::{Clock, ClockExt, CounterClock};
+
+    static TICKS: AtomicU64 = AtomicU64::new(0);
+
+    fn ticks() -> u64 {
+        TICKS.load(Ordering::Relaxed)
+    }
+
+    #[test]
+    fn counter_clock() {
+        let clock = CounterClock::<1_000>::new(ticks);
+        let start = clock.now();
+
+        TICKS.fetch_add(1_500, Ordering::Relaxed);
+        assert_eq!(clock.elapsed_since(start), Duration::from_millis(1_500));
+        assert_eq!(clock.duration_between(clock.now(), start), Duration::ZERO);
+    }
+}
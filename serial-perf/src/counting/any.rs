@@ -0,0 +1,237 @@
+use embedded_hal_nb::nb::Result;
+use embedded_hal_nb::serial::{Read, Write};
+
+use crate::statistics::{CountingStatistics, Statistics};
+
+use super::{Counting, DEFAULT_MAX_PACKET_SIZE};
+
+/// Width of the counter of `AnyCounting`, e.g. selected in runtime by a command line argument
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CounterWidth {
+    U8,
+    U16,
+    #[cfg(any(target_pointer_width = "32", target_pointer_width = "64"))]
+    U32,
+    #[cfg(target_pointer_width = "64")]
+    U64,
+}
+
+/// Counting test with the `Number` selected in runtime
+///
+/// Holds `Counting` with one of builtin counters, so one variable can store any of them.
+/// Configure the test with `Counting::builder` and convert it with `into` if needed.
+pub enum AnyCounting<
+    Serial,
+    TxStats = CountingStatistics,
+    RxStats = CountingStatistics,
+    LossStats = CountingStatistics,
+    const MAX_PACKET_SIZE: usize = DEFAULT_MAX_PACKET_SIZE,
+> {
+    U8(Counting<Serial, u8, TxStats, RxStats, LossStats, MAX_PACKET_SIZE>),
+    U16(Counting<Serial, u16, TxStats, RxStats, LossStats, MAX_PACKET_SIZE>),
+    #[cfg(any(target_pointer_width = "32", target_pointer_width = "64"))]
+    U32(Counting<Serial, u32, TxStats, RxStats, LossStats, MAX_PACKET_SIZE>),
+    #[cfg(target_pointer_width = "64")]
+    U64(Counting<Serial, u64, TxStats, RxStats, LossStats, MAX_PACKET_SIZE>),
+}
+
+/// Calls `$body` with `$counting` bound to the counting test of any width
+macro_rules! dispatch {
+    ($self:expr, $counting:ident => $body:expr) => {
+        match $self {
+            AnyCounting::U8($counting) => $body,
+            AnyCounting::U16($counting) => $body,
+            #[cfg(any(target_pointer_width = "32", target_pointer_width = "64"))]
+            AnyCounting::U32($counting) => $body,
+            #[cfg(target_pointer_width = "64")]
+            AnyCounting::U64($counting) => $body,
+        }
+    };
+}
+
+macro_rules! impl_from_counting {
+    ($variant:ident, $number:ty) => {
+        impl<Serial, TxStats, RxStats, LossStats, const MAX_PACKET_SIZE: usize>
+            From<Counting<Serial, $number, TxStats, RxStats, LossStats, MAX_PACKET_SIZE>>
+            for AnyCounting<Serial, TxStats, RxStats, LossStats, MAX_PACKET_SIZE>
+        {
+            fn from(
+                counting: Counting<Serial, $number, TxStats, RxStats, LossStats, MAX_PACKET_SIZE>,
+            ) -> Self {
+                Self::$variant(counting)
+            }
+        }
+    };
+}
+
+impl_from_counting!(U8, u8);
+impl_from_counting!(U16, u16);
+#[cfg(any(target_pointer_width = "32", target_pointer_width = "64"))]
+impl_from_counting!(U32, u32);
+#[cfg(target_pointer_width = "64")]
+impl_from_counting!(U64, u64);
+
+impl<Serial, TxStats, RxStats, LossStats, const MAX_PACKET_SIZE: usize>
+    AnyCounting<Serial, TxStats, RxStats, LossStats, MAX_PACKET_SIZE>
+where
+    TxStats: Statistics,
+    RxStats: Statistics,
+    LossStats: Statistics,
+{
+    /// Creates counting test with the counter of `width`, see `Counting::new`
+    pub fn new(
+        width: CounterWidth,
+        serial: Serial,
+        tx_stats: TxStats,
+        rx_stats: RxStats,
+        loss_stats: LossStats,
+    ) -> Self {
+        match width {
+            CounterWidth::U8 => Self::U8(Counting::new(serial, tx_stats, rx_stats, loss_stats)),
+            CounterWidth::U16 => Self::U16(Counting::new(serial, tx_stats, rx_stats, loss_stats)),
+            #[cfg(any(target_pointer_width = "32", target_pointer_width = "64"))]
+            CounterWidth::U32 => Self::U32(Counting::new(serial, tx_stats, rx_stats, loss_stats)),
+            #[cfg(target_pointer_width = "64")]
+            CounterWidth::U64 => Self::U64(Counting::new(serial, tx_stats, rx_stats, loss_stats)),
+        }
+    }
+
+    /// Returns width of the counter
+    pub fn width(&self) -> CounterWidth {
+        match self {
+            Self::U8(_) => CounterWidth::U8,
+            Self::U16(_) => CounterWidth::U16,
+            #[cfg(any(target_pointer_width = "32", target_pointer_width = "64"))]
+            Self::U32(_) => CounterWidth::U32,
+            #[cfg(target_pointer_width = "64")]
+            Self::U64(_) => CounterWidth::U64,
+        }
+    }
+
+    /// Resets the test and all statistics, see `Counting::reset`
+    pub fn reset(&mut self) {
+        dispatch!(self, counting => counting.reset())
+    }
+
+    pub fn tx_stats(&self) -> &TxStats {
+        dispatch!(self, counting => counting.tx_stats())
+    }
+
+    pub fn rx_stats(&self) -> &RxStats {
+        dispatch!(self, counting => counting.rx_stats())
+    }
+
+    pub fn loss_stats(&self) -> &LossStats {
+        dispatch!(self, counting => counting.loss_stats())
+    }
+
+    /// Returns reference to the underlying serial
+    pub fn serial(&self) -> &Serial {
+        dispatch!(self, counting => counting.serial())
+    }
+
+    /// Returns mutable reference to the underlying serial
+    pub fn serial_mut(&mut self) -> &mut Serial {
+        dispatch!(self, counting => counting.serial_mut())
+    }
+
+    /// Returns size of the packet on wire, see `Counting::packet_size`
+    pub fn packet_size(&self) -> usize {
+        dispatch!(self, counting => counting.packet_size())
+    }
+
+    /// Returns amount of packets sent completely, see `Counting::tx_packets`
+    pub fn tx_packets(&self) -> usize {
+        dispatch!(self, counting => counting.tx_packets())
+    }
+
+    /// Returns amount of packets received, see `Counting::rx_packets`
+    pub fn rx_packets(&self) -> usize {
+        dispatch!(self, counting => counting.rx_packets())
+    }
+
+    /// Returns amount of packets with wrong checksum, see `Counting::crc_errors`
+    pub fn crc_errors(&self) -> usize {
+        dispatch!(self, counting => counting.crc_errors())
+    }
+}
+
+impl<Serial, TxStats, RxStats, LossStats, const MAX_PACKET_SIZE: usize>
+    AnyCounting<Serial, TxStats, RxStats, LossStats, MAX_PACKET_SIZE>
+where
+    Serial: Read,
+    TxStats: Statistics,
+    RxStats: Statistics,
+    LossStats: Statistics,
+{
+    /// Receive byte from the serial port and verify it, see `Counting::recv_nb`
+    pub fn recv_nb(&mut self) -> Result<(), Serial::Error> {
+        dispatch!(self, counting => counting.recv_nb())
+    }
+}
+
+impl<Serial, TxStats, RxStats, LossStats, const MAX_PACKET_SIZE: usize>
+    AnyCounting<Serial, TxStats, RxStats, LossStats, MAX_PACKET_SIZE>
+where
+    Serial: Write,
+    TxStats: Statistics,
+    RxStats: Statistics,
+    LossStats: Statistics,
+{
+    /// Sends next byte of the packet, see `Counting::send_nb`
+    pub fn send_nb(&mut self) -> Result<(), Serial::Error> {
+        dispatch!(self, counting => counting.send_nb())
+    }
+}
+
+impl<Serial, TxStats, RxStats, LossStats, const MAX_PACKET_SIZE: usize>
+    AnyCounting<Serial, TxStats, RxStats, LossStats, MAX_PACKET_SIZE>
+where
+    Serial: Write + Read,
+    TxStats: Statistics,
+    RxStats: Statistics,
+    LossStats: Statistics,
+{
+    /// Receives and sends a byte, see `Counting::loop_nb`
+    pub fn loop_nb(&mut self) -> Result<(), Serial::Error> {
+        dispatch!(self, counting => counting.loop_nb())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::paired_serial::PairedSerial;
+    use crate::statistics::CountingStatistics;
+
+    use super::{AnyCounting, CounterWidth};
+
+    fn new_counting<S>(width: CounterWidth, serial: S) -> AnyCounting<S> {
+        AnyCounting::new(
+            width,
+            serial,
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        )
+    }
+
+    #[test]
+    fn runtime_width() {
+        for width in [CounterWidth::U8, CounterWidth::U16, CounterWidth::U32] {
+            let paired = PairedSerial::<16>::new();
+            let (a, b) = paired.split();
+            let mut client = new_counting(width, a);
+            let mut server = new_counting(width, b);
+            assert_eq!(client.width(), width);
+
+            for _ in 0..100 {
+                client.loop_nb().ok();
+                server.loop_nb().ok();
+            }
+
+            assert!(server.rx_packets() > 0);
+            assert_eq!(server.crc_errors(), 0);
+            assert_eq!(server.loss_stats().failed(), 0);
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use embedded_io_async::{Read, ReadReady, Write};
+
+use crate::statistics::Statistics;
+use crate::stream_error::StreamError;
+
+use super::counter::Counter;
+use super::Counting;
+
+impl<Serial, Number, TxStats, RxStats, LossStats, const MAX_PACKET_SIZE: usize>
+    Counting<Serial, Number, TxStats, RxStats, LossStats, MAX_PACKET_SIZE>
+where
+    Serial: Read,
+    Number: Counter,
+    TxStats: Statistics,
+    RxStats: Statistics,
+    LossStats: Statistics,
+{
+    /// Receive byte from the serial port and verify it. Waits until byte is received.
+    ///
+    /// Returns `StreamError::Closed` if the serial reached end of file, it's counted as failed receive.
+    pub async fn recv_async(&mut self) -> Result<(), StreamError<Serial::Error>> {
+        let mut byte_read = [0];
+        match self.serial.read(&mut byte_read).await {
+            Ok(0) => {
+                self.rx_stats.add_failed(1);
+                Err(StreamError::Closed)
+            }
+            Ok(_) => {
+                self.on_byte_received(byte_read[0]);
+                Ok(())
+            }
+            Err(e) => {
+                self.rx_stats.add_failed(1);
+                Err(StreamError::Serial(e))
+            }
+        }
+    }
+}
+
+impl<Serial, Number, TxStats, RxStats, LossStats, const MAX_PACKET_SIZE: usize>
+    Counting<Serial, Number, TxStats, RxStats, LossStats, MAX_PACKET_SIZE>
+where
+    Serial: Write,
+    Number: Counter,
+    TxStats: Statistics,
+    RxStats: Statistics,
+    LossStats: Statistics,
+{
+    /// Sends next byte. Waits until byte is accepted by serial.
+    ///
+    /// Returns `StreamError::Closed` if the serial accepted no bytes, it's counted as failed send.
+    /// Flushes the serial every N bytes if configured with `CountingBuilder::flush_every`.
+    pub async fn send_async(&mut self) -> Result<(), StreamError<Serial::Error>> {
+        let byte_to_send = self.tx_state.peek();
+
+        match self.serial.write(&[byte_to_send]).await {
+            Ok(0) => {
+                self.tx_stats.add_failed(1);
+                Err(StreamError::Closed)
+            }
+            Ok(_) => {
+                self.on_byte_sent();
+                self.flush_cadence.on_written(1);
+                if self.flush_cadence.is_due() {
+                    self.serial.flush().await.map_err(StreamError::Serial)?;
+                    self.flush_cadence.on_flushed();
+                }
+                Ok(())
+            }
+            Err(e) => {
+                self.tx_stats.add_failed(1);
+                Err(StreamError::Serial(e))
+            }
+        }
+    }
+
+    /// Flushes serial port, waits until all data is sent.
+    ///
+    /// # Warning
+    /// The error happened here will not affect tx_state
+    pub async fn flush_async(&mut self) -> Result<(), Serial::Error> {
+        self.serial.flush().await
+    }
+}
+
+impl<Serial, Number, TxStats, RxStats, LossStats, const MAX_PACKET_SIZE: usize>
+    Counting<Serial, Number, TxStats, RxStats, LossStats, MAX_PACKET_SIZE>
+where
+    Serial: Write + Read + ReadReady,
+    Number: Counter,
+    TxStats: Statistics,
+    RxStats: Statistics,
+    LossStats: Statistics,
+{
+    /// Sends next byte then receives all bytes ready to be read.
+    ///
+    /// Reading does not wait for new bytes so the sending is not stalled if the other side is silent.
+    pub async fn loop_async(&mut self) -> Result<(), StreamError<Serial::Error>> {
+        self.send_async().await?;
+
+        while self.serial.read_ready().map_err(StreamError::Serial)? {
+            self.recv_async().await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::counting::Counting;
+    use crate::paired_serial::PairedSerial;
+    use crate::statistics::CountingStatistics;
+    use crate::stream_error::StreamError;
+    use crate::test_serial::{block_on, ClosedSerial, ScriptedSerial};
+
+    #[test]
+    fn loop_async_no_loss() {
+        let paired = PairedSerial::<64>::new();
+        let mut counting = Counting::<_, u8>::new(
+            paired.echo(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+
+        for _ in 0..1000 {
+            block_on(counting.loop_async()).unwrap();
+        }
+
+        assert_eq!(counting.tx_stats().successful(), 1000);
+        assert_eq!(counting.rx_stats().successful(), 1000);
+        assert_ne!(counting.loss_stats().successful(), 0);
+        assert_eq!(counting.loss_stats().failed(), 0);
+    }
+
+    #[test]
+    fn flush_every_async() {
+        let mut counting = Counting::<_, u16>::builder(
+            ScriptedSerial::new(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        )
+        .flush_every(4)
+        .build();
+
+        for _ in 0..11 {
+            block_on(counting.send_async()).unwrap();
+        }
+
+        assert_eq!(counting.serial().flushes(), 2);
+    }
+
+    #[test]
+    fn closed_serial() {
+        let mut counting = Counting::<_, u8>::new(
+            ClosedSerial,
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+
+        assert!(matches!(
+            block_on(counting.send_async()),
+            Err(StreamError::Closed)
+        ));
+        assert!(matches!(
+            block_on(counting.recv_async()),
+            Err(StreamError::Closed)
+        ));
+
+        assert_eq!(counting.tx_stats().failed(), 1);
+        assert_eq!(counting.rx_stats().failed(), 1);
+        assert_eq!(counting.tx_packets(), 0);
+    }
+}
@@ -0,0 +1,548 @@
+use core::marker::PhantomData;
+
+use crc::Algorithm;
+
+use crate::flush_cadence::FlushCadence;
+use crate::statistics::{LossEventStatistics, LossLogger, Statistics};
+
+use super::checksum::Checksum;
+use super::counter::{ByteOrder, Counter};
+use super::rx_state::RxState;
+use super::tx_state::TxState;
+use super::{ConfigError, Counting, DEFAULT_CRC_ALGORITHM, DEFAULT_MAX_PACKET_SIZE};
+
+/// Builder of `Counting` test with optional settings
+///
+/// By default the packets are protected with 8-bit CRC (`DEFAULT_CRC_ALGORITHM`), not padded
+/// and the maximum gap is half of the `Number` range.
+pub struct CountingBuilder<
+    Serial,
+    Number,
+    TxStats,
+    RxStats,
+    LossStats,
+    const MAX_PACKET_SIZE: usize = DEFAULT_MAX_PACKET_SIZE,
+> {
+    serial: Serial,
+    tx_stats: TxStats,
+    rx_stats: RxStats,
+    loss_stats: LossStats,
+
+    checksum: Checksum,
+    packet_size: Option<usize>,
+    max_gap: Option<usize>,
+    reorder_window: Option<usize>,
+    separator: Option<u8>,
+    byte_order: ByteOrder,
+    #[cfg(feature = "cobs")]
+    cobs: bool,
+    start: Option<Number>,
+    width_preamble: bool,
+    flush_every: usize,
+
+    _number: PhantomData<Number>,
+}
+
+impl<Serial, Number, TxStats, RxStats, LossStats, const MAX_PACKET_SIZE: usize>
+    CountingBuilder<Serial, Number, TxStats, RxStats, LossStats, MAX_PACKET_SIZE>
+where
+    Number: Counter,
+    TxStats: Statistics,
+    RxStats: Statistics,
+    LossStats: Statistics,
+{
+    pub fn new(
+        serial: Serial,
+        tx_stats: TxStats,
+        rx_stats: RxStats,
+        loss_stats: LossStats,
+    ) -> Self {
+        Self {
+            serial,
+            tx_stats,
+            rx_stats,
+            loss_stats,
+            checksum: Checksum::crc8(DEFAULT_CRC_ALGORITHM),
+            packet_size: None,
+            max_gap: None,
+            reorder_window: None,
+            separator: Some(0),
+            byte_order: ByteOrder::Little,
+            #[cfg(feature = "cobs")]
+            cobs: false,
+            start: None,
+            width_preamble: false,
+            flush_every: 0,
+            _number: PhantomData,
+        }
+    }
+
+    /// Enables or disables packets checksum. Enabling uses `DEFAULT_CRC_ALGORITHM` unless CRC is already selected
+    pub fn checksum(mut self, enabled: bool) -> Self {
+        if !enabled {
+            self.checksum = Checksum::None;
+        } else if !self.checksum.is_enabled() {
+            self.checksum = Checksum::crc8(DEFAULT_CRC_ALGORITHM);
+        }
+        self
+    }
+
+    /// Uses specified 8-bit CRC algorithm for packets checksum. Both sides must use the same algorithm.
+    pub fn crc_algorithm(mut self, algorithm: &'static Algorithm<u8>) -> Self {
+        self.checksum = Checksum::crc8(algorithm);
+        self
+    }
+
+    /// Uses specified 16-bit CRC algorithm for packets checksum. Both sides must use the same algorithm.
+    pub fn crc16_algorithm(mut self, algorithm: &'static Algorithm<u16>) -> Self {
+        let () = TxState::<Number, MAX_PACKET_SIZE>::CRC16_PACKET_SIZE_CHECK;
+
+        self.checksum = Checksum::crc16(algorithm);
+        self
+    }
+
+    /// Uses `separator` instead of null byte to separate count from checksum. Both sides must use the same separator.
+    ///
+    /// Count bytes are never zero, so they are sent XORed with `separator` to never equal it.
+    /// The checksum and padding bytes may be equal to `separator`, they are located by their size.
+    /// The null separator keeps count bytes as is.
+    pub fn separator(mut self, separator: u8) -> Self {
+        self.separator = Some(separator);
+        self
+    }
+
+    /// Sends packets without separator, saving a byte per packet for maximum throughput. Both sides must use this mode.
+    ///
+    /// The receiver splits packets by the fixed size of `Number` bytes and checksum instead of separator,
+    /// so loss detection relies on the framing: a byte lost or inserted on the line shifts all following packets
+    /// and the receiver never recovers. Use it on reliable links only, with checksum to reject misaligned packets.
+    pub fn without_separator(mut self) -> Self {
+        self.separator = None;
+        self
+    }
+
+    /// Frames packets with COBS instead of separator and sends count as plain little-endian integer.
+    /// Both sides must use this mode.
+    ///
+    /// COBS encoding allows null bytes, so the counter uses the full numeric range of `Number` bytes, e.g. 65536
+    /// values of `u16` instead of 65025 values without null bytes, and wraps later. `start_at` sets the position in
+    /// the sequence, so the first count equals the normalized `start`. The count and checksum are COBS encoded and
+    /// each packet is followed by null delimiter: encoding is simpler than XOR with separator and interoperates with
+    /// COBS based protocols, but the packet is a byte larger. The receiver recovers from lost bytes on the next delimiter.
+    ///
+    /// The frame must fit `MAX_PACKET_SIZE`, see `try_build`. E.g. `u64` counter fits the default size with 8-bit
+    /// checksum, but needs 12 bytes with 16-bit checksum.
+    #[cfg(feature = "cobs")]
+    pub fn cobs(mut self) -> Self {
+        self.cobs = true;
+        self
+    }
+
+    /// Passes each loss event to `logger`, e.g. a closure logging it with defmt or sending to a channel.
+    ///
+    /// Wraps the loss statistics into `LossEventStatistics` keeping only the last event, the wrapped
+    /// statistics are available with `inner`.
+    pub fn loss_logger<Logger: LossLogger>(
+        self,
+        logger: Logger,
+    ) -> CountingBuilder<
+        Serial,
+        Number,
+        TxStats,
+        RxStats,
+        LossEventStatistics<LossStats, 1, Logger>,
+        MAX_PACKET_SIZE,
+    > {
+        CountingBuilder {
+            serial: self.serial,
+            tx_stats: self.tx_stats,
+            rx_stats: self.rx_stats,
+            loss_stats: LossEventStatistics::with_logger(self.loss_stats, logger),
+            checksum: self.checksum,
+            packet_size: self.packet_size,
+            max_gap: self.max_gap,
+            reorder_window: self.reorder_window,
+            separator: self.separator,
+            byte_order: self.byte_order,
+            #[cfg(feature = "cobs")]
+            cobs: self.cobs,
+            start: self.start,
+            width_preamble: self.width_preamble,
+            flush_every: self.flush_every,
+            _number: PhantomData,
+        }
+    }
+
+    /// Sets byte order of counter bytes on wire, little-endian by default. Both sides must use the same order.
+    pub fn byte_order(mut self, byte_order: ByteOrder) -> Self {
+        self.byte_order = byte_order;
+        self
+    }
+
+    /// Sends the size of counter bytes in a short preamble before the first packet, so the peer can detect
+    /// that it uses different `Number`, see `Counting::recv_nb_checked`. Both sides must use this mode.
+    ///
+    /// The preamble is sent again after `Counting::reset` and is searched for only until the first valid packet,
+    /// so the side started later misses it. It's meant for separated or COBS framed packets: without separator
+    /// the preamble may be counted as a corrupted packet.
+    pub fn width_preamble(mut self) -> Self {
+        self.width_preamble = true;
+        self
+    }
+
+    /// Flushes the serial automatically every `bytes` bytes sent, 0 disables it.
+    ///
+    /// Useful on buffered OS serial ports, which send data in batches when flushed rather than every byte.
+    /// Applied by `send_nb`, `send_io` and `send_async`, if the flush would block, the next `send_nb` completes it
+    /// before sending. Bytes of `fill_send_slice` are written by the application, so it flushes them too.
+    /// There is no time cadence, see `Counting::timed_flush` to flush every T milliseconds and measure the latency.
+    pub fn flush_every(mut self, bytes: usize) -> Self {
+        self.flush_every = bytes;
+        self
+    }
+
+    /// Pads every packet to `packet_size` bytes on wire, see `Counting::with_packet_size`
+    pub fn packet_size(mut self, packet_size: usize) -> Self {
+        self.packet_size = Some(packet_size);
+        self
+    }
+
+    /// Sets the maximum distance between two consecutive received numbers counted as packet loss, see `Counting::set_max_gap`
+    pub fn max_gap(mut self, max_gap: usize) -> Self {
+        self.max_gap = Some(max_gap);
+        self
+    }
+
+    /// Sets the maximum distance back from the last received number counted as reordered packet, see `Counting::set_reorder_window`
+    pub fn reorder_window(mut self, reorder_window: usize) -> Self {
+        self.reorder_window = Some(reorder_window);
+        self
+    }
+
+    /// Sets the number of the first packet sent, for example to resume a test.
+    ///
+    /// Returns error if `start` is greater than the maximum counter value of `Number`.
+    pub fn start_at(mut self, start: Number) -> Result<Self, ConfigError> {
+        let start = start
+            .to_counter_value()
+            .ok_or(ConfigError::StartValueOutOfRange)?;
+
+        self.start = Some(start);
+        Ok(self)
+    }
+
+    /// Builds the test.
+    ///
+    /// # Panics
+    /// If `MAX_PACKET_SIZE` doesn't fit COBS frame with the configured checksum, use `try_build` to get an error
+    pub fn build(self) -> Counting<Serial, Number, TxStats, RxStats, LossStats, MAX_PACKET_SIZE> {
+        match self.try_build() {
+            Ok(counting) => counting,
+            Err(_) => panic!("MAX_PACKET_SIZE must fit COBS frame"),
+        }
+    }
+
+    /// Builds the test, returns error if `MAX_PACKET_SIZE` doesn't fit COBS frame with the configured checksum
+    pub fn try_build(
+        self,
+    ) -> Result<Counting<Serial, Number, TxStats, RxStats, LossStats, MAX_PACKET_SIZE>, ConfigError>
+    {
+        #[cfg(feature = "cobs")]
+        if self.cobs {
+            let frame_size = core::mem::size_of::<Number::Bytes>()
+                + self.checksum.len()
+                + super::cobs::COBS_FRAMING_SIZE;
+            if frame_size > MAX_PACKET_SIZE {
+                return Err(ConfigError::PacketSizeTooSmall);
+            }
+        }
+
+        let mut counting = Counting {
+            serial: self.serial,
+            tx_state: TxState::new(self.checksum.clone()),
+            rx_state: RxState::new(self.loss_stats, self.checksum),
+            tx_stats: self.tx_stats,
+            rx_stats: self.rx_stats,
+            flush_cadence: FlushCadence::new(self.flush_every),
+        };
+
+        counting.tx_state.set_separator(self.separator);
+        counting.rx_state.set_separator(self.separator);
+        counting.tx_state.set_byte_order(self.byte_order);
+        #[cfg(feature = "cobs")]
+        {
+            counting.tx_state.set_cobs(self.cobs);
+            counting.rx_state.set_cobs(self.cobs);
+        }
+        counting.rx_state.set_byte_order(self.byte_order);
+        counting.tx_state.set_width_preamble(self.width_preamble);
+        counting.rx_state.set_width_preamble(self.width_preamble);
+
+        if let Some(packet_size) = self.packet_size {
+            counting = counting.with_packet_size(packet_size);
+        }
+
+        if let Some(start) = self.start {
+            counting.tx_state.set_start(start);
+        }
+
+        if let Some(max_gap) = self.max_gap {
+            counting.set_max_gap(max_gap);
+        }
+
+        if let Some(reorder_window) = self.reorder_window {
+            counting.set_reorder_window(reorder_window);
+        }
+
+        Ok(counting)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::counting::{ByteOrder, ConfigError, Counting, DEFAULT_CRC16_ALGORITHM};
+    use crate::statistics::CountingStatistics;
+
+    #[test]
+    fn defaults() {
+        let built = Counting::<_, u16>::builder(
+            (),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        )
+        .build();
+        let created = Counting::<_, u16>::new(
+            (),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+
+        assert_eq!(built.max_gap(), created.max_gap());
+        assert_eq!(built.tx_state.unpadded_packet_size(), 4);
+    }
+
+    #[test]
+    fn options_applied() {
+        let counting = Counting::<_, u16>::builder(
+            (),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        )
+        .crc16_algorithm(DEFAULT_CRC16_ALGORITHM)
+        .packet_size(8)
+        .max_gap(10)
+        .build();
+
+        assert_eq!(counting.max_gap(), 10);
+        assert_eq!(counting.tx_state.unpadded_packet_size(), 5);
+    }
+
+    #[test]
+    fn start_at() {
+        let builder = || {
+            Counting::<_, u16>::builder(
+                (),
+                CountingStatistics::default(),
+                CountingStatistics::default(),
+                CountingStatistics::default(),
+            )
+        };
+
+        let mut counting = builder().start_at(500).unwrap().build();
+        for _ in 0..counting.packet_size() {
+            counting.on_byte_sent();
+        }
+        assert_eq!(counting.last_sent(), Some(500));
+
+        assert_eq!(
+            builder().start_at(u16::MAX).err(),
+            Some(ConfigError::StartValueOutOfRange)
+        );
+    }
+
+    #[test]
+    fn custom_separator() {
+        let mut counting = Counting::<_, u16>::builder(
+            (),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        )
+        .separator(0x7E)
+        .build();
+
+        let mut separators = 0;
+        for _ in 0..2000 {
+            let byte = counting.tx_state.take();
+            if byte == 0x7E {
+                separators += 1;
+            }
+            counting.rx_state.on_byte_received(byte);
+        }
+
+        // Checksum may be equal to separator so there can be more of them than packets
+        assert!(separators >= 2000 / 4);
+        assert_eq!(counting.loss_stats().successful(), 2000 / 4);
+        assert_eq!(counting.loss_stats().failed(), 0);
+    }
+
+    #[test]
+    fn without_separator() {
+        let mut counting = Counting::<_, u16>::builder(
+            (),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        )
+        .without_separator()
+        .build();
+        assert_eq!(counting.tx_state.unpadded_packet_size(), 3);
+
+        let mut buf = [0; 3 * 100];
+        counting.fill_send_slice(&mut buf);
+
+        // Drop a whole packet, the framing is kept
+        counting.recv_slice(&buf[..3 * 10]);
+        counting.recv_slice(&buf[3 * 11..]);
+
+        assert_eq!(counting.loss_stats().successful(), 99);
+        assert_eq!(counting.loss_stats().failed(), 1);
+        assert_eq!(counting.crc_errors(), 0);
+    }
+
+    #[test]
+    fn big_endian() {
+        let builder = |byte_order| {
+            Counting::<_, u16>::builder(
+                (),
+                CountingStatistics::default(),
+                CountingStatistics::default(),
+                CountingStatistics::default(),
+            )
+            .byte_order(byte_order)
+            .start_at(255)
+            .unwrap()
+            .build()
+        };
+
+        // Normalized 255 is 0x0201 counter value
+        let mut big = builder(ByteOrder::Big);
+        let mut buf = [0; 4 * 100];
+        big.fill_send_slice(&mut buf);
+        assert_eq!(buf[..3], [0x02, 0x01, 0x00]);
+
+        big.recv_slice(&buf);
+        assert_eq!(big.loss_stats().successful(), 100);
+        assert_eq!(big.loss_stats().failed(), 0);
+        assert_eq!(big.crc_errors(), 0);
+
+        // Little-endian receiver sees valid checksums but garbage numbers
+        let mut little = builder(ByteOrder::Little);
+        little.recv_slice(&buf);
+        assert_eq!(little.crc_errors(), 0);
+        assert_ne!(little.last_received(), big.last_received());
+    }
+
+    #[cfg(feature = "cobs")]
+    #[test]
+    fn cobs() {
+        let mut counting = Counting::<_, u16>::builder(
+            (),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        )
+        .cobs()
+        .packet_size(6)
+        .build();
+        assert_eq!(counting.tx_state.unpadded_packet_size(), 5);
+
+        let mut buf = [0; 6 * 100];
+        counting.fill_send_slice(&mut buf);
+        assert_eq!(buf[4], 0);
+
+        // Drop a whole packet, then a part of packet, the framing is recovered on the next delimiter
+        counting.recv_slice(&buf[..6 * 10]);
+        counting.recv_slice(&buf[6 * 11..6 * 20]);
+        counting.recv_slice(&buf[6 * 20 + 2..]);
+
+        assert_eq!(counting.loss_stats().successful(), 98);
+        assert_eq!(counting.loss_stats().failed(), 2);
+        assert_eq!(counting.desync_count(), 1);
+        assert_eq!(counting.crc_errors(), 0);
+    }
+
+    #[cfg(feature = "cobs")]
+    #[test]
+    fn cobs_plain_count() {
+        let mut counting = Counting::<_, u8>::builder(
+            (),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        )
+        .cobs()
+        .start_at(254)
+        .unwrap()
+        .build();
+        assert_eq!(counting.counter_range(), (0, 255));
+
+        // Count 254, 255, 0, 1 framed as overhead, count, checksum and delimiter
+        let mut buf = [0; 4 * 4];
+        counting.fill_send_slice(&mut buf);
+        assert_eq!(buf[1], 254);
+        assert_eq!(buf[4 + 1], 255);
+        assert_eq!(buf[12 + 1], 1);
+        assert!(buf.chunks(4).all(|frame| frame[3] == 0));
+        // The null count is encoded by COBS: the count replaced by the next block code
+        assert_eq!(buf[8..10], [0x01, 0x02]);
+
+        counting.recv_slice(&buf);
+        assert_eq!(counting.last_received(), Some(1));
+        assert_eq!(counting.last_sent(), Some(1));
+        assert_eq!(counting.loss_stats().successful(), 4);
+        assert_eq!(counting.loss_stats().failed(), 0);
+        assert_eq!(counting.crc_errors(), 0);
+    }
+
+    #[cfg(feature = "cobs")]
+    #[test]
+    fn cobs_packet_size_too_small() {
+        // The frame fits with 8-bit checksum but not with 16-bit one
+        let counting = Counting::<_, u64>::builder(
+            (),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        )
+        .cobs()
+        .try_build()
+        .unwrap();
+        assert_eq!(counting.tx_state.unpadded_packet_size(), 11);
+
+        let result = Counting::<_, u64>::builder(
+            (),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        )
+        .cobs()
+        .crc16_algorithm(DEFAULT_CRC16_ALGORITHM)
+        .try_build();
+        assert_eq!(result.err(), Some(ConfigError::PacketSizeTooSmall));
+
+        let counting = Counting::<_, u64, _, _, _, 12>::builder(
+            (),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        )
+        .cobs()
+        .crc16_algorithm(DEFAULT_CRC16_ALGORITHM)
+        .build();
+        assert_eq!(counting.tx_state.unpadded_packet_size(), 12);
+    }
+}
@@ -0,0 +1,77 @@
+use crc::{Algorithm, Crc, NoTable};
+
+/// Max amount of bytes used by checksum in packet
+pub const MAX_CHECKSUM_SIZE: usize = 2;
+
+/// Checksum used to verify counting packets
+///
+/// # Note
+/// The CRCs are calculated without lookup tables: packets are a few bytes long so
+/// tables would waste memory without noticeable speed up.
+#[derive(Clone)]
+pub enum Checksum {
+    /// Checksum disabled, the first byte of count is sent in place of checksum.
+    ///
+    /// The byte is never verified, it only keeps the packet size. A receiver with CRC accepts a packet
+    /// of a sender without checksum when the CRC equals the first byte by coincidence, so both sides must
+    /// use the same mode.
+    None,
+    /// 8-bit CRC
+    Crc8(Crc<u8, NoTable>),
+    /// 16-bit CRC, sent in little-endian byte order
+    Crc16(Crc<u16, NoTable>),
+}
+
+impl Checksum {
+    pub fn crc8(algorithm: &'static Algorithm<u8>) -> Self {
+        Self::Crc8(Crc::<u8, NoTable>::new(algorithm))
+    }
+
+    pub fn crc16(algorithm: &'static Algorithm<u16>) -> Self {
+        Self::Crc16(Crc::<u16, NoTable>::new(algorithm))
+    }
+
+    /// Returns amount of bytes checksum takes in packet
+    pub fn len(&self) -> usize {
+        match self {
+            Self::None | Self::Crc8(_) => 1,
+            Self::Crc16(_) => 2,
+        }
+    }
+
+    /// Returns `true` if checksum is calculated and verified
+    pub fn is_enabled(&self) -> bool {
+        !matches!(self, Self::None)
+    }
+
+    /// Calculates checksum bytes of `data` in the order they are sent
+    pub fn calculate(&self, data: &[u8]) -> heapless::Vec<u8, MAX_CHECKSUM_SIZE> {
+        let mut out = heapless::Vec::new();
+
+        match self {
+            Self::None => {
+                out.push(data.first().copied().unwrap_or(0)).unwrap();
+            }
+            Self::Crc8(crc) => {
+                out.push(crc.checksum(data)).unwrap();
+            }
+            Self::Crc16(crc) => {
+                out.extend_from_slice(&crc.checksum(data).to_le_bytes())
+                    .unwrap();
+            }
+        }
+
+        out
+    }
+
+    /// Verifies that `checksum` received matches `data`.
+    ///
+    /// Always `true` if checksum is disabled.
+    pub fn verify(&self, data: &[u8], checksum: &[u8]) -> bool {
+        if !self.is_enabled() {
+            return true;
+        }
+
+        self.calculate(data).as_slice() == checksum
+    }
+}
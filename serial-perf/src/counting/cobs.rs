@@ -0,0 +1,47 @@
+//!
+//! COBS framing of counting packets, an alternative to the separator
+//!
+
+use heapless::Vec;
+
+/// Byte delimiting COBS frames
+pub const COBS_DELIMITER: u8 = 0;
+
+/// Amount of bytes COBS framing adds to packet: overhead byte and delimiter
+///
+/// Packets are shorter than 254 bytes, so the encoding adds exactly one byte.
+pub const COBS_FRAMING_SIZE: usize = 2;
+
+/// Encodes packet made by `into_packet` without separator into COBS frame, both are stored reversed.
+///
+/// # Panics
+/// If the frame doesn't fit into `MAX_PACKET_SIZE`
+pub fn encode<const MAX_PACKET_SIZE: usize>(
+    reversed_packet: Vec<u8, MAX_PACKET_SIZE>,
+) -> Vec<u8, MAX_PACKET_SIZE> {
+    let mut packet = reversed_packet;
+    packet.reverse();
+
+    let mut frame = [0; MAX_PACKET_SIZE];
+    let len = cobs::try_encode(&packet, &mut frame).expect("MAX_PACKET_SIZE must fit COBS frame");
+
+    let mut out = Vec::new();
+    out.push(COBS_DELIMITER)
+        .expect("MAX_PACKET_SIZE must fit COBS frame");
+    for byte in frame[..len].iter().rev() {
+        out.push(*byte).unwrap();
+    }
+
+    out
+}
+
+/// Decodes COBS frame received without delimiter in place, returns `false` if the frame is malformed
+pub fn decode<const MAX_PACKET_SIZE: usize>(frame: &mut Vec<u8, MAX_PACKET_SIZE>) -> bool {
+    match cobs::decode_in_place(frame) {
+        Ok(len) => {
+            frame.truncate(len);
+            true
+        }
+        Err(_) => false,
+    }
+}
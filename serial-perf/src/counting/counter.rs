@@ -1,34 +1,80 @@
-use crc::Crc;
-
-use super::MAX_PACKET_SIZE;
+use super::checksum::Checksum;
 use core::fmt::Debug;
 
+/// Byte order of counter bytes on wire
+///
+/// Both sides must use the same byte order. The checksum is calculated over the bytes in wire order.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ByteOrder {
+    /// The least significant byte is sent first
+    #[default]
+    Little,
+    /// The most significant byte is sent first
+    Big,
+}
+
+impl ByteOrder {
+    /// Converts little-endian bytes to this order and back
+    pub(crate) fn apply<Bytes: LeBytes>(self, mut bytes: Bytes) -> Bytes {
+        if self == ByteOrder::Big {
+            bytes.reverse();
+        }
+        bytes
+    }
+}
+
 /// Internal bytes for counter that should always have non zero bytes
 pub trait LeBytes: Sized + Debug {
-    fn from_slice_checked(slice: &[u8], checksum: Option<u8>) -> Option<Self>;
+    /// Creates bytes from slice verifying it with `received_checksum`.
+    ///
+    /// The `received_checksum` is ignored if checksum disabled.
+    fn from_slice_checked(
+        slice: &[u8],
+        checksum: &Checksum,
+        received_checksum: &[u8],
+    ) -> Option<Self>;
     /// Returns package for sending these bytes.
     ///
     /// If checksum enabled crc will be calculated and appended to the end of packet,
     /// otherwise it will be set to value of the first byte.
-    fn into_packet(self, checksum_enabled: bool) -> heapless::Vec<u8, MAX_PACKET_SIZE>;
+    /// The bytes are XORed with `separator` so they never equal it, the checksum is calculated before that.
+    /// Without separator the bytes are sent as is followed by checksum.
+    ///
+    /// # Panics
+    /// If the packet doesn't fit into `MAX_PACKET_SIZE`
+    fn into_packet<const MAX_PACKET_SIZE: usize>(
+        self,
+        checksum: &Checksum,
+        separator: Option<u8>,
+    ) -> heapless::Vec<u8, MAX_PACKET_SIZE>;
 
     fn ones() -> Self;
 
     fn filled() -> Self;
+
+    /// Reverses order of the bytes
+    fn reverse(&mut self);
+
+    /// Returns the bytes as slice, the least significant byte first
+    fn as_slice(&self) -> &[u8];
+
+    /// Returns the bytes as mutable slice, the least significant byte first
+    fn as_mut_slice(&mut self) -> &mut [u8];
 }
 
 impl<const N: usize> LeBytes for [u8; N] {
-    fn from_slice_checked(slice: &[u8], checksum: Option<u8>) -> Option<Self> {
+    fn from_slice_checked(
+        slice: &[u8],
+        checksum: &Checksum,
+        received_checksum: &[u8],
+    ) -> Option<Self> {
         if N != slice.len() {
             return None;
         }
 
-        if let Some(checksum) = checksum {
-            let crc = Crc::<u8>::new(&crc::CRC_8_AUTOSAR);
-            let checksum_input = crc.checksum(slice);
-            if checksum_input != checksum {
-                return None;
-            }
+        if !checksum.verify(slice, received_checksum) {
+            return None;
         }
 
         let mut out: Self = [0; N];
@@ -36,23 +82,25 @@ impl<const N: usize> LeBytes for [u8; N] {
         Some(out)
     }
 
-    fn into_packet(self, checksum_enabled: bool) -> heapless::Vec<u8, MAX_PACKET_SIZE> {
+    fn into_packet<const MAX_PACKET_SIZE: usize>(
+        self,
+        checksum: &Checksum,
+        separator: Option<u8>,
+    ) -> heapless::Vec<u8, MAX_PACKET_SIZE> {
         let mut out = heapless::Vec::new();
-        let mut crc_data = heapless::Vec::<_, MAX_PACKET_SIZE>::new();
 
-        for byte in self {
-            out.insert(0, byte).unwrap();
-            crc_data.push(byte).unwrap();
+        // The packet is stored reversed: the last byte is sent first
+        for byte in checksum.calculate(&self).iter().rev() {
+            out.push(*byte).unwrap();
         }
-
-        let mut checksum = crc_data.first().copied().unwrap_or(0);
-        if checksum_enabled {
-            let crc = Crc::<u8>::new(&crc::CRC_8_AUTOSAR);
-            checksum = crc.checksum(crc_data.as_slice());
+        if let Some(separator) = separator {
+            out.push(separator).unwrap();
         }
 
-        out.insert(0, 0).unwrap();
-        out.insert(0, checksum).unwrap();
+        let mask = separator.unwrap_or(0);
+        for byte in self.iter().rev() {
+            out.push(byte ^ mask).unwrap();
+        }
 
         out
     }
@@ -64,20 +112,207 @@ impl<const N: usize> LeBytes for [u8; N] {
     fn filled() -> Self {
         [0xFF; N]
     }
+
+    fn reverse(&mut self) {
+        <[u8]>::reverse(self)
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        self
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        self
+    }
+}
+
+/// Position of a counter relative to another one in the cyclic sequence, see `Counter::delta`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CounterDelta {
+    /// The counters are equal
+    Same,
+    /// The counter is ahead by the number of `pop` calls
+    Forward(usize),
+    /// The counter is behind by the number of `push` calls
+    Backward(usize),
+}
+
+/// Sequence of counter values on wire
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Sequence {
+    /// Values of `Counter`, the counter bytes are never null
+    #[default]
+    Counter,
+    /// Every value of counter bytes read as little-endian integer, for framings allowing null bytes.
+    ///
+    /// Only the first 8 bytes count, the rest of wider counter bytes stay `0x01`.
+    #[cfg(feature = "cobs")]
+    Plain,
 }
 
-pub trait Counter: Default + Debug {
+impl Sequence {
+    /// Converts the first value of `Counter` sequence to the value of this sequence at the same position
+    pub fn first<Number: Counter>(self, start: Number) -> Number {
+        match self {
+            Sequence::Counter => start,
+            #[cfg(feature = "cobs")]
+            Sequence::Plain => from_plain(start.normalize().map_or(0, |n| n.as_u64())),
+        }
+    }
+
+    /// Moves `number` to the next value in the sequence and returns its previous value
+    pub fn next<Number: Counter>(self, number: &mut Number) -> Number {
+        match self {
+            Sequence::Counter => number.pop(),
+            #[cfg(feature = "cobs")]
+            Sequence::Plain => {
+                let out = *number;
+                let next = plain_value(number).wrapping_add(1) & plain_mask::<Number>();
+                *number = from_plain(next);
+                out
+            }
+        }
+    }
+
+    /// Returns `true` if `number` is in the sequence
+    pub fn contains<Number: Counter>(self, number: &Number) -> bool {
+        match self {
+            Sequence::Counter => number.normalize().is_some(),
+            #[cfg(feature = "cobs")]
+            Sequence::Plain => true,
+        }
+    }
+
+    /// Returns position of `number` in the sequence, see `Counter::normalize`
+    pub fn normalize<Number: Counter>(self, number: &Number) -> Option<Number> {
+        match self {
+            Sequence::Counter => number.normalize(),
+            #[cfg(feature = "cobs")]
+            Sequence::Plain => Some(*number),
+        }
+    }
+
+    /// Returns position of the last value of the sequence
+    pub fn max_normalized<Number: Counter>(self) -> u64 {
+        match self {
+            Sequence::Counter => Number::MAX_NORMALIZED.as_u64(),
+            #[cfg(feature = "cobs")]
+            Sequence::Plain => plain_mask::<Number>(),
+        }
+    }
+
+    /// Returns the number of `next` calls moving `from` to `to`, see `Counter::distance`
+    pub fn distance<Number: Counter>(self, from: &Number, to: &Number) -> usize {
+        match self {
+            Sequence::Counter => from.distance(to),
+            #[cfg(feature = "cobs")]
+            Sequence::Plain => {
+                let distance =
+                    plain_value(to).wrapping_sub(plain_value(from)) & plain_mask::<Number>();
+                usize::try_from(distance).unwrap_or(usize::MAX)
+            }
+        }
+    }
+
+    /// Returns position of `to` relative to `from` by the shorter way around the sequence, see `Counter::delta`
+    pub fn delta<Number: Counter>(self, from: &Number, to: &Number) -> CounterDelta {
+        match self {
+            Sequence::Counter => from.delta(to),
+            #[cfg(feature = "cobs")]
+            Sequence::Plain => {
+                let forward = self.distance(from, to);
+                if forward == 0 {
+                    return CounterDelta::Same;
+                }
+
+                let backward = self.distance(to, from);
+                if forward <= backward {
+                    CounterDelta::Forward(forward)
+                } else {
+                    CounterDelta::Backward(backward)
+                }
+            }
+        }
+    }
+}
+
+/// Returns counter bytes read as little-endian integer
+#[cfg(feature = "cobs")]
+fn plain_value<Number: Counter>(number: &Number) -> u64 {
+    let bytes = number.to_le_bytes();
+    bytes
+        .as_slice()
+        .iter()
+        .rev()
+        .fold(0, |value, byte| (value << 8) | u64::from(*byte))
+}
+
+/// Returns counter with bytes of `value` in little-endian order
+#[cfg(feature = "cobs")]
+fn from_plain<Number: Counter>(value: u64) -> Number {
+    let mut bytes = Number::Bytes::ones();
+    for (byte, value_byte) in bytes.as_mut_slice().iter_mut().zip(value.to_le_bytes()) {
+        *byte = value_byte;
+    }
+    Number::from_le_bytes(bytes)
+}
+
+/// Returns the maximum little-endian integer fitting counter bytes
+#[cfg(feature = "cobs")]
+fn plain_mask<Number: Counter>() -> u64 {
+    match core::mem::size_of::<Number::Bytes>() {
+        width @ 0..8 => (1 << (8 * width)) - 1,
+        _ => u64::MAX,
+    }
+}
+
+/// Cyclic sequence of values sent by the counting test
+///
+/// Implemented for unsigned integers which count up skipping values with null bytes. It can be implemented
+/// for a custom type to send other patterns, e.g. values of LFSR, as long as the receiver can tell
+/// the position of each value in the sequence. For loss detection to work the implementation must uphold:
+/// - every value of the sequence has no null bytes in `to_le_bytes` and `from_le_bytes` is its inverse;
+/// - `Default` value is not in the sequence, so `normalize` returns `None` for it;
+/// - `normalize` returns position of a value in the sequence, from 0 for `min_counter` to `MAX_NORMALIZED`
+///   for `max_counter`, and `to_counter_value` is its inverse returning `None` for positions out of range;
+/// - `pop` moves to the next value in the sequence, wrapping from `max_counter` to `min_counter`,
+///   and `push` moves back;
+/// - `distance` is the number of `pop` calls needed to get from one value to another.
+///
+/// The sequence is restarted from `min_counter` when the counter is not in the sequence.
+pub trait Counter: Default + Debug + Copy {
     type Bytes: LeBytes;
 
-    /// Increment the counter and return its previous value.
+    /// The maximum normalized value, `max_counter().normalize()`
+    const MAX_NORMALIZED: Self;
+
+    /// Moves the counter to the next value in the sequence and returns its previous value.
     fn pop(&mut self) -> Self;
-    /// Decrement the counter.
+    /// Moves the counter to the previous value in the sequence.
     fn push(&mut self);
-    /// Gets the one sided distance between two counters.
+    /// Gets the one sided distance between two counters, the number of `pop` calls moving `self` to `value`.
     ///
     /// If self < value then the distance is value-self, otherwise it's the distance to the MAX and from 1 to value.
     fn distance(&self, value: &Self) -> usize;
 
+    /// Gets position of `value` relative to `self` by the shorter way around the sequence.
+    ///
+    /// Unlike `distance` a value slightly behind `self`, e.g. a delayed packet, is `Backward` instead of
+    /// a jump almost over the whole sequence. Values exactly half the sequence away are `Forward`.
+    fn delta(&self, value: &Self) -> CounterDelta {
+        let forward = self.distance(value);
+        if forward == 0 {
+            return CounterDelta::Same;
+        }
+
+        let backward = value.distance(self);
+        if forward <= backward {
+            CounterDelta::Forward(forward)
+        } else {
+            CounterDelta::Backward(backward)
+        }
+    }
+
     fn to_le_bytes(&self) -> Self::Bytes;
     fn from_le_bytes(bytes: Self::Bytes) -> Self;
 
@@ -86,18 +321,23 @@ pub trait Counter: Default + Debug {
     /// Converts a value into a counter. Reverse of `normalize`.
     fn to_counter_value(self) -> Option<Self>;
 
+    /// Returns the value as `u64` without any conversion, use on normalized values
+    fn as_u64(&self) -> u64;
+
+    /// Returns the first value of the sequence, the value with all bytes set to 1 by default
     fn min_counter() -> Self {
         let ones = Self::Bytes::ones();
         Self::from_le_bytes(ones)
     }
 
+    /// Returns the last value of the sequence, the value with all bytes filled by default
     fn max_counter() -> Self {
         let filled = Self::Bytes::filled();
         Self::from_le_bytes(filled)
     }
 
     fn max_normalized() -> Self {
-        Self::max_counter().normalize().unwrap()
+        Self::MAX_NORMALIZED
     }
 }
 
@@ -106,6 +346,9 @@ macro_rules! impl_counter {
         impl Counter for $x {
             type Bytes = [u8; $sz];
 
+            // Each byte has 255 possible values [1..255]
+            const MAX_NORMALIZED: Self = (255 as $x).pow($sz) - 1;
+
             fn pop(&mut self) -> Self {
                 if self.normalize().is_none() {
                     *self = Self::min_counter();
@@ -115,7 +358,7 @@ macro_rules! impl_counter {
 
                 // Safe: We checked above and the counter is valid.
                 let mut normalized = unsafe { self.normalize().unwrap_unchecked() };
-                if normalized == Self::max_normalized() {
+                if normalized == Self::MAX_NORMALIZED {
                     normalized = 0;
                 } else {
                     normalized += 1;
@@ -134,7 +377,7 @@ macro_rules! impl_counter {
                 // Safe: We checked above and the counter is valid.
                 let mut normalized = unsafe { self.normalize().unwrap_unchecked() };
                 if normalized == 0 {
-                    normalized = Self::max_normalized();
+                    normalized = Self::MAX_NORMALIZED;
                 } else {
                     normalized -= 1;
                 }
@@ -155,7 +398,7 @@ macro_rules! impl_counter {
                     return normalized_right as usize - normalized_left as usize;
                 }
 
-                let to_max = Self::max_normalized() - normalized_left;
+                let to_max = Self::MAX_NORMALIZED - normalized_left;
                 let from_min = normalized_right /*- 0*/ + 1;
 
                 to_max as usize + from_min as usize
@@ -165,6 +408,10 @@ macro_rules! impl_counter {
                 Self::to_le_bytes(*self)
             }
 
+            fn as_u64(&self) -> u64 {
+                *self as u64
+            }
+
             fn from_le_bytes(bytes: Self::Bytes) -> Self {
                 Self::from_le_bytes(bytes)
             }
@@ -184,7 +431,7 @@ macro_rules! impl_counter {
             }
 
             fn to_counter_value(self) -> Option<Self> {
-                if Self::max_normalized() < self {
+                if Self::MAX_NORMALIZED < self {
                     return None;
                 }
 
@@ -226,6 +473,7 @@ impl_counter!(u64, 8);
 
 #[cfg(test)]
 mod tests {
+    use super::super::{DEFAULT_CRC16_ALGORITHM, DEFAULT_CRC_ALGORITHM, DEFAULT_MAX_PACKET_SIZE};
     use super::*;
 
     /// Test that incrementing and decrementing is working
@@ -379,6 +627,25 @@ mod tests {
         assert_eq!(test_counter.distance(&pop_value), 254);
     }
 
+    #[cfg(any(
+        target_pointer_width = "16",
+        target_pointer_width = "32",
+        target_pointer_width = "64"
+    ))]
+    #[test]
+    fn delta_u8() {
+        let first = u8::min_counter();
+        let last = u8::max_counter();
+
+        assert_eq!(first.delta(&first), CounterDelta::Same);
+        assert_eq!(first.delta(&0x03), CounterDelta::Forward(2));
+        assert_eq!(0x03.delta(&first), CounterDelta::Backward(2));
+
+        // Wrapping around the end of sequence
+        assert_eq!(last.delta(&first), CounterDelta::Forward(1));
+        assert_eq!(first.delta(&last), CounterDelta::Backward(1));
+    }
+
     #[cfg(any(
         target_pointer_width = "16",
         target_pointer_width = "32",
@@ -388,17 +655,18 @@ mod tests {
     fn double_conversion() {
         let test_counter = 5_u16;
         let as_le_bytes = test_counter.to_le_bytes();
-        let mut as_data_queue = as_le_bytes.into_packet(true);
+        let crc = Checksum::crc8(DEFAULT_CRC_ALGORITHM);
+        let mut as_data_queue = as_le_bytes.into_packet::<DEFAULT_MAX_PACKET_SIZE>(&crc, Some(0));
         assert_eq!(as_data_queue.len(), 2 + 1 + 1); // +1 for null terminator +1 crc
 
-        let crc = *as_data_queue.first().unwrap();
+        let checksum = *as_data_queue.first().unwrap();
 
-        let mut recv_side = heapless::Vec::<u8, MAX_PACKET_SIZE>::new();
+        let mut recv_side = heapless::Vec::<u8, DEFAULT_MAX_PACKET_SIZE>::new();
         for _ in 0..2 {
             recv_side.push(as_data_queue.pop().unwrap()).unwrap();
         }
 
-        let recv_bytes = <u16 as Counter>::Bytes::from_slice_checked(&recv_side, Some(crc))
+        let recv_bytes = <u16 as Counter>::Bytes::from_slice_checked(&recv_side, &crc, &[checksum])
             .expect("failed to create from slice");
 
         assert_eq!(as_le_bytes, recv_bytes);
@@ -416,18 +684,20 @@ mod tests {
     fn double_conversion_no_checksum() {
         let test_counter = 5_u16;
         let as_le_bytes = test_counter.to_le_bytes();
-        let mut as_data_queue = as_le_bytes.into_packet(false);
+        let mut as_data_queue =
+            as_le_bytes.into_packet::<DEFAULT_MAX_PACKET_SIZE>(&Checksum::None, Some(0));
         assert_eq!(as_data_queue.len(), 2 + 1 + 1); // +1 for null terminator +1 crc
 
         let _crc = *as_data_queue.first().unwrap();
 
-        let mut recv_side = heapless::Vec::<u8, MAX_PACKET_SIZE>::new();
+        let mut recv_side = heapless::Vec::<u8, DEFAULT_MAX_PACKET_SIZE>::new();
         for _ in 0..2 {
             recv_side.push(as_data_queue.pop().unwrap()).unwrap();
         }
 
-        let recv_bytes = <u16 as Counter>::Bytes::from_slice_checked(&recv_side, None)
-            .expect("failed to create from slice");
+        let recv_bytes =
+            <u16 as Counter>::Bytes::from_slice_checked(&recv_side, &Checksum::None, &[])
+                .expect("failed to create from slice");
 
         assert_eq!(as_le_bytes, recv_bytes);
 
@@ -435,6 +705,97 @@ mod tests {
         assert_eq!(recv_value, test_counter)
     }
 
+    #[cfg(any(
+        target_pointer_width = "16",
+        target_pointer_width = "32",
+        target_pointer_width = "64"
+    ))]
+    #[test]
+    fn no_checksum_first_byte_not_verified() {
+        let crc = Checksum::crc8(DEFAULT_CRC_ALGORITHM);
+
+        // Values with every possible first byte
+        let mut counter = 0x0101_u16;
+        for _ in 0..255 {
+            let bytes = counter.pop().to_le_bytes();
+            let pseudo_checksum = Checksum::None.calculate(&bytes);
+            assert_eq!(pseudo_checksum, [bytes[0]]);
+
+            // Without checksum any received checksum byte is accepted
+            let wrong = [bytes[0] ^ 0xFF];
+            assert_eq!(
+                <u16 as Counter>::Bytes::from_slice_checked(&bytes, &Checksum::None, &wrong),
+                Some(bytes)
+            );
+
+            // With checksum a wrong byte is rejected
+            let wrong_crc = [crc.calculate(&bytes)[0] ^ 0xFF];
+            assert_eq!(
+                <u16 as Counter>::Bytes::from_slice_checked(&bytes, &crc, &wrong_crc),
+                None
+            );
+
+            // Pseudo-checksum of sender without checksum never passes CRC for these values
+            assert_eq!(
+                <u16 as Counter>::Bytes::from_slice_checked(&bytes, &crc, &pseudo_checksum),
+                None
+            );
+        }
+    }
+
+    #[cfg(any(
+        target_pointer_width = "16",
+        target_pointer_width = "32",
+        target_pointer_width = "64"
+    ))]
+    #[test]
+    fn checksum_algorithm_mismatch() {
+        let test_counter = 5_u16;
+        let crc_autosar = Checksum::crc8(&crc::CRC_8_AUTOSAR);
+        let crc_maxim = Checksum::crc8(&crc::CRC_8_MAXIM_DOW);
+
+        let as_le_bytes = test_counter.to_le_bytes();
+        let packet = as_le_bytes.into_packet::<DEFAULT_MAX_PACKET_SIZE>(&crc_maxim, Some(0));
+        let checksum = *packet.first().unwrap();
+
+        let accepted =
+            <u16 as Counter>::Bytes::from_slice_checked(&as_le_bytes, &crc_maxim, &[checksum]);
+        assert_eq!(accepted, Some(as_le_bytes));
+
+        let rejected =
+            <u16 as Counter>::Bytes::from_slice_checked(&as_le_bytes, &crc_autosar, &[checksum]);
+        assert_eq!(rejected, None);
+    }
+
+    #[cfg(target_pointer_width = "64")]
+    #[test]
+    fn double_conversion_crc16() {
+        let test_counter = 0x0102_0304_0506_0708_u64;
+        let crc = Checksum::crc16(DEFAULT_CRC16_ALGORITHM);
+        let as_le_bytes = test_counter.to_le_bytes();
+        let mut as_data_queue = as_le_bytes.into_packet::<DEFAULT_MAX_PACKET_SIZE>(&crc, Some(0));
+        assert_eq!(as_data_queue.len(), 8 + 1 + 2); // +1 for null terminator +2 crc
+
+        let mut recv_side = heapless::Vec::<u8, DEFAULT_MAX_PACKET_SIZE>::new();
+        for _ in 0..8 {
+            recv_side.push(as_data_queue.pop().unwrap()).unwrap();
+        }
+        assert_eq!(as_data_queue.pop(), Some(0));
+
+        let mut checksum = heapless::Vec::<u8, 2>::new();
+        while let Some(byte) = as_data_queue.pop() {
+            checksum.push(byte).unwrap();
+        }
+
+        let recv_bytes = <u64 as Counter>::Bytes::from_slice_checked(&recv_side, &crc, &checksum)
+            .expect("failed to create from slice");
+        assert_eq!(u64::from_le_bytes(recv_bytes), test_counter);
+
+        checksum[1] ^= 0x01;
+        let corrupted = <u64 as Counter>::Bytes::from_slice_checked(&recv_side, &crc, &checksum);
+        assert_eq!(corrupted, None);
+    }
+
     // #[cfg(any(
     //     target_pointer_width = "16",
     //     target_pointer_width = "32",
@@ -449,4 +810,143 @@ mod tests {
     //         println!("{}\t: {:04x}", i, pop_value);
     //     }
     // }
+
+    #[test]
+    fn max_normalized_const() {
+        assert_eq!(Some(u8::MAX_NORMALIZED), u8::max_counter().normalize());
+        assert_eq!(Some(u16::MAX_NORMALIZED), u16::max_counter().normalize());
+        #[cfg(any(target_pointer_width = "32", target_pointer_width = "64"))]
+        assert_eq!(Some(u32::MAX_NORMALIZED), u32::max_counter().normalize());
+        #[cfg(target_pointer_width = "64")]
+        assert_eq!(Some(u64::MAX_NORMALIZED), u64::max_counter().normalize());
+    }
+
+    /// 8-bit Galois LFSR walking all non zero bytes
+    #[derive(Debug, Default, Clone, Copy, PartialEq)]
+    struct Lfsr(u8);
+
+    impl Lfsr {
+        fn next(self) -> Self {
+            let shifted = self.0 >> 1;
+            if self.0 & 1 == 1 {
+                Self(shifted ^ 0xB8)
+            } else {
+                Self(shifted)
+            }
+        }
+    }
+
+    impl Counter for Lfsr {
+        type Bytes = [u8; 1];
+
+        const MAX_NORMALIZED: Self = Self(254);
+
+        fn pop(&mut self) -> Self {
+            if self.normalize().is_none() {
+                *self = Self::min_counter();
+            }
+
+            let out = *self;
+            *self = self.next();
+            out
+        }
+
+        fn push(&mut self) {
+            let position = self.normalize().map_or(0, |p| p.0);
+            let previous = Self(((u16::from(position) + 254) % 255) as u8);
+            *self = previous.to_counter_value().unwrap();
+        }
+
+        fn distance(&self, value: &Self) -> usize {
+            let from = self.normalize().unwrap().0 as usize;
+            let to = value.normalize().unwrap().0 as usize;
+            (to + 255 - from) % 255
+        }
+
+        fn to_le_bytes(&self) -> Self::Bytes {
+            [self.0]
+        }
+
+        fn from_le_bytes(bytes: Self::Bytes) -> Self {
+            Self(bytes[0])
+        }
+
+        fn normalize(&self) -> Option<Self> {
+            if self.0 == 0 {
+                return None;
+            }
+
+            let mut value = Self::min_counter();
+            let mut position = 0;
+            while value != *self {
+                value = value.next();
+                position += 1;
+            }
+
+            Some(Self(position))
+        }
+
+        fn to_counter_value(self) -> Option<Self> {
+            if self.0 > Self::MAX_NORMALIZED.0 {
+                return None;
+            }
+
+            let mut value = Self::min_counter();
+            for _ in 0..self.0 {
+                value = value.next();
+            }
+
+            Some(value)
+        }
+
+        fn as_u64(&self) -> u64 {
+            self.0 as u64
+        }
+
+        fn max_counter() -> Self {
+            Self::MAX_NORMALIZED.to_counter_value().unwrap()
+        }
+    }
+
+    #[test]
+    fn custom_sequence() {
+        let mut lfsr = Lfsr::default();
+        let first = lfsr.pop();
+        assert_eq!(first, Lfsr::min_counter());
+
+        let mut seen = [false; 256];
+        let mut value = first;
+        for _ in 0..255 {
+            assert!(!seen[value.0 as usize]);
+            seen[value.0 as usize] = true;
+            value = value.next();
+        }
+        assert_eq!(value, first);
+
+        assert_eq!(Lfsr::max_counter().distance(&first), 1);
+        assert_eq!(Lfsr::max_counter().normalize(), Some(Lfsr::MAX_NORMALIZED));
+    }
+
+    #[test]
+    fn custom_sequence_loss() {
+        use crate::counting::Counting;
+        use crate::statistics::CountingStatistics;
+
+        let mut counting = Counting::<_, Lfsr>::new(
+            (),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+
+        let mut buf = [0; 3 * 300];
+        counting.fill_send_slice(&mut buf);
+
+        // Drop the 10th packet
+        counting.recv_slice(&buf[..3 * 9]);
+        counting.recv_slice(&buf[3 * 10..]);
+
+        assert_eq!(counting.loss_stats().failed(), 1);
+        assert_eq!(counting.loss_stats().successful(), 299);
+    }
 }
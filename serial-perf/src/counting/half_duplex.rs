@@ -0,0 +1,106 @@
+use core::time::Duration;
+
+use embedded_hal_nb::nb::Error;
+use embedded_hal_nb::serial::{Read, Write};
+
+use crate::clock::{Clock, ClockExt};
+use crate::statistics::Statistics;
+
+use super::counter::Counter;
+use super::Counting;
+
+/// Direction of half-duplex transceiver
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Direction {
+    /// Transmitting, e.g. DE line of RS-485 transceiver is high
+    Tx,
+    /// Receiving, e.g. RE line of RS-485 transceiver is low
+    Rx,
+}
+
+impl<Serial, Number, TxStats, RxStats, LossStats, const MAX_PACKET_SIZE: usize>
+    Counting<Serial, Number, TxStats, RxStats, LossStats, MAX_PACKET_SIZE>
+where
+    Serial: Write + Read,
+    Number: Counter,
+    TxStats: Statistics,
+    RxStats: Statistics,
+    LossStats: Statistics,
+{
+    /// Runs one cycle of half-duplex test: sends a burst of `packets` and receives for `rx_window`.
+    ///
+    /// `set_direction` is called to switch the transceiver before sending and before receiving.
+    /// The burst is flushed before switching to receive, but some serials report flush before the last
+    /// bit leaves the wire, so add a delay to `set_direction` if the last byte is cut off.
+    /// The peer is expected to echo the burst back, e.g. with `Loopback`, or to send its own.
+    /// Returns early on serial error.
+    pub fn half_duplex_cycle<Clk: Clock>(
+        &mut self,
+        clock: &Clk,
+        packets: usize,
+        rx_window: Duration,
+        mut set_direction: impl FnMut(Direction),
+    ) -> core::result::Result<(), Serial::Error> {
+        set_direction(Direction::Tx);
+        for _ in 0..packets {
+            self.send()?;
+            while !self.tx_state.is_packet_sent() {
+                self.send()?;
+            }
+        }
+        self.flush()?;
+
+        set_direction(Direction::Rx);
+        let start = clock.now();
+        while clock.elapsed_since(start) < rx_window {
+            match self.recv_nb() {
+                Ok(()) | Err(Error::WouldBlock) => (),
+                Err(Error::Other(e)) => return Err(e),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use crate::clock::MockClock;
+    use crate::counting::{Counting, Direction};
+    use crate::paired_serial::PairedSerial;
+    use crate::statistics::CountingStatistics;
+    use crate::test_serial::ClockedSerial;
+
+    #[test]
+    fn burst_then_receive() {
+        let clock = MockClock::new();
+        let paired = PairedSerial::<64>::new();
+        let mut counting = Counting::<_, u16>::new(
+            // A millisecond passes on every read, so the receive window ends
+            ClockedSerial::new(paired.echo(), &clock).advance_on_read(Duration::from_millis(1)),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+
+        let mut directions = heapless::Vec::<Direction, 4>::new();
+        for _ in 0..2 {
+            counting
+                .half_duplex_cycle(&clock, 5, Duration::from_millis(100), |direction| {
+                    directions.push(direction).unwrap()
+                })
+                .unwrap();
+        }
+
+        assert_eq!(
+            directions,
+            [Direction::Tx, Direction::Rx, Direction::Tx, Direction::Rx]
+        );
+        assert_eq!(counting.tx_packets(), 10);
+        assert_eq!(counting.loss_stats().successful(), 10);
+        assert_eq!(counting.loss_stats().failed(), 0);
+    }
+}
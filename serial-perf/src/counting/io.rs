@@ -0,0 +1,185 @@
+use embedded_io::{Read, ReadReady, Write};
+
+use crate::statistics::Statistics;
+use crate::stream_error::StreamError;
+
+use super::counter::Counter;
+use super::Counting;
+
+/// Size of buffer used to read bytes in one call
+const READ_BUFFER_SIZE: usize = 64;
+
+impl<Serial, Number, TxStats, RxStats, LossStats, const MAX_PACKET_SIZE: usize>
+    Counting<Serial, Number, TxStats, RxStats, LossStats, MAX_PACKET_SIZE>
+where
+    Serial: Read,
+    Number: Counter,
+    TxStats: Statistics,
+    RxStats: Statistics,
+    LossStats: Statistics,
+{
+    /// Receive bytes from the serial port and verify them. Blocks until any byte is received.
+    ///
+    /// Returns `StreamError::Closed` if the serial reached end of file, it's counted as failed receive.
+    pub fn recv_io(&mut self) -> Result<(), StreamError<Serial::Error>> {
+        let mut bytes_read = [0; READ_BUFFER_SIZE];
+        match self.serial.read(&mut bytes_read) {
+            Ok(0) => {
+                self.rx_stats.add_failed(1);
+                Err(StreamError::Closed)
+            }
+            Ok(read) => {
+                for byte in &bytes_read[..read] {
+                    self.on_byte_received(*byte);
+                }
+                Ok(())
+            }
+            Err(e) => {
+                self.rx_stats.add_failed(1);
+                Err(StreamError::Serial(e))
+            }
+        }
+    }
+}
+
+impl<Serial, Number, TxStats, RxStats, LossStats, const MAX_PACKET_SIZE: usize>
+    Counting<Serial, Number, TxStats, RxStats, LossStats, MAX_PACKET_SIZE>
+where
+    Serial: Write,
+    Number: Counter,
+    TxStats: Statistics,
+    RxStats: Statistics,
+    LossStats: Statistics,
+{
+    /// Sends the rest of current packet. Blocks until any byte is accepted by serial.
+    ///
+    /// Returns `StreamError::Closed` if the serial accepted no bytes, it's counted as failed send.
+    /// Flushes the serial every N bytes if configured with `CountingBuilder::flush_every`.
+    pub fn send_io(&mut self) -> Result<(), StreamError<Serial::Error>> {
+        let mut bytes_to_send = [0; MAX_PACKET_SIZE];
+        let len = self.tx_state.peek_packet(&mut bytes_to_send);
+
+        match self.serial.write(&bytes_to_send[..len]) {
+            Ok(0) => {
+                self.tx_stats.add_failed(1);
+                Err(StreamError::Closed)
+            }
+            Ok(written) => {
+                for _ in 0..written {
+                    self.on_byte_sent();
+                }
+                self.flush_cadence.on_written(written);
+                if self.flush_cadence.is_due() {
+                    self.serial.flush().map_err(StreamError::Serial)?;
+                    self.flush_cadence.on_flushed();
+                }
+                Ok(())
+            }
+            Err(e) => {
+                self.tx_stats.add_failed(1);
+                Err(StreamError::Serial(e))
+            }
+        }
+    }
+
+    /// Flushes serial port, blocks until all data is sent.
+    ///
+    /// # Warning
+    /// The error happened here will not affect tx_state
+    pub fn flush_io(&mut self) -> Result<(), Serial::Error> {
+        self.serial.flush()
+    }
+}
+
+impl<Serial, Number, TxStats, RxStats, LossStats, const MAX_PACKET_SIZE: usize>
+    Counting<Serial, Number, TxStats, RxStats, LossStats, MAX_PACKET_SIZE>
+where
+    Serial: Write + Read + ReadReady,
+    Number: Counter,
+    TxStats: Statistics,
+    RxStats: Statistics,
+    LossStats: Statistics,
+{
+    /// Sends the rest of current packet then receives bytes if any ready to be read.
+    ///
+    /// Reading does not block so the sending is not stalled if the other side is silent.
+    pub fn loop_io(&mut self) -> Result<(), StreamError<Serial::Error>> {
+        self.send_io()?;
+
+        if self.serial.read_ready().map_err(StreamError::Serial)? {
+            self.recv_io()?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::counting::Counting;
+    use crate::paired_serial::PairedSerial;
+    use crate::statistics::CountingStatistics;
+    use crate::stream_error::StreamError;
+    use crate::test_serial::ScriptedSerial;
+
+    #[test]
+    fn loop_io_no_loss() {
+        let paired = PairedSerial::<64>::new();
+        let mut counting = Counting::<_, u16>::new(
+            paired.echo(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+
+        for _ in 0..1000 {
+            counting.loop_io().unwrap();
+        }
+
+        assert_eq!(counting.tx_stats().successful(), 4000);
+        assert_eq!(counting.rx_stats().successful(), 4000);
+        assert_eq!(counting.loss_stats().successful(), 1000);
+        assert_eq!(counting.loss_stats().failed(), 0);
+    }
+
+    #[test]
+    fn flush_every_io() {
+        let mut counting = Counting::<_, u16>::builder(
+            ScriptedSerial::new(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        )
+        .flush_every(6)
+        .build();
+
+        // A packet is 4 bytes, the flush is due after the second one
+        counting.send_io().unwrap();
+        assert_eq!(counting.serial().flushes(), 0);
+        counting.send_io().unwrap();
+        assert_eq!(counting.serial().flushes(), 1);
+        counting.send_io().unwrap();
+        assert_eq!(counting.serial().flushes(), 1);
+    }
+
+    #[test]
+    fn closed_serial() {
+        let paired = PairedSerial::<4>::new();
+        let (_peer, serial) = paired.split();
+        let mut counting = Counting::<_, u16>::new(
+            serial,
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+
+        // The peer reads nothing, the second packet doesn't fit
+        counting.send_io().unwrap();
+        assert!(matches!(counting.send_io(), Err(StreamError::Closed)));
+        assert!(matches!(counting.recv_io(), Err(StreamError::Closed)));
+
+        assert_eq!(counting.tx_stats().failed(), 1);
+        assert_eq!(counting.rx_stats().failed(), 1);
+        assert_eq!(counting.tx_packets(), 1);
+    }
+}
@@ -2,23 +2,76 @@
 //! Counting stress test that sent null-terminated numbers from 1 to type's max value.
 //!
 
+mod any;
 mod rx_state;
-use counter::Counter;
+pub use any::{AnyCounting, CounterWidth};
+pub use counter::{ByteOrder, Counter, CounterDelta};
 use rx_state::RxState;
+#[cfg(feature = "async")]
+mod asynch;
+mod builder;
+mod checksum;
+#[cfg(feature = "cobs")]
+mod cobs;
+pub use builder::CountingBuilder;
 mod counter;
+mod half_duplex;
+pub use half_duplex::Direction;
+#[cfg(feature = "embedded-io")]
+mod io;
 mod nb;
+mod preamble;
+pub use preamble::CheckedRecvError;
+mod quality;
+pub use quality::LinkQuality;
+mod summary;
+pub use summary::{CountingSummary, ProbeResult, RunSummary};
 mod tx_state;
 use tx_state::TxState;
 
 // Counting test packets structure
 // [0-8 bytes] - count
-// [1 byte] - null \0
-// [1 byte] - crc8 for count
+// [0-1 byte] - null \0 or custom separator, omitted without separator
+// [1-2 bytes] - crc8 or crc16 for count
+// [0.. bytes] - optional padding
+//
+// With COBS framing the count is sent as plain little-endian integer, the count and crc are COBS encoded
+// and followed by null delimiter
 
-const MAX_PACKET_SIZE: usize = 10; // 10 - 8 bytes if u64 and 1 byte for nul-terminator 1 byte for crc
+/// Default size of packet buffer, enough for any `Number` and checksum
+pub const DEFAULT_MAX_PACKET_SIZE: usize = 11; // 11 - 8 bytes if u64 and 1 byte for nul-terminator 2 bytes for crc16
 
+/// Returns size of packet on wire for `Number` with the default null separator and 8-bit checksum, or without checksum.
+///
+/// Without checksum the first byte of count is sent in its place, so the size is the same.
+/// Use `Counting::packet_size` for the size of other configurations, e.g. with 16-bit checksum or padding.
+pub const fn default_packet_size<Number: Counter>() -> usize {
+    core::mem::size_of::<Number::Bytes>() + 2
+}
+
+use crc::Algorithm;
+
+/// Byte used to pad packets to requested size
+const PADDING_BYTE: u8 = 0x55;
+
+/// CRC algorithm used for packets checksum by default
+pub const DEFAULT_CRC_ALGORITHM: &Algorithm<u8> = &crc::CRC_8_AUTOSAR;
+
+/// CRC algorithm suggested for 16-bit checksum mode
+pub const DEFAULT_CRC16_ALGORITHM: &Algorithm<u16> = &crc::CRC_16_IBM_3740;
+
+use crate::flush_cadence::FlushCadence;
 use crate::statistics::{CountingStatistics, Statistics};
 
+/// Error of the counting test configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigError {
+    /// Start value is greater than the maximum counter value of `Number`
+    StartValueOutOfRange,
+    /// `MAX_PACKET_SIZE` doesn't fit a packet of the requested framing
+    PacketSizeTooSmall,
+}
+
 /// Counting test is a test that sends a special increasing numbers
 /// with checksum and null separator and can receive these packets
 /// and calculate amount of lost packages by comparing the number in package
@@ -26,6 +79,9 @@ use crate::statistics::{CountingStatistics, Statistics};
 /// # Template parameters
 /// - `Serial` - serial device to use for communication
 /// - `Number` - a size of counter used. limited to size of usize. Can be u8, u16, u32, u64 on 64 bit platforms
+///   or a custom sequence implementing `Counter`
+/// - `MAX_PACKET_SIZE` - a size of buffer for packets. Must fit `Number` bytes, null terminator and checksum,
+///   that is checked at compile time. The default fits any `Number` and checksum.
 ///
 /// # Warning
 /// If `Counting` receives a packets from a different `Counting` they both must use same `Number` template argument.
@@ -35,36 +91,43 @@ pub struct Counting<
     TxStats = CountingStatistics,
     RxStats = CountingStatistics,
     LossStats = CountingStatistics,
+    const MAX_PACKET_SIZE: usize = DEFAULT_MAX_PACKET_SIZE,
 > {
     serial: Serial,
-    tx_state: TxState<Number>,
-    rx_state: RxState<Number, LossStats>,
+    tx_state: TxState<Number, MAX_PACKET_SIZE>,
+    rx_state: RxState<Number, LossStats, MAX_PACKET_SIZE>,
 
     tx_stats: TxStats,
     rx_stats: RxStats,
+
+    flush_cadence: FlushCadence,
 }
 
-impl<Serial, Number, TxStats, RxStats, LossStats>
-    Counting<Serial, Number, TxStats, RxStats, LossStats>
+impl<Serial, Number, TxStats, RxStats, LossStats, const MAX_PACKET_SIZE: usize>
+    Counting<Serial, Number, TxStats, RxStats, LossStats, MAX_PACKET_SIZE>
 where
     Number: Counter,
     TxStats: Statistics,
     RxStats: Statistics,
     LossStats: Statistics,
 {
+    /// Returns builder to configure the counting test
+    pub fn builder(
+        serial: Serial,
+        tx_stats: TxStats,
+        rx_stats: RxStats,
+        loss_stats: LossStats,
+    ) -> CountingBuilder<Serial, Number, TxStats, RxStats, LossStats, MAX_PACKET_SIZE> {
+        CountingBuilder::new(serial, tx_stats, rx_stats, loss_stats)
+    }
+
     pub fn new(
         serial: Serial,
         tx_stats: TxStats,
         rx_stats: RxStats,
         loss_stats: LossStats,
     ) -> Self {
-        Self {
-            serial,
-            tx_state: Default::default(),
-            rx_state: RxState::new(loss_stats),
-            tx_stats,
-            rx_stats,
-        }
+        Self::builder(serial, tx_stats, rx_stats, loss_stats).build()
     }
 
     pub fn new_without_checksum(
@@ -73,13 +136,51 @@ where
         rx_stats: RxStats,
         loss_stats: LossStats,
     ) -> Self {
-        Self {
-            serial,
-            tx_state: TxState::new_without_checksum(),
-            rx_state: RxState::new_without_checksum(loss_stats),
-            tx_stats,
-            rx_stats,
-        }
+        Self::builder(serial, tx_stats, rx_stats, loss_stats)
+            .checksum(false)
+            .build()
+    }
+
+    /// Creates counting test which uses specified CRC algorithm for packets checksum.
+    ///
+    /// The algorithms can be found in `crc` crate. Both sides must use the same algorithm.
+    pub fn new_with_crc(
+        serial: Serial,
+        algorithm: &'static Algorithm<u8>,
+        tx_stats: TxStats,
+        rx_stats: RxStats,
+        loss_stats: LossStats,
+    ) -> Self {
+        Self::builder(serial, tx_stats, rx_stats, loss_stats)
+            .crc_algorithm(algorithm)
+            .build()
+    }
+
+    /// Creates counting test which uses 16-bit CRC for packets checksum.
+    ///
+    /// This gives a better protection against corrupted packets for wide counters
+    /// at the cost of an extra byte per packet. Both sides must use the same algorithm.
+    pub fn new_with_crc16(
+        serial: Serial,
+        algorithm: &'static Algorithm<u16>,
+        tx_stats: TxStats,
+        rx_stats: RxStats,
+        loss_stats: LossStats,
+    ) -> Self {
+        Self::builder(serial, tx_stats, rx_stats, loss_stats)
+            .crc16_algorithm(algorithm)
+            .build()
+    }
+
+    /// Pads every packet with filler bytes to reach `packet_size` bytes on wire.
+    ///
+    /// The padding is appended after checksum and skipped by receiver, so both sides must use the same packet size.
+    /// If `packet_size` is less than the size of packet without padding, no padding is added.
+    pub fn with_packet_size(mut self, packet_size: usize) -> Self {
+        let padding = packet_size.saturating_sub(self.tx_state.unpadded_packet_size());
+        self.tx_state.set_padding(padding);
+        self.rx_state.set_padding(padding);
+        self
     }
 
     pub fn reset(&mut self) {
@@ -89,6 +190,21 @@ where
         self.rx_stats.reset();
     }
 
+    /// Resets only TX statistics, the test continues as is
+    pub fn reset_tx_stats(&mut self) {
+        self.tx_stats.reset();
+    }
+
+    /// Resets only RX statistics, the test continues as is
+    pub fn reset_rx_stats(&mut self) {
+        self.rx_stats.reset();
+    }
+
+    /// Resets only packet loss statistics, the test continues as is
+    pub fn reset_loss_stats(&mut self) {
+        self.rx_state.reset_loss_stats();
+    }
+
     pub fn tx_stats(&self) -> &TxStats {
         &self.tx_stats
     }
@@ -96,10 +212,25 @@ where
     pub fn rx_stats(&self) -> &RxStats {
         &self.rx_stats
     }
+
+    /// Returns reference to the underlying serial
+    pub fn serial(&self) -> &Serial {
+        &self.serial
+    }
+
+    /// Returns mutable reference to the underlying serial
+    pub fn serial_mut(&mut self) -> &mut Serial {
+        &mut self.serial
+    }
+
+    /// Destroys the wrapper returning the underlying serial
+    pub fn into_inner(self) -> Serial {
+        self.serial
+    }
 }
 
-impl<Serial, Number, TxStats, RxStats, LossStats>
-    Counting<Serial, Number, TxStats, RxStats, LossStats>
+impl<Serial, Number, TxStats, RxStats, LossStats, const MAX_PACKET_SIZE: usize>
+    Counting<Serial, Number, TxStats, RxStats, LossStats, MAX_PACKET_SIZE>
 where
     Number: Counter,
     TxStats: Statistics,
@@ -116,7 +247,352 @@ where
         self.tx_stats.add_successful(1);
     }
 
+    /// Handles bytes received by the application itself, e.g. with bulk reads of the serial
+    pub fn recv_slice(&mut self, buf: &[u8]) {
+        for byte in buf {
+            self.on_byte_received(*byte);
+        }
+    }
+
+    /// Fills `buf` with next bytes to send, returns amount of bytes filled.
+    ///
+    /// The bytes are counted as sent, so the application must send all of them, e.g. with bulk writes of the serial.
+    /// `CountingBuilder::flush_every` is not applied, the application flushes the serial itself.
+    pub fn fill_send_slice(&mut self, buf: &mut [u8]) -> usize {
+        for slot in buf.iter_mut() {
+            *slot = self.tx_state.peek();
+            self.on_byte_sent();
+        }
+
+        buf.len()
+    }
+
+    /// Stages the next packet to send ahead of time, e.g. before timing a benchmark.
+    ///
+    /// Nothing is sent and the same packet is sent next, only its preparation is moved out of `send`.
+    /// Hidden from docs, it's public only for the benchmarks of this crate.
+    #[doc(hidden)]
+    pub fn prime_tx(&mut self) {
+        self.tx_state.prime();
+    }
+
     pub fn loss_stats(&self) -> &LossStats {
         self.rx_state.loss_stats()
     }
+
+    /// Returns mutable reference to the loss statistics, e.g. to take collected events
+    pub fn loss_stats_mut(&mut self) -> &mut LossStats {
+        self.rx_state.loss_stats_mut()
+    }
+
+    /// Returns the maximum distance between two consecutive received numbers counted as packet loss.
+    pub fn max_gap(&self) -> usize {
+        self.rx_state.max_gap()
+    }
+
+    /// Sets the maximum distance between two consecutive received numbers counted as packet loss.
+    ///
+    /// A bigger jump is most likely caused by a corrupted packet or a desync, so instead of
+    /// adding an enormous loss, the receiver resyncs to the new number.
+    /// Defaults to half of the `Number` range.
+    pub fn set_max_gap(&mut self, max_gap: usize) {
+        self.rx_state.set_max_gap(max_gap);
+    }
+
+    /// Returns amount of resyncs caused by jumps bigger than `max_gap`
+    pub fn resync_count(&self) -> usize {
+        self.rx_state.resyncs()
+    }
+
+    /// Returns the maximum distance back from the last received number counted as reordered packet.
+    pub fn reorder_window(&self) -> usize {
+        self.rx_state.reorder_window()
+    }
+
+    /// Sets the maximum distance back from the last received number counted as reordered packet.
+    ///
+    /// Such packets are duplicated or delayed, they are counted by `reordered_count` and do not affect loss.
+    /// Defaults to 0, that is only duplicates of the last packet are detected.
+    ///
+    /// # Note
+    /// A delayed packet was already counted as lost when a later packet had been received.
+    pub fn set_reorder_window(&mut self, reorder_window: usize) {
+        self.rx_state.set_reorder_window(reorder_window);
+    }
+
+    /// Returns amount of duplicated or reordered packets received
+    pub fn reordered_count(&self) -> usize {
+        self.rx_state.reordered()
+    }
+
+    /// Returns size of packet on wire, including separator, checksum and padding
+    pub fn packet_size(&self) -> usize {
+        self.tx_state.packet_size()
+    }
+
+    /// Returns amount of packets sent completely, including padding
+    pub fn tx_packets(&self) -> usize {
+        self.tx_state.packets_sent()
+    }
+
+    /// Returns amount of bytes of the current packet not sent yet, including padding.
+    ///
+    /// A new packet is started only when the next byte is sent, so 0 means the last packet is sent completely.
+    pub fn tx_pending_bytes(&self) -> usize {
+        self.tx_state.pending_bytes()
+    }
+
+    /// Returns amount of valid packets received, including duplicated and reordered ones
+    pub fn rx_packets(&self) -> usize {
+        self.rx_state.packets_received()
+    }
+
+    /// Returns amount of received packets rejected by checksum or holding invalid counter value.
+    ///
+    /// These packets are corrupted on the line and counted as lost too, as their numbers are missing.
+    /// Invalid counter values, e.g. with null bytes, can be received only without checksum and separator.
+    pub fn crc_errors(&self) -> usize {
+        self.rx_state.crc_errors()
+    }
+
+    /// Returns amount of framing desyncs: packets overflowing `MAX_PACKET_SIZE` before the separator.
+    ///
+    /// It happens when a separator is lost and packets merge. Bytes received so far are dropped
+    /// and the receiver waits for the next separator.
+    pub fn desync_count(&self) -> usize {
+        self.rx_state.desyncs()
+    }
+
+    /// Returns `true` if at least one valid packet is received since the start or reset.
+    ///
+    /// Before that the loss statistics are meaningless, as no sequence is received to compare with.
+    pub fn has_synced(&self) -> bool {
+        self.rx_state.last_number().is_some()
+    }
+
+    /// Returns size of counter bytes advertised by the peer, `None` if the width preamble is not received.
+    ///
+    /// The preamble is received only if it's enabled with `CountingBuilder::width_preamble` on both sides.
+    pub fn peer_width(&self) -> Option<usize> {
+        self.rx_state.peer_width().map(usize::from)
+    }
+
+    /// Returns `true` if the peer advertised different size of counter bytes, so the loss statistics are meaningless
+    pub fn width_mismatch(&self) -> bool {
+        self.peer_width()
+            .is_some_and(|width| width != core::mem::size_of::<Number::Bytes>())
+    }
+
+    /// Returns the normalized number of the last valid packet received.
+    ///
+    /// Returns `None` if no valid packet has been received yet.
+    pub fn last_received(&self) -> Option<Number> {
+        let last = self.rx_state.last_number()?;
+        self.rx_state.sequence().normalize(last)
+    }
+
+    /// Returns the normalized numbers of the first and the last packet of the sequence.
+    ///
+    /// The numbers are saturated at `usize::MAX`.
+    pub fn counter_range(&self) -> (usize, usize) {
+        let max = self.tx_state.sequence().max_normalized::<Number>();
+        (0, usize::try_from(max).unwrap_or(usize::MAX))
+    }
+
+    /// Returns position of the last packet sent in the sequence in `[0.0, 1.0]`, 0.0 if nothing sent yet.
+    ///
+    /// The sequence wraps to the start after 1.0.
+    pub fn tx_progress(&self) -> f32 {
+        let Some(last) = self.last_sent() else {
+            return 0.0;
+        };
+
+        let max = self.tx_state.sequence().max_normalized::<Number>();
+        if max == 0 {
+            return 1.0;
+        }
+
+        (last.as_u64() as f64 / max as f64) as f32
+    }
+
+    /// Returns the normalized number of the last packet sent completely, including padding.
+    ///
+    /// Returns `None` if no packet has been sent completely yet.
+    pub fn last_sent(&self) -> Option<Number> {
+        let last = self.tx_state.last_number()?;
+        self.tx_state.sequence().normalize(&last)
+    }
+}
+
+impl<Serial, Number, TxStats, RxStats, const MAX_PACKET_SIZE: usize>
+    Counting<Serial, Number, TxStats, RxStats, CountingStatistics, MAX_PACKET_SIZE>
+where
+    Number: Counter,
+    TxStats: Statistics,
+    RxStats: Statistics,
+{
+    /// Returns amount of bytes lost: lost packets times packet size on wire.
+    ///
+    /// Unlike the loss statistics counting packets, it can be compared with the bytes of TX and RX statistics.
+    pub fn loss_bytes(&self) -> usize {
+        self.loss_stats()
+            .failed()
+            .saturating_mul(self.packet_size())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slices_roundtrip() {
+        let mut counting = Counting::<_, u16>::new(
+            (),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+
+        let mut buf = [0; 41];
+        for _ in 0..100 {
+            let len = counting.fill_send_slice(&mut buf);
+            counting.recv_slice(&buf[..len]);
+        }
+
+        assert_eq!(counting.tx_stats().successful(), 4100);
+        assert_eq!(counting.rx_stats().successful(), 4100);
+        assert_eq!(counting.loss_stats().successful(), 1025);
+        assert_eq!(counting.loss_stats().failed(), 0);
+        assert_eq!(counting.tx_packets(), 1025);
+        assert_eq!(counting.rx_packets(), 1025);
+    }
+
+    #[test]
+    fn default_packet_size_matches() {
+        const U16_PACKET_SIZE: usize = default_packet_size::<u16>();
+        assert_eq!(U16_PACKET_SIZE, 4);
+
+        let counting = Counting::<_, u32>::new(
+            (),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+        assert_eq!(counting.packet_size(), default_packet_size::<u32>());
+
+        let counting = Counting::<_, u32>::new_without_checksum(
+            (),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+        assert_eq!(counting.packet_size(), default_packet_size::<u32>());
+    }
+
+    #[test]
+    fn loss_bytes() {
+        let mut counting = Counting::<_, u16>::new(
+            (),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        )
+        .with_packet_size(6);
+        assert_eq!(counting.packet_size(), 6);
+
+        let mut buf = [0; 6 * 10];
+        counting.fill_send_slice(&mut buf);
+        counting.recv_slice(&buf[..6 * 2]);
+        counting.recv_slice(&buf[6 * 5..]);
+
+        assert_eq!(counting.loss_stats().failed(), 3);
+        assert_eq!(counting.loss_bytes(), 3 * 6);
+    }
+
+    #[test]
+    fn counter_range_and_progress() {
+        let mut counting = Counting::<_, u8>::new(
+            (),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+        assert_eq!(counting.counter_range(), (0, 254));
+        assert_eq!(counting.tx_progress(), 0.0);
+
+        let mut buf = [0; 3 * 128];
+        counting.fill_send_slice(&mut buf);
+        assert_eq!(counting.tx_progress(), 0.5);
+    }
+
+    #[test]
+    fn last_received_and_sent() {
+        let mut counting = Counting::<_, u16>::new(
+            (),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+        assert_eq!(counting.last_sent(), None);
+        assert_eq!(counting.last_received(), None);
+
+        let mut buf = [0; 4 * 3 + 2];
+        counting.fill_send_slice(&mut buf);
+        assert_eq!(counting.last_sent(), Some(2));
+
+        counting.recv_slice(&buf[..4]);
+        assert_eq!(counting.last_received(), Some(0));
+
+        // The packet in the middle is lost, the partial one is not received yet
+        counting.recv_slice(&buf[8..]);
+        assert_eq!(counting.last_received(), Some(2));
+
+        counting.reset();
+        assert_eq!(counting.last_sent(), None);
+        assert_eq!(counting.last_received(), None);
+    }
+
+    #[test]
+    fn has_synced() {
+        let mut counting = Counting::<_, u16>::new(
+            (),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+        assert!(!counting.has_synced());
+
+        let mut buf = [0; 4];
+        counting.fill_send_slice(&mut buf);
+        counting.recv_slice(&buf[..3]);
+        assert!(!counting.has_synced());
+
+        counting.recv_slice(&buf[3..]);
+        assert!(counting.has_synced());
+
+        counting.reset();
+        assert!(!counting.has_synced());
+    }
+
+    #[test]
+    fn tx_pending_bytes() {
+        let mut counting = Counting::<_, u16>::new(
+            (),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        )
+        .with_packet_size(6);
+        assert_eq!(counting.tx_pending_bytes(), 0);
+
+        let mut buf = [0; 1];
+        counting.fill_send_slice(&mut buf);
+        assert_eq!(counting.tx_pending_bytes(), 5);
+
+        let mut buf = [0; 5];
+        counting.fill_send_slice(&mut buf);
+        assert_eq!(counting.tx_pending_bytes(), 0);
+        assert_eq!(counting.tx_packets(), 1);
+    }
 }
@@ -1,13 +1,19 @@
-use embedded_hal_nb::nb::{Error, Result};
+use core::time::Duration;
+
+use embedded_hal_nb::nb::{block, Error, Result};
 use embedded_hal_nb::serial::{Read, Write};
 
+use crate::byte_rate::limit::{ByteRateLimiter, ByteRateSerialLimiter, LimitedSerialError};
+use crate::byte_rate::rate::ByteRate;
+use crate::clock::{Clock, ClockExt};
 use crate::statistics::Statistics;
+use crate::timeout_serial::{TimeoutSerial, TimeoutSerialError};
 
 use super::counter::Counter;
-use super::Counting;
+use super::{CheckedRecvError, Counting, ProbeResult, RunSummary};
 
-impl<Serial, Number, TxStats, RxStats, LossStats>
-    Counting<Serial, Number, TxStats, RxStats, LossStats>
+impl<Serial, Number, TxStats, RxStats, LossStats, const MAX_PACKET_SIZE: usize>
+    Counting<Serial, Number, TxStats, RxStats, LossStats, MAX_PACKET_SIZE>
 where
     Serial: Read,
     Number: Counter,
@@ -30,10 +36,55 @@ where
 
         Ok(())
     }
+
+    /// Receive byte from the serial port and verify it. Blocks until byte is received.
+    pub fn recv(&mut self) -> core::result::Result<(), Serial::Error> {
+        block!(self.recv_nb())
+    }
+
+    /// Reads and discards bytes available in the serial until it would block, returns amount of bytes dropped.
+    ///
+    /// Unlike `recv_nb`, the bytes are not verified and the statistics are not touched.
+    /// Never returns `WouldBlock`, `Ok(0)` is returned if nothing is available.
+    pub fn drain_rx(&mut self) -> Result<usize, Serial::Error> {
+        self.drain_rx_max(usize::MAX).map_err(Error::Other)
+    }
+
+    /// Same as `drain_rx`, but stops after `max` bytes even if more are available.
+    pub fn drain_rx_max(&mut self, max: usize) -> core::result::Result<usize, Serial::Error> {
+        let mut dropped = 0;
+        while dropped < max {
+            match self.serial.read() {
+                Ok(_) => dropped += 1,
+                Err(Error::WouldBlock) => break,
+                Err(Error::Other(e)) => return Err(e),
+            }
+        }
+
+        Ok(dropped)
+    }
+
+    /// Drains stale bytes for `duration`, then resets the test.
+    ///
+    /// Returns early on serial error.
+    pub fn warm_up<Clk: Clock>(
+        &mut self,
+        clock: &Clk,
+        duration: Duration,
+    ) -> core::result::Result<(), Serial::Error> {
+        let start = clock.now();
+        while clock.elapsed_since(start) < duration {
+            // Check time every byte, the peer may be streaming without a gap
+            self.drain_rx_max(1)?;
+        }
+
+        self.reset();
+        Ok(())
+    }
 }
 
-impl<Serial, Number, TxStats, RxStats, LossStats>
-    Counting<Serial, Number, TxStats, RxStats, LossStats>
+impl<Serial, Number, TxStats, RxStats, LossStats, const MAX_PACKET_SIZE: usize>
+    Counting<Serial, Number, TxStats, RxStats, LossStats, MAX_PACKET_SIZE>
 where
     Serial: Write,
     Number: Counter,
@@ -42,15 +93,32 @@ where
     LossStats: Statistics,
 {
     /// Sends next byte using non blocking API
+    ///
+    /// Flushes the serial every N bytes if configured with `CountingBuilder::flush_every`.
     pub fn send_nb(&mut self) -> Result<(), Serial::Error> {
+        // Complete the flush blocked after the previous byte
+        if let Err(e) = self.flush_cadence.flush_if_due(&mut self.serial) {
+            if let Error::WouldBlock = e {
+                self.tx_stats.add_blocked(1);
+            }
+            return Err(e);
+        }
+
         let byte_to_send = self.tx_state.peek();
 
         match self.serial.write(byte_to_send) {
             Ok(_) => {
                 self.on_byte_sent();
-                Ok(())
+                self.flush_cadence.on_written(1);
+                match self.flush_cadence.flush_if_due(&mut self.serial) {
+                    Ok(()) | Err(Error::WouldBlock) => Ok(()),
+                    Err(e) => Err(e),
+                }
+            }
+            Err(Error::WouldBlock) => {
+                self.tx_stats.add_blocked(1);
+                Err(Error::WouldBlock)
             }
-            Err(Error::WouldBlock) => Err(Error::WouldBlock),
             Err(e) => {
                 self.tx_stats.add_failed(1);
                 Err(e)
@@ -65,10 +133,36 @@ where
     pub fn flush_nb(&mut self) -> Result<(), Serial::Error> {
         self.serial.flush()
     }
+
+    /// Flushes serial port, blocks until all data is sent, then returns time the flush took measured by `clock`.
+    ///
+    /// Call it every T milliseconds from the loop owning the clock to flush on time rather than every N bytes.
+    pub fn timed_flush<Clk: Clock>(
+        &mut self,
+        clock: &Clk,
+    ) -> core::result::Result<Duration, Serial::Error> {
+        let start = clock.now();
+        block!(self.serial.flush())?;
+        self.flush_cadence.on_flushed();
+        Ok(clock.elapsed_since(start))
+    }
+
+    /// Sends next byte. Blocks until byte is accepted by serial.
+    pub fn send(&mut self) -> core::result::Result<(), Serial::Error> {
+        block!(self.send_nb())
+    }
+
+    /// Flushes serial port. Blocks until all data is sent.
+    ///
+    /// # Warning
+    /// The error happened here will not affect tx_state
+    pub fn flush(&mut self) -> core::result::Result<(), Serial::Error> {
+        block!(self.flush_nb())
+    }
 }
 
-impl<Serial, Number, TxStats, RxStats, LossStats>
-    Counting<Serial, Number, TxStats, RxStats, LossStats>
+impl<Serial, Number, TxStats, RxStats, LossStats, const MAX_PACKET_SIZE: usize>
+    Counting<Serial, Number, TxStats, RxStats, LossStats, MAX_PACKET_SIZE>
 where
     Serial: Write + Read,
     Number: Counter,
@@ -90,4 +184,718 @@ where
             (Err(e), _) | (_, Err(e)) => Err(e),
         }
     }
+
+    /// Runs `loop_nb` once, blocking while both sending and receiving are blocked.
+    pub fn run_loop(&mut self) -> core::result::Result<(), Serial::Error> {
+        block!(self.loop_nb())
+    }
+
+    /// Gracefully stops the test using non blocking API, so the final stats are accurate on both sides.
+    ///
+    /// In order:
+    /// 1. sends the rest of current packet, so the peer does not count a half-sent packet as lost;
+    /// 2. flushes the serial port;
+    /// 3. receives and verifies bytes until the serial would block.
+    ///
+    /// Returns `WouldBlock` if any step would block, call again to continue from that step.
+    /// No new packet is started, so call the other methods after this one to continue the test.
+    pub fn finish_nb(&mut self) -> Result<(), Serial::Error> {
+        while !self.tx_state.is_packet_sent() {
+            self.send_nb()?;
+        }
+
+        self.flush_nb()?;
+
+        loop {
+            match self.recv_nb() {
+                Ok(()) => (),
+                Err(Error::WouldBlock) => return Ok(()),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Runs the test for `duration` measured by `clock`, then returns packet counts of the run.
+    ///
+    /// Returns early on serial error. Read the stats or `summary` afterwards for the rates.
+    pub fn run_until<Clk: Clock>(
+        &mut self,
+        clock: &Clk,
+        duration: Duration,
+    ) -> core::result::Result<RunSummary, Serial::Error> {
+        let before = self.run_summary();
+        let start = clock.now();
+        while clock.elapsed_since(start) < duration {
+            match self.loop_nb() {
+                Ok(()) | Err(Error::WouldBlock) => (),
+                Err(Error::Other(e)) => return Err(e),
+            }
+        }
+
+        Ok(self.run_summary().since(&before))
+    }
+
+    /// Runs the test flat-out for `duration` measured by `clock` and returns the achieved rate and loss.
+    ///
+    /// Only packets received during the probe are counted, so the statistics and results before it don't matter.
+    /// The rate counts bytes of valid packets, so corrupted and lost ones don't add to it. Returns early on serial error.
+    pub fn probe<Clk: Clock>(
+        &mut self,
+        clock: &Clk,
+        duration: Duration,
+    ) -> core::result::Result<ProbeResult, Serial::Error> {
+        let received = self.rx_state.packets_received();
+        let lost = self.rx_state.packets_lost();
+        let start = clock.now();
+
+        self.run_until(clock, duration)?;
+
+        let elapsed = clock.elapsed_since(start);
+        let received = self.rx_state.packets_received().saturating_sub(received);
+        let lost = self.rx_state.packets_lost().saturating_sub(lost);
+
+        let expected = received.saturating_add(lost);
+        let loss_ratio = match expected {
+            0 => 0.0,
+            expected => lost as f32 / expected as f32,
+        };
+
+        Ok(ProbeResult {
+            achieved_rate: ByteRate::new(received.saturating_mul(self.packet_size()), elapsed),
+            loss_ratio,
+        })
+    }
+
+    /// Runs the test until `packets` more packets are sent completely, then returns packet counts of the run.
+    ///
+    /// Bytes received meanwhile are verified as usual, but the last packets sent may be not
+    /// received yet when this returns. Returns early on serial error.
+    pub fn run_count(&mut self, packets: usize) -> core::result::Result<RunSummary, Serial::Error> {
+        let before = self.run_summary();
+        let mut sent = 0;
+        while sent < packets {
+            let was_sent = self.tx_state.is_packet_sent();
+            self.run_loop()?;
+
+            // At most one byte is sent per loop so packets cannot be skipped
+            if !was_sent && self.tx_state.is_packet_sent() {
+                sent += 1;
+            }
+        }
+
+        Ok(self.run_summary().since(&before))
+    }
+}
+
+impl<Serial, Clk, Number, TxStats, RxStats, LossStats, const MAX_PACKET_SIZE: usize>
+    Counting<TimeoutSerial<'_, Serial, Clk>, Number, TxStats, RxStats, LossStats, MAX_PACKET_SIZE>
+where
+    Serial: Read,
+    Clk: Clock,
+    Number: Counter,
+    TxStats: Statistics,
+    RxStats: Statistics,
+    LossStats: Statistics,
+{
+    /// Same as `recv_nb`, but returns `TimeoutSerialError::Timeout` if no byte is received within
+    /// the timeout of `TimeoutSerial` since the last successful read. Non-blocking.
+    ///
+    /// The timeout is not counted as failed byte in RX statistics.
+    pub fn recv_nb_timeout(&mut self) -> Result<(), TimeoutSerialError<Serial::Error>> {
+        let byte_read = match self.serial.read_timeout() {
+            Ok(b) => b,
+            Err(Error::Other(TimeoutSerialError::Serial(e))) => {
+                self.rx_stats.add_failed(1);
+                return Err(Error::Other(TimeoutSerialError::Serial(e)));
+            }
+            Err(e) => return Err(e),
+        };
+
+        self.on_byte_received(byte_read);
+
+        Ok(())
+    }
+}
+
+impl<Serial, Number, TxStats, RxStats, LossStats, const MAX_PACKET_SIZE: usize>
+    Counting<Serial, Number, TxStats, RxStats, LossStats, MAX_PACKET_SIZE>
+where
+    Serial: Read,
+    Number: Counter,
+    TxStats: Statistics,
+    RxStats: Statistics,
+    LossStats: Statistics,
+{
+    /// Same as `recv_nb`, but returns `CheckedRecvError::WidthMismatch` if the peer advertised different
+    /// size of counter bytes in the width preamble, see `CountingBuilder::width_preamble`. Non-blocking.
+    ///
+    /// The error is returned while the mismatch persists, the byte is received anyway.
+    pub fn recv_nb_checked(&mut self) -> Result<(), CheckedRecvError<Serial::Error>> {
+        self.recv_nb()
+            .map_err(|e| e.map(CheckedRecvError::Serial))?;
+
+        match self.peer_width() {
+            Some(peer) if self.width_mismatch() => {
+                Err(Error::Other(CheckedRecvError::WidthMismatch {
+                    local: core::mem::size_of::<Number::Bytes>(),
+                    peer,
+                }))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<Serial, Number, TxStats, RxStats, LossStats, const MAX_PACKET_SIZE: usize>
+    Counting<Serial, Number, TxStats, RxStats, LossStats, MAX_PACKET_SIZE>
+where
+    Serial: Write + Read,
+    Number: Counter,
+    TxStats: Statistics,
+    RxStats: Statistics,
+    LossStats: Statistics,
+{
+    /// Runs the test for `duration` measured by `clock`, sleeping for `backoff` while both sending and receiving are blocked.
+    ///
+    /// Unlike `run_until` it does not spin on a slow link, at the cost of up to `backoff` latency of reading.
+    /// Bytes arriving while sleeping must fit into the receive buffer of the serial, so keep `backoff` small.
+    /// The `duration` is the stop condition of the run as in `run_until`, sleeping never exceeds it.
+    /// Returns packet counts of the run, returns early on serial error.
+    #[cfg(feature = "std")]
+    pub fn run_with_backoff<Clk: Clock>(
+        &mut self,
+        clock: &Clk,
+        duration: Duration,
+        backoff: Duration,
+    ) -> core::result::Result<RunSummary, Serial::Error> {
+        self.run_with_backoff_using(clock, duration, backoff, std::thread::sleep)
+    }
+
+    /// Same as `run_with_backoff`, but waits with `sleep`, e.g. a delay of HAL or RTOS without std
+    pub fn run_with_backoff_using<Clk: Clock>(
+        &mut self,
+        clock: &Clk,
+        duration: Duration,
+        backoff: Duration,
+        sleep: impl FnMut(Duration),
+    ) -> core::result::Result<RunSummary, Serial::Error> {
+        self.run_sleeping(clock, duration, |_| backoff, sleep)
+    }
+
+    fn run_sleeping<Clk: Clock>(
+        &mut self,
+        clock: &Clk,
+        duration: Duration,
+        backoff: impl Fn(&Serial) -> Duration,
+        mut sleep: impl FnMut(Duration),
+    ) -> core::result::Result<RunSummary, Serial::Error> {
+        let before = self.run_summary();
+        let start = clock.now();
+        while clock.elapsed_since(start) < duration {
+            match self.loop_nb() {
+                Ok(()) => (),
+                Err(Error::WouldBlock) => {
+                    let left = duration.saturating_sub(clock.elapsed_since(start));
+                    sleep(backoff(&self.serial).min(left));
+                }
+                Err(Error::Other(e)) => return Err(e),
+            }
+        }
+
+        Ok(self.run_summary().since(&before))
+    }
+}
+
+impl<Serial, Limiter, Number, TxStats, RxStats, LossStats, const MAX_PACKET_SIZE: usize>
+    Counting<
+        ByteRateSerialLimiter<Serial, Limiter>,
+        Number,
+        TxStats,
+        RxStats,
+        LossStats,
+        MAX_PACKET_SIZE,
+    >
+where
+    Serial: Write + Read,
+    Limiter: ByteRateLimiter,
+    Number: Counter,
+    TxStats: Statistics,
+    RxStats: Statistics,
+    LossStats: Statistics,
+{
+    /// Same as `run_with_backoff`, but sleeps until the rate limiter allows writing if it knows when.
+    ///
+    /// The limiter runs its own timer, so the sleep ends when the limiter resets and the next byte is written
+    /// right away. `backoff` is used when the limiter cannot tell the duration, e.g. the serial itself is blocked.
+    /// Bytes received during the whole limiter interval must fit into the receive buffer of the serial.
+    /// Returns packet counts of the run.
+    #[cfg(feature = "std")]
+    pub fn run_limited_with_backoff<Clk: Clock>(
+        &mut self,
+        clock: &Clk,
+        duration: Duration,
+        backoff: Duration,
+    ) -> core::result::Result<RunSummary, LimitedSerialError<Serial::Error>> {
+        self.run_limited_with_backoff_using(clock, duration, backoff, std::thread::sleep)
+    }
+
+    /// Same as `run_limited_with_backoff`, but waits with `sleep`, e.g. a delay of HAL or RTOS without std
+    pub fn run_limited_with_backoff_using<Clk: Clock>(
+        &mut self,
+        clock: &Clk,
+        duration: Duration,
+        backoff: Duration,
+        sleep: impl FnMut(Duration),
+    ) -> core::result::Result<RunSummary, LimitedSerialError<Serial::Error>> {
+        self.run_sleeping(
+            clock,
+            duration,
+            |serial| match serial.duration_until_reset() {
+                Some(reset) if !reset.is_zero() => reset,
+                _ => backoff,
+            },
+            sleep,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use embedded_hal_nb::serial::Write;
+
+    use crate::clock::MockClock;
+    use crate::counting::{CheckedRecvError, Counting};
+    use crate::paired_serial::{PairedSerial, PairedSerialEndpoint};
+    use crate::statistics::CountingStatistics;
+    use crate::test_serial::{ClockedSerial, ScriptedSerial};
+
+    type EchoSerial<'a> = ClockedSerial<'a, PairedSerialEndpoint<'a, 16>>;
+
+    /// Serial that sends written bytes back, advancing the clock every byte
+    fn echo_serial<'a>(paired: &'a PairedSerial<16>, clock: &'a MockClock) -> EchoSerial<'a> {
+        ClockedSerial::new(paired.echo(), clock).advance_on_write(Duration::from_millis(1))
+    }
+
+    fn echo_counting<'a>(
+        paired: &'a PairedSerial<16>,
+        clock: &'a MockClock,
+    ) -> Counting<EchoSerial<'a>, u16, CountingStatistics, CountingStatistics, CountingStatistics>
+    {
+        Counting::new(
+            echo_serial(paired, clock),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        )
+    }
+
+    #[test]
+    fn run_until_stops_after_duration() {
+        let clock = MockClock::new();
+        let paired = PairedSerial::new();
+        let mut counting = echo_counting(&paired, &clock);
+
+        let summary = counting
+            .run_until(&clock, Duration::from_millis(100))
+            .unwrap();
+
+        assert_eq!(counting.tx_stats().successful(), 100);
+        assert_eq!(counting.loss_stats().failed(), 0);
+        assert_eq!(summary.tx_packets, 25);
+        assert_eq!(summary.lost_packets, 0);
+        assert_eq!(summary.loss_ratio(), 0.0);
+    }
+
+    #[test]
+    fn run_count_stops_after_packets() {
+        let clock = MockClock::new();
+        let paired = PairedSerial::new();
+        let mut counting = echo_counting(&paired, &clock);
+
+        let summary = counting.run_count(10).unwrap();
+
+        assert_eq!(counting.tx_stats().successful(), 10 * 4);
+        assert_eq!(summary.tx_packets, 10);
+        assert_eq!(summary.rx_packets, 9);
+
+        // The last byte sent is still in flight
+        assert_eq!(counting.loss_stats().successful(), 9);
+        counting.recv().unwrap();
+        assert_eq!(counting.loss_stats().successful(), 10);
+        assert_eq!(counting.loss_stats().failed(), 0);
+
+        // Only packets of the second run are counted
+        let summary = counting.run_count(10).unwrap();
+        assert_eq!(summary.tx_packets, 10);
+        assert_eq!(summary.rx_packets, 9);
+        assert_eq!(counting.tx_packets(), 20);
+    }
+
+    #[test]
+    fn u8_wraps_without_loss() {
+        let clock = MockClock::new();
+        let paired = PairedSerial::new();
+        for checksum in [true, false] {
+            let mut counting = Counting::<_, u8>::builder(
+                echo_serial(&paired, &clock),
+                CountingStatistics::default(),
+                CountingStatistics::default(),
+                CountingStatistics::default(),
+            )
+            .checksum(checksum)
+            .build();
+
+            // Two full cycles of 255 values and a few more across the wrap
+            counting.run_count(2 * 255 + 5).unwrap();
+            counting.finish_nb().unwrap();
+
+            assert_eq!(counting.loss_stats().successful(), 2 * 255 + 5);
+            assert_eq!(counting.loss_stats().failed(), 0);
+            assert_eq!(counting.resync_count(), 0);
+            assert_eq!(counting.reordered_count(), 0);
+            assert_eq!(counting.last_received(), Some(4));
+        }
+    }
+
+    #[test]
+    fn finish_completes_packet_and_drains() {
+        let clock = MockClock::new();
+        let paired = PairedSerial::new();
+        let mut counting = echo_counting(&paired, &clock);
+
+        for _ in 0..6 {
+            counting.send().unwrap();
+        }
+
+        counting.finish_nb().unwrap();
+
+        assert_eq!(counting.tx_stats().successful(), 8);
+        assert_eq!(counting.rx_stats().successful(), 8);
+        assert_eq!(counting.loss_stats().successful(), 2);
+        assert_eq!(counting.loss_stats().failed(), 0);
+    }
+
+    #[test]
+    fn warm_up_discards_and_resets() {
+        let clock = MockClock::new();
+        let mut counting = Counting::<_, u16>::new(
+            // Receives a byte every millisecond of the clock
+            ClockedSerial::new(ScriptedSerial::new().reading(0xAA), &clock)
+                .advance_on_read(Duration::from_millis(1)),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+
+        counting
+            .warm_up(&clock, Duration::from_millis(100))
+            .unwrap();
+
+        assert_eq!(counting.rx_stats().total(), 0);
+        assert_eq!(counting.loss_stats().total(), 0);
+    }
+
+    #[test]
+    fn drain_rx_discards_without_stats() {
+        let paired = PairedSerial::<16>::new();
+        let (mut peer, serial) = paired.split();
+        let mut counting = Counting::<_, u16>::new(
+            serial,
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+
+        for byte in 0..5 {
+            peer.write(byte).unwrap();
+        }
+
+        assert_eq!(counting.drain_rx_max(2), Ok(2));
+        assert_eq!(counting.drain_rx(), Ok(3));
+        assert_eq!(counting.drain_rx(), Ok(0));
+        assert_eq!(counting.rx_stats().total(), 0);
+        assert_eq!(counting.crc_errors(), 0);
+    }
+
+    #[test]
+    fn run_with_backoff_sleeps() {
+        let clock = MockClock::new();
+        let mut counting = Counting::<_, u16>::new(
+            ScriptedSerial::blocked(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+
+        counting
+            .run_with_backoff_using(
+                &clock,
+                Duration::from_millis(50),
+                Duration::from_millis(10),
+                |d| clock.advance(d),
+            )
+            .unwrap();
+
+        // Polled once per backoff instead of spinning
+        assert_eq!(counting.serial().reads(), 5);
+    }
+
+    #[test]
+    fn run_limited_with_backoff_waits_for_limiter() {
+        use crate::byte_rate::limit::{ByteRateSerialLimiter, PollingByteRateLimiter};
+        use crate::byte_rate::rate::ByteRate;
+        let echo_clock = MockClock::new();
+        let paired = PairedSerial::new();
+        let clock = MockClock::new();
+        let limiter =
+            PollingByteRateLimiter::new(ByteRate::new(4, Duration::from_millis(40)), &clock);
+        let mut counting = Counting::<_, u16>::new(
+            ByteRateSerialLimiter::new(echo_serial(&paired, &echo_clock), limiter),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+
+        let summary = counting
+            .run_limited_with_backoff_using(
+                &clock,
+                Duration::from_millis(100),
+                Duration::from_secs(1),
+                |d| clock.advance(d),
+            )
+            .unwrap();
+
+        // Three intervals are started within the run, the backoff is never used
+        assert_eq!(counting.tx_stats().successful(), 12);
+        assert_eq!(summary.tx_packets, 3);
+        assert_eq!(counting.loss_stats().failed(), 0);
+    }
+
+    #[test]
+    fn recv_nb_timeout() {
+        use crate::timeout_serial::{TimeoutSerial, TimeoutSerialError};
+        use embedded_hal_nb::nb::Error;
+
+        let clock = MockClock::new();
+        let echo_clock = MockClock::new();
+        let paired = PairedSerial::new();
+        let mut counting = Counting::<_, u16>::new(
+            TimeoutSerial::new(
+                echo_serial(&paired, &echo_clock),
+                &clock,
+                Duration::from_secs(5),
+            ),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+
+        counting.send().unwrap();
+        clock.advance(Duration::from_secs(4));
+        counting.recv_nb_timeout().unwrap();
+
+        // The timeout is counted from the last byte received
+        clock.advance(Duration::from_secs(4));
+        assert!(matches!(counting.recv_nb_timeout(), Err(Error::WouldBlock)));
+
+        clock.advance(Duration::from_secs(2));
+        assert!(matches!(
+            counting.recv_nb_timeout(),
+            Err(Error::Other(TimeoutSerialError::Timeout))
+        ));
+        assert_eq!(counting.rx_stats().failed(), 0);
+
+        counting.send().unwrap();
+        counting.recv_nb_timeout().unwrap();
+        assert!(!counting.serial().is_timed_out());
+    }
+
+    #[test]
+    fn width_mismatch_detected() {
+        let paired = PairedSerial::<16>::new();
+        let (a, b) = paired.split();
+
+        let mut client = Counting::<_, u16>::builder(
+            a,
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        )
+        .width_preamble()
+        .build();
+        let mut server = Counting::<_, u32>::builder(
+            b,
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        )
+        .width_preamble()
+        .build();
+
+        for _ in 0..4 {
+            client.send_nb().unwrap();
+        }
+
+        for _ in 0..3 {
+            server.recv_nb_checked().unwrap();
+        }
+        assert!(matches!(
+            server.recv_nb_checked(),
+            Err(embedded_hal_nb::nb::Error::Other(
+                CheckedRecvError::WidthMismatch { local: 4, peer: 2 }
+            ))
+        ));
+        assert!(server.width_mismatch());
+    }
+
+    #[test]
+    fn width_preamble_magic_in_data() {
+        let paired = PairedSerial::<16>::new();
+        let (a, b) = paired.split();
+
+        // Counter bytes of the first packets are the magic bytes of the preamble
+        let mut client = Counting::<_, u16>::builder(
+            a,
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        )
+        .start_at(0x5AA5)
+        .unwrap()
+        .build();
+        let mut server = Counting::<_, u16>::builder(
+            b,
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        )
+        .width_preamble()
+        .build();
+
+        for _ in 0..5 * 4 {
+            client.send_nb().unwrap();
+            server.recv_nb_checked().unwrap();
+        }
+
+        assert_eq!(server.peer_width(), None);
+        assert!(!server.width_mismatch());
+        assert_eq!(server.rx_packets(), 5);
+        assert_eq!(server.loss_stats().failed(), 0);
+    }
+
+    #[test]
+    fn width_preamble_matches() {
+        let paired = PairedSerial::<16>::new();
+        let (a, b) = paired.split();
+
+        let mut client = Counting::<_, u16>::builder(
+            a,
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        )
+        .width_preamble()
+        .build();
+        let mut server = Counting::<_, u16>::builder(
+            b,
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        )
+        .width_preamble()
+        .build();
+
+        for _ in 0..100 {
+            client.send_nb().ok();
+            while server.recv_nb_checked().is_ok() {}
+        }
+
+        assert_eq!(server.peer_width(), Some(2));
+        assert!(!server.width_mismatch());
+        assert_eq!(client.tx_packets(), 100 / 4 - 1);
+        assert_eq!(server.rx_packets(), client.tx_packets());
+        assert_eq!(server.crc_errors(), 0);
+        assert_eq!(server.desync_count(), 0);
+        assert_eq!(server.loss_stats().failed(), 0);
+    }
+
+    #[test]
+    fn flush_every() {
+        let mut counting = Counting::<_, u16>::builder(
+            ScriptedSerial::new(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        )
+        .flush_every(4)
+        .build();
+
+        for _ in 0..11 {
+            counting.send_nb().unwrap();
+        }
+
+        assert_eq!(counting.serial().flushes(), 2);
+    }
+
+    #[test]
+    fn flush_every_blocked() {
+        use crate::statistics::BlockedStatistics;
+
+        let mut counting = Counting::<_, u16, _>::builder(
+            ScriptedSerial::new().blocking_flushes(2),
+            BlockedStatistics::new(CountingStatistics::default()),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        )
+        .flush_every(4)
+        .build();
+
+        for _ in 0..4 {
+            counting.send_nb().unwrap();
+        }
+
+        // The flush after the 4th byte is blocked, the next send completes it first
+        assert!(matches!(
+            counting.send_nb(),
+            Err(embedded_hal_nb::nb::Error::WouldBlock)
+        ));
+        assert_eq!(counting.tx_stats().blocked(), 1);
+        counting.send_nb().unwrap();
+
+        assert_eq!(counting.serial().flushes(), 1);
+        assert_eq!(counting.serial().written(), 5);
+    }
+
+    #[test]
+    fn timed_flush() {
+        let clock = MockClock::new();
+        let mut counting = Counting::<_, u16>::new(
+            ClockedSerial::new(ScriptedSerial::new().blocking_flushes(2), &clock)
+                .advance_on_flush(Duration::from_millis(1)),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+
+        counting.send_nb().unwrap();
+
+        assert_eq!(counting.timed_flush(&clock), Ok(Duration::from_millis(3)));
+        assert_eq!(counting.serial().serial().flushes(), 1);
+    }
+
+    #[test]
+    fn probe() {
+        let clock = MockClock::new();
+        let paired = PairedSerial::new();
+        let mut counting = echo_counting(&paired, &clock);
+
+        let result = counting.probe(&clock, Duration::from_secs(1)).unwrap();
+
+        // A byte per millisecond, the last packet is not received completely
+        assert_eq!(*result.achieved_rate.interval(), Duration::from_secs(1));
+        assert_eq!(result.achieved_rate.bytes(), 1000 / 4 * 4 - 4);
+        assert_eq!(result.loss_ratio, 0.0);
+    }
 }
@@ -0,0 +1,128 @@
+use super::counter::Counter;
+
+/// Magic bytes starting the width preamble, followed by the size of counter bytes and its bitwise complement
+///
+/// Counter data may contain the magic bytes too, the complement check and the range of width make it unlikely
+/// to be taken for the preamble.
+const PREAMBLE_MAGIC: [u8; 2] = [0xA5, 0x5A];
+
+/// Size of the width preamble on wire
+pub const PREAMBLE_SIZE: usize = PREAMBLE_MAGIC.len() + 2;
+
+/// Largest size of counter bytes advertised, counters are up to `u64`
+const MAX_WIDTH: u8 = 8;
+
+/// Returns the width preamble advertising `Number` bytes size
+pub fn preamble<Number: Counter>() -> [u8; PREAMBLE_SIZE] {
+    let [first, second] = PREAMBLE_MAGIC;
+    let width = counter_width::<Number>();
+    [first, second, width, !width]
+}
+
+/// Returns size of `Number` bytes as advertised in the preamble
+pub fn counter_width<Number: Counter>() -> u8 {
+    core::mem::size_of::<Number::Bytes>() as u8
+}
+
+/// Finds the width preamble in a stream of bytes
+#[derive(Debug, Default)]
+pub struct PreambleMatcher {
+    /// Amount of magic bytes matched
+    matched: usize,
+    /// Width received after the magic bytes, waiting for its complement
+    width: Option<u8>,
+}
+
+impl PreambleMatcher {
+    /// Handles received byte, returns the advertised width once the whole preamble is received
+    pub fn on_byte(&mut self, byte: u8) -> Option<u8> {
+        if let Some(width) = self.width.take() {
+            if byte == !width {
+                return Some(width);
+            }
+        } else if self.matched == PREAMBLE_MAGIC.len() {
+            self.matched = 0;
+            if (1..=MAX_WIDTH).contains(&byte) {
+                self.width = Some(byte);
+                return None;
+            }
+        }
+
+        // Not a preamble, but the byte may start one
+        self.match_magic(byte);
+        None
+    }
+
+    pub fn reset(&mut self) {
+        self.matched = 0;
+        self.width = None;
+    }
+
+    fn match_magic(&mut self, byte: u8) {
+        if byte == PREAMBLE_MAGIC[self.matched] {
+            self.matched += 1;
+        } else {
+            self.matched = usize::from(byte == PREAMBLE_MAGIC[0]);
+        }
+    }
+}
+
+/// Error of receiving with the counter width check, see `Counting::recv_nb_checked`
+#[derive(Debug)]
+pub enum CheckedRecvError<E> {
+    /// Error of the underlying serial
+    Serial(E),
+    /// The peer advertised different size of counter bytes, e.g. it uses `u16` while we use `u32`
+    WidthMismatch { local: usize, peer: usize },
+}
+
+impl<E> core::fmt::Display for CheckedRecvError<E>
+where
+    E: core::fmt::Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Serial(e) => write!(f, "serial error: {e:?}"),
+            Self::WidthMismatch { local, peer } => write!(
+                f,
+                "peer uses {peer} byte counter, but {local} byte counter is expected"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E> std::error::Error for CheckedRecvError<E> where E: core::fmt::Debug {}
+
+#[cfg(test)]
+mod tests {
+    use super::{preamble, PreambleMatcher};
+
+    #[test]
+    fn matcher_finds_preamble() {
+        let mut matcher = PreambleMatcher::default();
+
+        let [first, second, width, check] = preamble::<u16>();
+        let stream = [0x01, 0xA5, 0xA5, 0x00, 0xA5, first, second, width, check];
+
+        let mut found = stream.iter().filter_map(|b| matcher.on_byte(*b));
+        assert_eq!(found.next(), Some(2));
+        assert_eq!(found.next(), None);
+    }
+
+    #[test]
+    fn matcher_skips_magic_in_data() {
+        let mut matcher = PreambleMatcher::default();
+
+        // Magic followed by an invalid width, by a width without its complement and by the magic again
+        let [first, second, width, check] = preamble::<u32>();
+        let stream = [
+            0xA5, 0x5A, 0x00, 0xFF, 0xA5, 0x5A, 0x09, 0xF6, 0xA5, 0x5A, 0x02, 0x5A, 0xA5, 0x5A,
+            0xA5, first, second, width, check,
+        ];
+
+        let mut found = stream.iter().filter_map(|b| matcher.on_byte(*b));
+        assert_eq!(found.next(), Some(4));
+        assert_eq!(found.next(), None);
+    }
+}
@@ -0,0 +1,90 @@
+use crate::statistics::Statistics;
+
+use super::counter::Counter;
+use super::Counting;
+
+/// Snapshot of independent link quality counters of the counting test
+///
+/// Take snapshots periodically and use `since` to get the counters over the interval between them.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LinkQuality {
+    /// Valid packets received
+    pub received: usize,
+    /// Packets missing in the sequence
+    pub loss: usize,
+    /// Packets rejected by checksum
+    pub corruption: usize,
+    /// Packets overflowing the buffer before separator
+    pub desync: usize,
+}
+
+impl LinkQuality {
+    /// Returns counters accumulated after `earlier` snapshot
+    pub fn since(&self, earlier: &Self) -> Self {
+        Self {
+            received: self.received.saturating_sub(earlier.received),
+            loss: self.loss.saturating_sub(earlier.loss),
+            corruption: self.corruption.saturating_sub(earlier.corruption),
+            desync: self.desync.saturating_sub(earlier.desync),
+        }
+    }
+}
+
+impl<Serial, Number, TxStats, RxStats, LossStats, const MAX_PACKET_SIZE: usize>
+    Counting<Serial, Number, TxStats, RxStats, LossStats, MAX_PACKET_SIZE>
+where
+    Number: Counter,
+    TxStats: Statistics,
+    RxStats: Statistics,
+    LossStats: Statistics,
+{
+    /// Returns loss, corruption and desync counters in one snapshot
+    ///
+    /// The counters are independent of loss statistics and reset only by `reset`.
+    pub fn link_quality(&self) -> LinkQuality {
+        LinkQuality {
+            received: self.rx_state.packets_received(),
+            loss: self.rx_state.packets_lost(),
+            corruption: self.rx_state.crc_errors(),
+            desync: self.rx_state.desyncs(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::counting::Counting;
+    use crate::statistics::CountingStatistics;
+
+    #[test]
+    fn loss_and_corruption() {
+        let mut counting = Counting::<_, u8>::new(
+            (),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+
+        let mut buf = [0; 3 * 10];
+        counting.fill_send_slice(&mut buf);
+
+        // Lose the 2nd packet and corrupt the 4th one
+        buf[3 * 3] ^= 0x80;
+        counting.recv_slice(&buf[..3]);
+        counting.recv_slice(&buf[3 * 2..3 * 5]);
+        let first = counting.link_quality();
+
+        assert_eq!(first.received, 3);
+        assert_eq!(first.loss, 2);
+        assert_eq!(first.corruption, 1);
+        assert_eq!(first.desync, 0);
+
+        counting.recv_slice(&buf[3 * 5..]);
+        let interval = counting.link_quality().since(&first);
+        assert_eq!(interval.received, 5);
+        assert_eq!(interval.loss, 0);
+        assert_eq!(interval.corruption, 0);
+    }
+}
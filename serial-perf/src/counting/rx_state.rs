@@ -1,86 +1,286 @@
-use crate::statistics::Statistics;
+use crate::statistics::{LossEvent, Statistics};
 
 use super::{
-    counter::{Counter, LeBytes},
-    MAX_PACKET_SIZE,
+    checksum::{Checksum, MAX_CHECKSUM_SIZE},
+    counter::{ByteOrder, Counter, CounterDelta, LeBytes, Sequence},
+    preamble::PreambleMatcher,
 };
 
 enum InternalState {
     Receiving,
     WaitingForCRC,
+    /// Skipping padding bytes, contains amount of bytes left
+    SkippingPadding(usize),
 }
 
-pub struct RxState<Number, LossStats> {
+pub struct RxState<Number, LossStats, const MAX_PACKET_SIZE: usize> {
     /// The last number received to analyze the packet loss.
     number: Option<Number>,
 
     /// The current packet being received.
     current_packet: heapless::Vec<u8, MAX_PACKET_SIZE>,
+    /// The checksum of current packet being received.
+    current_checksum: heapless::Vec<u8, MAX_CHECKSUM_SIZE>,
     /// State for parsing incoming package
     internal_state: InternalState,
 
     /// The statistics of the packet loss. Note: this is not a rx_stats because it's analyze packets, not bytes
     loss_stats: LossStats,
 
-    checksum_enabled: bool,
+    checksum: Checksum,
+    /// Byte separating count from checksum, `None` if packets are not separated
+    separator: Option<u8>,
+    byte_order: ByteOrder,
+    /// Whether packets are COBS framed instead of separated
+    #[cfg(feature = "cobs")]
+    cobs: bool,
+    sequence: Sequence,
+
+    /// The maximum distance between two numbers considered as a packet loss.
+    /// Bigger jumps are treated as resync.
+    max_gap: usize,
+    /// Amount of resyncs happened
+    resyncs: usize,
+
+    /// The maximum distance back from the last number considered as a reordered packet.
+    reorder_window: usize,
+    /// Amount of duplicated or reordered packets received
+    reordered: usize,
+    /// Amount of packets rejected by checksum
+    crc_errors: usize,
+    /// Amount of packets overflowing the buffer before separator
+    desyncs: usize,
+    /// Amount of packets parsed successfully
+    packets_received: usize,
+    /// Amount of packets lost in sequence
+    packets_lost: usize,
+
+    /// Amount of padding bytes after each packet
+    padding: usize,
+
+    /// Finds the width preamble until the first valid packet, `None` if the preamble is not expected
+    preamble: Option<PreambleMatcher>,
+    /// Size of counter bytes advertised by the peer
+    peer_width: Option<u8>,
 }
 
-impl<Number, LossStats> RxState<Number, LossStats>
+impl<Number, LossStats, const MAX_PACKET_SIZE: usize> RxState<Number, LossStats, MAX_PACKET_SIZE>
 where
     Number: Counter,
     LossStats: Statistics,
 {
-    pub fn new(loss_stats: LossStats) -> Self {
+    pub fn new(loss_stats: LossStats, checksum: Checksum) -> Self {
         Self {
             number: None,
             current_packet: heapless::Vec::new(),
+            current_checksum: heapless::Vec::new(),
             internal_state: InternalState::Receiving,
             loss_stats,
-            checksum_enabled: true,
+            checksum,
+            separator: Some(0),
+            byte_order: ByteOrder::Little,
+            #[cfg(feature = "cobs")]
+            cobs: false,
+            sequence: Sequence::Counter,
+            max_gap: Self::default_max_gap(),
+            resyncs: 0,
+            reorder_window: 0,
+            reordered: 0,
+            crc_errors: 0,
+            desyncs: 0,
+            packets_received: 0,
+            packets_lost: 0,
+            padding: 0,
+            preamble: None,
+            peer_width: None,
         }
     }
 
-    pub fn new_without_checksum(loss_stats: LossStats) -> Self {
-        Self {
-            number: None,
-            current_packet: heapless::Vec::new(),
-            internal_state: InternalState::Receiving,
-            loss_stats,
-            checksum_enabled: false,
-        }
+    /// Sets byte separating count from checksum
+    pub fn set_separator(&mut self, separator: Option<u8>) {
+        self.separator = separator;
+    }
+
+    /// Enables COBS framing instead of separator with plain counter bytes, resets the maximum gap to default
+    #[cfg(feature = "cobs")]
+    pub fn set_cobs(&mut self, cobs: bool) {
+        self.cobs = cobs;
+        self.sequence = if cobs {
+            Sequence::Plain
+        } else {
+            Sequence::Counter
+        };
+        let max_normalized = self.sequence.max_normalized::<Number>();
+        self.max_gap = usize::try_from(max_normalized).unwrap_or(usize::MAX) / 2;
+    }
+
+    /// Returns sequence of counter values received
+    pub fn sequence(&self) -> Sequence {
+        self.sequence
+    }
+
+    /// Enables searching for the width preamble before the first valid packet
+    pub fn set_width_preamble(&mut self, width_preamble: bool) {
+        self.preamble = width_preamble.then(PreambleMatcher::default);
+    }
+
+    /// Returns size of counter bytes advertised by the peer in the width preamble
+    pub fn peer_width(&self) -> Option<u8> {
+        self.peer_width
+    }
+
+    /// Sets byte order of counter bytes
+    pub fn set_byte_order(&mut self, byte_order: ByteOrder) {
+        self.byte_order = byte_order;
+    }
+
+    /// Sets amount of padding bytes skipped after each packet
+    pub fn set_padding(&mut self, padding: usize) {
+        self.padding = padding;
+    }
+
+    /// Returns default maximum gap: half of the counter range
+    pub fn default_max_gap() -> usize {
+        Number::min_counter().distance(&Number::max_counter()) / 2
+    }
+
+    pub fn max_gap(&self) -> usize {
+        self.max_gap
+    }
+
+    pub fn set_max_gap(&mut self, max_gap: usize) {
+        self.max_gap = max_gap;
+    }
+
+    pub fn resyncs(&self) -> usize {
+        self.resyncs
+    }
+
+    pub fn reorder_window(&self) -> usize {
+        self.reorder_window
+    }
+
+    pub fn set_reorder_window(&mut self, reorder_window: usize) {
+        self.reorder_window = reorder_window;
+    }
+
+    pub fn reordered(&self) -> usize {
+        self.reordered
+    }
+
+    pub fn crc_errors(&self) -> usize {
+        self.crc_errors
+    }
+
+    pub fn desyncs(&self) -> usize {
+        self.desyncs
+    }
+
+    pub fn packets_received(&self) -> usize {
+        self.packets_received
+    }
+
+    pub fn packets_lost(&self) -> usize {
+        self.packets_lost
     }
 
     pub fn reset(&mut self) {
         self.number = None;
         self.current_packet.clear();
+        self.current_checksum.clear();
         self.internal_state = InternalState::Receiving;
         self.loss_stats.reset();
+        self.resyncs = 0;
+        self.reordered = 0;
+        self.crc_errors = 0;
+        self.desyncs = 0;
+        self.packets_received = 0;
+        self.packets_lost = 0;
+        self.peer_width = None;
+        if let Some(preamble) = &mut self.preamble {
+            preamble.reset();
+        }
     }
 
-    /// Parses and handling incoming packet
-    fn parse_current_packet(&mut self, crc: u8) {
-        let checksum = if self.checksum_enabled {
-            Some(crc)
-        } else {
-            None
-        };
+    pub fn reset_loss_stats(&mut self) {
+        self.loss_stats.reset();
+    }
 
-        if let Some(new_number_raw) =
-            Number::Bytes::from_slice_checked(&self.current_packet, checksum)
+    /// Parses and handling incoming packet
+    fn parse_current_packet(&mut self) {
+        if let Some(new_number_raw) = Number::Bytes::from_slice_checked(
+            &self.current_packet,
+            &self.checksum,
+            &self.current_checksum,
+        ) {
+            let new_number = Number::from_le_bytes(self.byte_order.apply(new_number_raw));
+            if self.sequence.contains(&new_number) {
+                self.packets_received = self.packets_received.saturating_add(1);
+                self.on_new_number(new_number);
+            } else {
+                // Null byte in count, possible without checksum and separator on a corrupted stream
+                self.crc_errors = self.crc_errors.saturating_add(1);
+            }
+        } else if self.checksum.is_enabled()
+            && self.current_packet.len() == core::mem::size_of::<Number::Bytes>()
         {
-            let new_number = Number::from_le_bytes(new_number_raw);
-            self.on_new_number(new_number);
+            // Packet of the right size but corrupted, packets of wrong size are framing errors
+            #[cfg(feature = "defmt")]
+            defmt::warn!("counting packet rejected by checksum");
+
+            self.crc_errors = self.crc_errors.saturating_add(1);
         }
 
         self.current_packet.clear();
+        self.current_checksum.clear();
     }
 
     fn on_new_number(&mut self, new_number: Number) {
         if let Some(ref old_number) = self.number {
-            let distance = old_number.distance(&new_number);
-            let loss = distance - 1;
-            self.loss_stats.add_failed(loss);
-            // FIXME: Detect absurd jumps?
+            let delayed = match self.sequence.delta(old_number, &new_number) {
+                CounterDelta::Same => true,
+                CounterDelta::Backward(behind) => behind <= self.reorder_window,
+                CounterDelta::Forward(_) => false,
+            };
+
+            if delayed {
+                // Duplicate or delayed packet, the sequence continues from the last number
+                self.reordered = self.reordered.saturating_add(1);
+                return;
+            }
+
+            let distance = self.sequence.distance(old_number, &new_number);
+            if distance > self.max_gap {
+                // Absurd jump: the packet is corrupted or we lost sync, start over from new number
+                #[cfg(feature = "defmt")]
+                defmt::warn!("counting resynced after jump of {} packets", distance);
+
+                self.resyncs = self.resyncs.saturating_add(1);
+            } else {
+                let loss = distance.saturating_sub(1);
+
+                #[cfg(feature = "defmt")]
+                if loss > 0 {
+                    defmt::warn!("counting lost {} packets", loss);
+                }
+
+                self.packets_lost = self.packets_lost.saturating_add(loss);
+                self.loss_stats.add_failed(loss);
+
+                if loss > 0 {
+                    self.loss_stats.add_loss_event(LossEvent {
+                        from: self
+                            .sequence
+                            .normalize(old_number)
+                            .map_or(0, |n| n.as_u64()),
+                        to: self
+                            .sequence
+                            .normalize(&new_number)
+                            .map_or(0, |n| n.as_u64()),
+                        lost: loss,
+                    });
+                }
+            }
         }
 
         self.number = Some(new_number);
@@ -88,35 +288,347 @@ where
     }
 
     pub fn on_byte_received(&mut self, byte: u8) {
+        if let Some(width) = self.match_preamble(byte) {
+            // Bytes of the preamble are not a packet, start parsing over
+            self.peer_width = Some(width);
+            self.current_packet.clear();
+            self.current_checksum.clear();
+            self.internal_state = InternalState::Receiving;
+            return;
+        }
+
         match self.internal_state {
             InternalState::Receiving => self.on_byte_received_normal(byte),
             InternalState::WaitingForCRC => self.on_byte_received_crc(byte),
+            InternalState::SkippingPadding(left) => self.on_byte_received_padding(left),
         }
     }
 
+    /// Passes byte to the preamble matcher until the first valid packet, returns the advertised width if found
+    fn match_preamble(&mut self, byte: u8) -> Option<u8> {
+        if self.number.is_some() {
+            return None;
+        }
+
+        self.preamble.as_mut()?.on_byte(byte)
+    }
+
     pub fn loss_stats(&self) -> &LossStats {
         &self.loss_stats
     }
 
+    pub fn loss_stats_mut(&mut self) -> &mut LossStats {
+        &mut self.loss_stats
+    }
+
+    /// Returns the last number received in a valid packet, if any
+    pub fn last_number(&self) -> Option<&Number> {
+        self.number.as_ref()
+    }
+
     fn on_byte_received_normal(&mut self, byte: u8) {
-        // Null terminator
-        if byte == 0 {
+        #[cfg(feature = "cobs")]
+        if self.cobs {
+            return self.on_byte_received_cobs(byte);
+        }
+
+        let Some(separator) = self.separator else {
+            return self.on_byte_received_unseparated(byte);
+        };
+
+        // Null terminator or custom separator
+        if byte == separator {
             self.internal_state = InternalState::WaitingForCRC;
             return;
         }
 
-        // We cannot insert more bytes so try parse current package and then insert
+        // We cannot insert more bytes, the separator was lost so start over from this byte
         if self.current_packet.is_full() {
+            #[cfg(feature = "defmt")]
+            defmt::warn!("counting packet overflow, framing is lost");
+
+            self.desyncs = self.desyncs.saturating_add(1);
             self.current_packet.clear();
             self.internal_state = InternalState::Receiving;
         }
 
         debug_assert!(!self.current_packet.is_full());
-        self.current_packet.push(byte).unwrap();
+        self.current_packet.push(byte ^ separator).unwrap();
+    }
+
+    /// Receives fixed size count without separator, the checksum follows the last byte of count
+    fn on_byte_received_unseparated(&mut self, byte: u8) {
+        self.current_packet.push(byte).ok();
+        if self.current_packet.len() >= core::mem::size_of::<Number::Bytes>() {
+            self.internal_state = InternalState::WaitingForCRC;
+        }
+    }
+
+    /// Receives COBS frame until delimiter, then decodes and parses it
+    #[cfg(feature = "cobs")]
+    fn on_byte_received_cobs(&mut self, byte: u8) {
+        if byte != super::cobs::COBS_DELIMITER {
+            if self.current_packet.is_full() {
+                #[cfg(feature = "defmt")]
+                defmt::warn!("counting packet overflow, framing is lost");
+
+                self.desyncs = self.desyncs.saturating_add(1);
+                self.current_packet.clear();
+            }
+
+            self.current_packet.push(byte).unwrap();
+            return;
+        }
+
+        let count_size = core::mem::size_of::<Number::Bytes>();
+        let decoded = super::cobs::decode(&mut self.current_packet);
+        if decoded && self.current_packet.len() == count_size + self.checksum.len() {
+            self.current_checksum
+                .extend_from_slice(&self.current_packet[count_size..])
+                .unwrap();
+            self.current_packet.truncate(count_size);
+            self.parse_current_packet();
+        } else {
+            // Malformed frame or a frame of other size, e.g. a part of packet after lost bytes
+            self.desyncs = self.desyncs.saturating_add(1);
+            self.current_packet.clear();
+        }
+
+        if self.padding != 0 {
+            self.internal_state = InternalState::SkippingPadding(self.padding);
+        }
     }
 
     fn on_byte_received_crc(&mut self, byte: u8) {
-        self.parse_current_packet(byte);
-        self.internal_state = InternalState::Receiving;
+        self.current_checksum.push(byte).ok();
+        if self.current_checksum.len() < self.checksum.len() {
+            return;
+        }
+
+        self.parse_current_packet();
+        self.internal_state = match self.padding {
+            0 => InternalState::Receiving,
+            padding => InternalState::SkippingPadding(padding),
+        };
+    }
+
+    fn on_byte_received_padding(&mut self, left: usize) {
+        self.internal_state = match left {
+            0 | 1 => InternalState::Receiving,
+            left => InternalState::SkippingPadding(left - 1),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::counting::{DEFAULT_CRC_ALGORITHM, DEFAULT_MAX_PACKET_SIZE, PADDING_BYTE};
+    use crate::statistics::CountingStatistics;
+
+    fn receive<Number: Counter>(
+        state: &mut RxState<Number, CountingStatistics, DEFAULT_MAX_PACKET_SIZE>,
+        number: Number,
+        checksum: &Checksum,
+    ) {
+        let mut packet = number
+            .to_le_bytes()
+            .into_packet::<DEFAULT_MAX_PACKET_SIZE>(checksum, Some(0));
+        while let Some(byte) = packet.pop() {
+            state.on_byte_received(byte);
+        }
+    }
+
+    #[test]
+    fn loss_detected() {
+        let checksum = Checksum::crc8(DEFAULT_CRC_ALGORITHM);
+        let mut state = RxState::<u8, _, DEFAULT_MAX_PACKET_SIZE>::new(
+            CountingStatistics::default(),
+            checksum.clone(),
+        );
+
+        receive(&mut state, 0x01, &checksum);
+        receive(&mut state, 0x04, &checksum);
+
+        assert_eq!(state.loss_stats().successful(), 2);
+        assert_eq!(state.loss_stats().failed(), 2);
+        assert_eq!(state.resyncs(), 0);
+    }
+
+    #[test]
+    fn loss_stats_reset_keeps_sequence() {
+        let checksum = Checksum::crc8(DEFAULT_CRC_ALGORITHM);
+        let mut state = RxState::<u8, _, DEFAULT_MAX_PACKET_SIZE>::new(
+            CountingStatistics::default(),
+            checksum.clone(),
+        );
+
+        receive(&mut state, 0x01, &checksum);
+        receive(&mut state, 0x04, &checksum);
+        state.reset_loss_stats();
+        receive(&mut state, 0x05, &checksum);
+
+        assert_eq!(state.loss_stats().successful(), 1);
+        assert_eq!(state.loss_stats().failed(), 0);
+    }
+
+    #[test]
+    fn duplicates_and_reorders_detected() {
+        let checksum = Checksum::crc8(DEFAULT_CRC_ALGORITHM);
+        let mut state = RxState::<u8, _, DEFAULT_MAX_PACKET_SIZE>::new(
+            CountingStatistics::default(),
+            checksum.clone(),
+        );
+        state.set_reorder_window(2);
+
+        receive(&mut state, 0x01, &checksum);
+        receive(&mut state, 0x04, &checksum);
+        receive(&mut state, 0x04, &checksum);
+        receive(&mut state, 0x03, &checksum);
+        receive(&mut state, 0x05, &checksum);
+
+        assert_eq!(state.reordered(), 2);
+        assert_eq!(state.resyncs(), 0);
+        assert_eq!(state.loss_stats().successful(), 3);
+        assert_eq!(state.loss_stats().failed(), 2);
+        assert_eq!(state.last_number(), Some(&0x05));
+    }
+
+    #[test]
+    fn delayed_reordered_and_jump_lost() {
+        let checksum = Checksum::crc8(DEFAULT_CRC_ALGORITHM);
+        let mut state = RxState::<u16, _, DEFAULT_MAX_PACKET_SIZE>::new(
+            CountingStatistics::default(),
+            checksum.clone(),
+        );
+        state.set_reorder_window(2);
+
+        // Values without zero bytes, zero is the separator
+        receive(&mut state, 0x0110, &checksum);
+        receive(&mut state, 0x0112, &checksum);
+        // Delayed packet within the window is not a loss
+        receive(&mut state, 0x0111, &checksum);
+        assert_eq!(state.reordered(), 1);
+        assert_eq!(state.loss_stats().failed(), 1);
+
+        // Large jump forward within max gap is a loss, not a reorder
+        receive(&mut state, 0x01F0, &checksum);
+        assert_eq!(state.reordered(), 1);
+        assert_eq!(state.loss_stats().failed(), 222);
+
+        receive(&mut state, 0x01EE, &checksum);
+        receive(&mut state, 0x01F1, &checksum);
+
+        assert_eq!(state.reordered(), 2);
+        assert_eq!(state.resyncs(), 0);
+        assert_eq!(state.packets_lost(), 222);
+        assert_eq!(state.loss_stats().successful(), 4);
+        assert_eq!(state.last_number(), Some(&0x01F1));
+    }
+
+    #[test]
+    fn absurd_jump_resyncs() {
+        let checksum = Checksum::crc8(DEFAULT_CRC_ALGORITHM);
+        let mut state = RxState::<u8, _, DEFAULT_MAX_PACKET_SIZE>::new(
+            CountingStatistics::default(),
+            checksum.clone(),
+        );
+        state.set_max_gap(10);
+
+        receive(&mut state, 0x01, &checksum);
+        receive(&mut state, 0x80, &checksum);
+        receive(&mut state, 0x81, &checksum);
+
+        assert_eq!(state.loss_stats().successful(), 3);
+        assert_eq!(state.loss_stats().failed(), 0);
+        assert_eq!(state.resyncs(), 1);
+        assert_eq!(state.last_number(), Some(&0x81));
+    }
+
+    #[test]
+    fn padding_skipped() {
+        let checksum = Checksum::crc8(DEFAULT_CRC_ALGORITHM);
+        let mut state = RxState::<u8, _, DEFAULT_MAX_PACKET_SIZE>::new(
+            CountingStatistics::default(),
+            checksum.clone(),
+        );
+        state.set_padding(4);
+
+        for number in [0x01, 0x02, 0x03] {
+            receive(&mut state, number, &checksum);
+            for _ in 0..4 {
+                state.on_byte_received(PADDING_BYTE);
+            }
+        }
+
+        assert_eq!(state.loss_stats().successful(), 3);
+        assert_eq!(state.loss_stats().failed(), 0);
+    }
+
+    #[test]
+    fn crc_errors_counted() {
+        let checksum = Checksum::crc8(DEFAULT_CRC_ALGORITHM);
+        let mut state = RxState::<u8, _, DEFAULT_MAX_PACKET_SIZE>::new(
+            CountingStatistics::default(),
+            checksum.clone(),
+        );
+
+        receive(&mut state, 0x01, &checksum);
+
+        let mut packet = 0x02_u8
+            .to_le_bytes()
+            .into_packet::<DEFAULT_MAX_PACKET_SIZE>(&checksum, Some(0));
+        packet[0] ^= 0xFF;
+        while let Some(byte) = packet.pop() {
+            state.on_byte_received(byte);
+        }
+
+        receive(&mut state, 0x03, &checksum);
+
+        assert_eq!(state.crc_errors(), 1);
+        assert_eq!(state.loss_stats().successful(), 2);
+        assert_eq!(state.loss_stats().failed(), 1);
+    }
+
+    #[test]
+    fn desync_counted() {
+        let checksum = Checksum::crc8(DEFAULT_CRC_ALGORITHM);
+        let mut state = RxState::<u8, _, DEFAULT_MAX_PACKET_SIZE>::new(
+            CountingStatistics::default(),
+            checksum.clone(),
+        );
+
+        for _ in 0..DEFAULT_MAX_PACKET_SIZE {
+            state.on_byte_received(0x01);
+        }
+        assert_eq!(state.desyncs(), 0);
+
+        state.on_byte_received(0x01);
+        assert_eq!(state.desyncs(), 1);
+
+        // Framing is recovered on the next separator
+        state.on_byte_received(0);
+        state.on_byte_received(0);
+        receive(&mut state, 0x02, &checksum);
+
+        assert_eq!(state.desyncs(), 1);
+        assert_eq!(state.last_number(), Some(&0x02));
+    }
+
+    #[test]
+    fn non_counter_value_skipped() {
+        let mut state = RxState::<u16, _, DEFAULT_MAX_PACKET_SIZE>::new(
+            CountingStatistics::default(),
+            Checksum::None,
+        );
+        state.set_separator(None);
+
+        for byte in [0x01, 0x01, 0x01, 0x00, 0x05, 0x00, 0x02, 0x01, 0x02] {
+            state.on_byte_received(byte);
+        }
+
+        assert_eq!(state.crc_errors(), 1);
+        assert_eq!(state.loss_stats().successful(), 2);
+        assert_eq!(state.loss_stats().failed(), 0);
     }
 }
@@ -0,0 +1,194 @@
+use crate::byte_rate::limit::{ByteRateLimiter, ByteRateSerialLimiter};
+use crate::byte_rate::rate::ByteRate;
+use crate::statistics::{CountingStatistics, RateStatistics, Statistics};
+
+use super::counter::Counter;
+use super::Counting;
+
+/// Summary of both directions of the counting test
+#[derive(Debug, Clone, PartialEq)]
+pub struct CountingSummary {
+    /// Rate of bytes sent, if measured
+    pub tx_rate: Option<ByteRate>,
+    /// Rate of bytes received, if measured
+    pub rx_rate: Option<ByteRate>,
+    /// Ratio of lost packets to all packets expected
+    pub loss_ratio: f32,
+}
+
+/// Result of `Counting::probe`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProbeResult {
+    /// Bytes of valid packets received over the probe duration
+    pub achieved_rate: ByteRate,
+    /// Ratio of lost packets to all packets expected during the probe
+    pub loss_ratio: f32,
+}
+
+/// Packet counts of the counting test
+///
+/// `Counting::run_summary` counts the packets since the start or the last reset of the test, while the `run_*`
+/// methods, e.g. `Counting::run_until`, return the packets counted during the run only.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunSummary {
+    /// Packets sent completely
+    pub tx_packets: usize,
+    /// Valid packets received, including duplicated and reordered ones
+    pub rx_packets: usize,
+    /// Packets lost, including ones rejected by checksum
+    pub lost_packets: usize,
+    /// Packets rejected by checksum
+    pub crc_errors: usize,
+}
+
+impl RunSummary {
+    /// Returns ratio of lost packets to all packets expected, 0.0 if nothing received yet
+    pub fn loss_ratio(&self) -> f32 {
+        match self.rx_packets.saturating_add(self.lost_packets) {
+            0 => 0.0,
+            expected => self.lost_packets as f32 / expected as f32,
+        }
+    }
+
+    /// Returns packets counted since `start`, counts are 0 if the test was reset meanwhile
+    pub(crate) fn since(&self, start: &Self) -> Self {
+        Self {
+            tx_packets: self.tx_packets.saturating_sub(start.tx_packets),
+            rx_packets: self.rx_packets.saturating_sub(start.rx_packets),
+            lost_packets: self.lost_packets.saturating_sub(start.lost_packets),
+            crc_errors: self.crc_errors.saturating_sub(start.crc_errors),
+        }
+    }
+}
+
+impl<Serial, Number, TxStats, RxStats, LossStats, const MAX_PACKET_SIZE: usize>
+    Counting<Serial, Number, TxStats, RxStats, LossStats, MAX_PACKET_SIZE>
+where
+    Number: Counter,
+    TxStats: Statistics,
+    RxStats: Statistics,
+    LossStats: Statistics,
+{
+    /// Returns packet counts of the test, independent of statistics used
+    pub fn run_summary(&self) -> RunSummary {
+        RunSummary {
+            tx_packets: self.tx_packets(),
+            rx_packets: self.rx_packets(),
+            lost_packets: self.rx_state.packets_lost(),
+            crc_errors: self.crc_errors(),
+        }
+    }
+}
+
+impl<Serial, Number, TxStats, RxStats, const MAX_PACKET_SIZE: usize>
+    Counting<Serial, Number, TxStats, RxStats, CountingStatistics, MAX_PACKET_SIZE>
+where
+    Number: Counter,
+    TxStats: RateStatistics,
+    RxStats: RateStatistics,
+{
+    /// Returns TX, RX rates and packet loss in one call
+    pub fn summary(&self) -> CountingSummary {
+        CountingSummary {
+            tx_rate: self.tx_stats.success_rate(),
+            rx_rate: self.rx_stats.success_rate(),
+            loss_ratio: self.loss_stats().loss_ratio(),
+        }
+    }
+
+    /// Returns sum of TX and RX rates, the total goodput of full-duplex link.
+    ///
+    /// Returns `None` if neither direction is measured yet.
+    pub fn aggregate_rate(&self) -> Option<ByteRate> {
+        match (self.tx_stats.success_rate(), self.rx_stats.success_rate()) {
+            (Some(tx), Some(rx)) => Some(tx + rx),
+            (tx, rx) => tx.or(rx),
+        }
+    }
+}
+
+impl<Serial, Limiter, Number, TxStats, RxStats, const MAX_PACKET_SIZE: usize>
+    Counting<
+        ByteRateSerialLimiter<Serial, Limiter>,
+        Number,
+        TxStats,
+        RxStats,
+        CountingStatistics,
+        MAX_PACKET_SIZE,
+    >
+where
+    Limiter: ByteRateLimiter,
+    Number: Counter,
+    TxStats: RateStatistics,
+    RxStats: RateStatistics,
+{
+    /// Returns ratio of achieved TX rate to the rate limit, 1.0 means the limit is reached.
+    ///
+    /// Only writing is limited, so the TX rate is compared. Returns `None` if writing is unlimited
+    /// or TX rate is not measured yet.
+    pub fn limit_utilization(&self) -> Option<f64> {
+        let limit = self.serial().rate_limit()?.bytes_per_second_f64()?;
+        let tx = self.tx_stats.success_rate()?.bytes_per_second_f64()?;
+
+        if limit <= f64::EPSILON {
+            return None;
+        }
+
+        Some(tx / limit)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use crate::clock::StdClock;
+    use crate::counting::Counting;
+    use crate::statistics::{AvgRateStatistics, CountingStatistics};
+
+    #[test]
+    fn summary_before_start() {
+        let clock = StdClock;
+        let counting = Counting::<_, u8, _, _>::new(
+            (),
+            AvgRateStatistics::new(&clock),
+            AvgRateStatistics::new(&clock),
+            CountingStatistics::default(),
+        );
+
+        let summary = counting.summary();
+        assert!(summary.tx_rate.is_none());
+        assert!(summary.rx_rate.is_none());
+        assert_eq!(summary.loss_ratio, 0.0);
+    }
+
+    #[test]
+    fn aggregate_rate() {
+        use core::time::Duration;
+
+        use crate::byte_rate::limit::{ByteRateSerialLimiter, PollingByteRateLimiter};
+        use crate::byte_rate::rate::ByteRate;
+        use crate::clock::MockClock;
+        use crate::statistics::Statistics;
+
+        let clock = MockClock::new();
+        let limiter =
+            PollingByteRateLimiter::new(ByteRate::new(100, Duration::from_secs(1)), &clock);
+        let mut counting = Counting::<_, u8, _, _>::new(
+            ByteRateSerialLimiter::new((), limiter),
+            AvgRateStatistics::new(&clock),
+            AvgRateStatistics::new(&clock),
+            CountingStatistics::default(),
+        );
+        assert!(counting.aggregate_rate().is_none());
+        assert!(counting.limit_utilization().is_none());
+
+        counting.tx_stats.add_successful(0);
+        counting.rx_stats.add_successful(0);
+        clock.advance(Duration::from_secs(1));
+        counting.tx_stats.add_successful(50);
+        counting.rx_stats.add_successful(40);
+
+        let aggregate = counting.aggregate_rate().unwrap();
+        assert_eq!(aggregate.bytes_per_second(), Some(90));
+        assert_eq!(counting.limit_utilization(), Some(0.5));
+    }
+}
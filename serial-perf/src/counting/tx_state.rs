@@ -1,66 +1,393 @@
 use heapless::Vec;
 
 use super::{
-    counter::{Counter, LeBytes},
-    MAX_PACKET_SIZE,
+    checksum::Checksum,
+    counter::{ByteOrder, Counter, LeBytes, Sequence},
+    preamble::preamble,
+    DEFAULT_CRC_ALGORITHM, PADDING_BYTE,
 };
 
-pub struct TxState<Number> {
-    number_to_send: Number,
+pub struct TxState<Number, const MAX_PACKET_SIZE: usize> {
+    /// Counter value of the first packet sent
+    start: Number,
+    /// Counter value of the next packet, `None` to start from `start`
+    number_to_send: Option<Number>,
+    /// Counter value of the current packet
+    packet_number: Option<Number>,
+    sequence: Sequence,
+    /// Current packet stored reversed, the bytes are sent from the end
     data_to_send: Vec<u8, MAX_PACKET_SIZE>,
-    checksum_enabled: bool,
+    /// Amount of packet bytes left to send, index past the next byte in `data_to_send`
+    data_left: usize,
+    checksum: Checksum,
+    /// Byte separating count from checksum, `None` if packets are not separated
+    separator: Option<u8>,
+    byte_order: ByteOrder,
+    /// Whether packets are COBS framed instead of separated
+    #[cfg(feature = "cobs")]
+    cobs: bool,
+
+    /// Amount of padding bytes sent after each packet
+    padding: usize,
+    /// Amount of padding bytes left to send for current packet
+    padding_left: usize,
+
+    /// Amount of packets sent completely
+    packets_sent: usize,
+    /// Counter value of the last packet sent completely
+    last_sent: Option<Number>,
+
+    /// Whether the width preamble is sent before the first packet
+    width_preamble: bool,
+    /// Whether the width preamble is to be sent next
+    preamble_pending: bool,
+    /// Whether the current data is the width preamble, not a packet
+    sending_preamble: bool,
 }
 
-impl<Number> Default for TxState<Number>
+impl<Number, const MAX_PACKET_SIZE: usize> Default for TxState<Number, MAX_PACKET_SIZE>
 where
-    Number: Default,
+    Number: Counter,
 {
     fn default() -> Self {
-        Self {
-            number_to_send: Default::default(),
-            data_to_send: Vec::new(),
-            checksum_enabled: true,
-        }
+        Self::new(Checksum::crc8(DEFAULT_CRC_ALGORITHM))
     }
 }
 
-impl<Number> TxState<Number>
+impl<Number, const MAX_PACKET_SIZE: usize> TxState<Number, MAX_PACKET_SIZE>
 where
     Number: Counter,
 {
-    pub fn new_without_checksum() -> Self {
+    const PACKET_SIZE_CHECK: () = assert!(
+        MAX_PACKET_SIZE >= super::default_packet_size::<Number>(),
+        "MAX_PACKET_SIZE must fit counter bytes, null terminator and checksum"
+    );
+
+    pub const CRC16_PACKET_SIZE_CHECK: () = assert!(
+        MAX_PACKET_SIZE >= core::mem::size_of::<Number::Bytes>() + 3,
+        "MAX_PACKET_SIZE must fit counter bytes, null terminator and 16-bit checksum"
+    );
+
+    pub fn new(checksum: Checksum) -> Self {
+        let () = Self::PACKET_SIZE_CHECK;
+
         Self {
-            number_to_send: Default::default(),
+            start: Number::min_counter(),
+            number_to_send: None,
+            packet_number: None,
+            sequence: Sequence::Counter,
             data_to_send: Vec::new(),
-            checksum_enabled: false,
+            data_left: 0,
+            checksum,
+            separator: Some(0),
+            byte_order: ByteOrder::Little,
+            #[cfg(feature = "cobs")]
+            cobs: false,
+            padding: 0,
+            padding_left: 0,
+            packets_sent: 0,
+            last_sent: None,
+            width_preamble: false,
+            preamble_pending: false,
+            sending_preamble: false,
         }
     }
 
     pub fn reset(&mut self) {
-        self.number_to_send = Default::default();
+        self.number_to_send = None;
+        self.packet_number = None;
         self.data_to_send.clear();
+        self.data_left = 0;
+        self.padding_left = 0;
+        self.packets_sent = 0;
+        self.last_sent = None;
+        self.preamble_pending = self.width_preamble;
+        self.sending_preamble = false;
+    }
+
+    /// Sets counter value of the first packet sent, applied after reset
+    pub fn set_start(&mut self, start: Number) {
+        self.start = start;
+    }
+
+    /// Sets byte separating count from checksum, `None` to send packets without separator. Applied from the next packet
+    pub fn set_separator(&mut self, separator: Option<u8>) {
+        self.separator = separator;
+    }
+
+    /// Enables COBS framing instead of separator with plain counter bytes. Applied after reset
+    #[cfg(feature = "cobs")]
+    pub fn set_cobs(&mut self, cobs: bool) {
+        self.cobs = cobs;
+        self.sequence = if cobs {
+            Sequence::Plain
+        } else {
+            Sequence::Counter
+        };
+    }
+
+    /// Returns sequence of counter values sent
+    pub fn sequence(&self) -> Sequence {
+        self.sequence
+    }
+
+    /// Enables sending the width preamble before the next packet and the first packet after reset
+    pub fn set_width_preamble(&mut self, width_preamble: bool) {
+        self.width_preamble = width_preamble;
+        self.preamble_pending = width_preamble;
+    }
+
+    /// Sets byte order of counter bytes. Applied from the next packet
+    pub fn set_byte_order(&mut self, byte_order: ByteOrder) {
+        self.byte_order = byte_order;
+    }
+
+    /// Sets amount of padding bytes sent after each packet. Applied from the next packet
+    pub fn set_padding(&mut self, padding: usize) {
+        self.padding = padding;
+    }
+
+    /// Returns size of packet on wire without padding
+    pub fn unpadded_packet_size(&self) -> usize {
+        #[cfg(feature = "cobs")]
+        if self.cobs {
+            let count = core::mem::size_of::<Number::Bytes>();
+            return count + self.checksum.len() + super::cobs::COBS_FRAMING_SIZE;
+        }
+
+        let separator = usize::from(self.separator.is_some());
+        core::mem::size_of::<Number::Bytes>() + separator + self.checksum.len()
+    }
+
+    /// Returns size of packet on wire including padding
+    pub fn packet_size(&self) -> usize {
+        self.unpadded_packet_size() + self.padding
+    }
+
+    /// Returns the number of the last packet sent completely, including padding, if any
+    pub fn last_number(&self) -> Option<Number> {
+        self.last_sent
+    }
+
+    /// Returns amount of packets sent completely, including padding
+    pub fn packets_sent(&self) -> usize {
+        self.packets_sent
+    }
+
+    /// Returns amount of bytes of current packet left to send, including padding
+    pub fn pending_bytes(&self) -> usize {
+        self.data_left + self.padding_left
+    }
+
+    /// Returns true if current packet, including padding, is sent completely
+    pub fn is_packet_sent(&self) -> bool {
+        self.data_left == 0 && self.padding_left == 0
     }
 
     pub fn peek(&mut self) -> u8 {
-        if self.data_to_send.is_empty() {
+        if self.is_packet_sent() {
             self.prepare_next_packet();
         }
 
-        debug_assert!(!self.data_to_send.is_empty());
+        debug_assert!(self.data_left != 0 || self.padding_left != 0);
 
-        self.data_to_send.last().copied().unwrap_or(0)
+        match self.data_left {
+            0 => PADDING_BYTE,
+            left => self.data_to_send[left - 1],
+        }
     }
 
     pub fn take(&mut self) -> u8 {
         let out = self.peek();
-        self.data_to_send.pop();
+        if self.data_left > 0 {
+            self.data_left -= 1;
+        } else {
+            self.padding_left -= 1;
+        }
+
+        if self.is_packet_sent() {
+            if self.sending_preamble {
+                self.sending_preamble = false;
+            } else {
+                self.packets_sent = self.packets_sent.saturating_add(1);
+                self.last_sent = self.packet_number;
+            }
+        }
 
         out
     }
 
+    /// Stages the next packet if current one is sent completely, so the next `peek` does not prepare it
+    pub fn prime(&mut self) {
+        if self.is_packet_sent() {
+            self.prepare_next_packet();
+        }
+    }
+
+    /// Copies the rest of current packet, including padding, into `buf` without taking it.
+    ///
+    /// Returns amount of bytes copied, call `take` for each byte actually sent.
+    #[cfg(any(test, feature = "embedded-io"))]
+    pub fn peek_packet(&mut self, buf: &mut [u8]) -> usize {
+        self.peek();
+
+        let padding = core::iter::repeat_n(PADDING_BYTE, self.padding_left);
+        let data = &self.data_to_send[..self.data_left];
+        let bytes = data.iter().rev().copied().chain(padding);
+
+        let mut copied = 0;
+        for (slot, byte) in buf.iter_mut().zip(bytes) {
+            *slot = byte;
+            copied += 1;
+        }
+
+        copied
+    }
+
     fn prepare_next_packet(&mut self) {
-        let next = self.number_to_send.pop();
-        let data = next.to_le_bytes().into_packet(self.checksum_enabled);
+        if self.preamble_pending {
+            self.preamble_pending = false;
+            self.sending_preamble = true;
+
+            self.data_to_send = preamble::<Number>().into_iter().rev().collect();
+            self.data_left = self.data_to_send.len();
+            self.padding_left = 0;
+            return;
+        }
+
+        let sequence = self.sequence;
+        let start = self.start;
+        let number_to_send = self
+            .number_to_send
+            .get_or_insert_with(|| sequence.first(start));
+        let next = sequence.next(number_to_send);
+        self.packet_number = Some(next);
+        let bytes = self.byte_order.apply(next.to_le_bytes());
+
+        #[cfg(feature = "cobs")]
+        let data = if self.cobs {
+            super::cobs::encode(bytes.into_packet(&self.checksum, None))
+        } else {
+            bytes.into_packet(&self.checksum, self.separator)
+        };
+        #[cfg(not(feature = "cobs"))]
+        let data = bytes.into_packet(&self.checksum, self.separator);
+
+        self.data_left = data.len();
         self.data_to_send = data;
+        self.padding_left = self.padding;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::DEFAULT_MAX_PACKET_SIZE;
+    use super::*;
+
+    #[test]
+    fn last_number() {
+        let mut state = TxState::<u8, DEFAULT_MAX_PACKET_SIZE>::default();
+        assert_eq!(state.last_number(), None);
+
+        // Not sent until the last byte of packet is taken
+        state.take();
+        state.take();
+        assert_eq!(state.last_number(), None);
+
+        state.take();
+        assert_eq!(state.last_number(), Some(u8::min_counter()));
+
+        state.take();
+        assert_eq!(state.last_number(), Some(u8::min_counter()));
+
+        state.reset();
+        assert_eq!(state.last_number(), None);
+    }
+
+    #[test]
+    fn starts_at_start_value() {
+        let mut state = TxState::<u16, DEFAULT_MAX_PACKET_SIZE>::default();
+        state.set_start(1000.to_counter_value().unwrap());
+
+        for _ in 0..4 {
+            state.take();
+        }
+        assert_eq!(state.last_number().unwrap().normalize(), Some(1000));
+
+        state.reset();
+        for _ in 0..4 {
+            state.take();
+        }
+        assert_eq!(state.last_number().unwrap().normalize(), Some(1000));
+    }
+
+    #[test]
+    fn smallest_packet_size() {
+        let mut state = TxState::<u8, 3>::default();
+
+        assert_eq!(state.take(), u8::min_counter());
+        assert_eq!(state.take(), 0);
+        state.take();
+        assert_eq!(state.take(), u8::min_counter() + 1);
+    }
+
+    #[test]
+    fn peek_packet_matches_take() {
+        let mut state = TxState::<u16, DEFAULT_MAX_PACKET_SIZE>::default();
+        state.set_padding(2);
+
+        let mut buf = [0; 16];
+        let len = state.peek_packet(&mut buf);
+        assert_eq!(len, 6);
+
+        for byte in &buf[..len] {
+            assert_eq!(state.take(), *byte);
+        }
+    }
+
+    #[test]
+    fn padding_appended() {
+        let mut state = TxState::<u8, DEFAULT_MAX_PACKET_SIZE>::default();
+        state.set_padding(3);
+
+        let packet: Vec<u8, 16> = (0..6).map(|_| state.take()).collect();
+        assert_eq!(packet[0], u8::min_counter());
+        assert_eq!(packet[1], 0);
+        assert_eq!(&packet[3..], &[PADDING_BYTE; 3]);
+
+        // Next packet starts after padding
+        assert_eq!(state.take(), u8::min_counter() + 1);
+    }
+
+    #[test]
+    fn packets_sent_counted() {
+        let mut state = TxState::<u8, DEFAULT_MAX_PACKET_SIZE>::default();
+        state.set_padding(1);
+
+        for _ in 0..7 {
+            state.take();
+        }
+        assert_eq!(state.packets_sent(), 1);
+
+        state.take();
+        assert_eq!(state.packets_sent(), 2);
+
+        state.reset();
+        assert_eq!(state.packets_sent(), 0);
+    }
+
+    #[test]
+    fn prime() {
+        let mut primed = TxState::<u16, DEFAULT_MAX_PACKET_SIZE>::default();
+        let mut plain = TxState::<u16, DEFAULT_MAX_PACKET_SIZE>::default();
+
+        primed.prime();
+        assert!(!primed.is_packet_sent());
+        primed.prime();
+
+        for _ in 0..8 {
+            assert_eq!(primed.take(), plain.take());
+        }
+        assert_eq!(primed.packets_sent(), 2);
     }
 }
@@ -0,0 +1,193 @@
+//!
+//! Serial wrapper injecting scripted faults into received bytes for testing loss accounting
+//!
+
+use embedded_hal_nb::nb;
+use embedded_hal_nb::serial::{ErrorType, Read, Write};
+
+/// Fault applied to a byte received, the bytes are indexed from 0 in order they are read from the wrapped serial
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// The byte is lost
+    Drop(usize),
+    /// The `bit` of the byte is inverted, counted from the least significant bit modulo 8
+    FlipBit { index: usize, bit: u8 },
+    /// The byte is received twice
+    Duplicate(usize),
+}
+
+impl Fault {
+    /// Returns index of the byte affected
+    pub fn index(&self) -> usize {
+        match self {
+            Self::Drop(index) | Self::FlipBit { index, .. } | Self::Duplicate(index) => *index,
+        }
+    }
+}
+
+/// A wrapper around serial that applies `faults` to bytes read, writing is passed as is
+///
+/// Combine it with `PairedSerial` to check loss, CRC and desync counting deterministically.
+pub struct FaultySerial<'a, Serial> {
+    serial: Serial,
+    faults: &'a [Fault],
+    /// Amount of bytes read from the wrapped serial
+    read_count: usize,
+    /// Byte to return again on the next read
+    duplicate: Option<u8>,
+}
+
+impl<'a, Serial> FaultySerial<'a, Serial> {
+    pub fn new(serial: Serial, faults: &'a [Fault]) -> Self {
+        Self {
+            serial,
+            faults,
+            read_count: 0,
+            duplicate: None,
+        }
+    }
+
+    /// Returns amount of bytes read from the wrapped serial
+    pub fn read_count(&self) -> usize {
+        self.read_count
+    }
+
+    /// Returns reference to the underlying serial
+    pub fn serial(&self) -> &Serial {
+        &self.serial
+    }
+
+    /// Destroys the wrapper returning the underlying serial
+    pub fn into_inner(self) -> Serial {
+        self.serial
+    }
+}
+
+impl<Serial> ErrorType for FaultySerial<'_, Serial>
+where
+    Serial: ErrorType,
+{
+    type Error = Serial::Error;
+}
+
+impl<Serial> Read for FaultySerial<'_, Serial>
+where
+    Serial: Read,
+{
+    fn read(&mut self) -> nb::Result<u8, Self::Error> {
+        if let Some(byte) = self.duplicate.take() {
+            return Ok(byte);
+        }
+
+        loop {
+            let mut byte = self.serial.read()?;
+            let index = self.read_count;
+            self.read_count += 1;
+
+            let mut dropped = false;
+            let mut duplicated = false;
+            for fault in self.faults.iter().filter(|f| f.index() == index) {
+                match fault {
+                    Fault::Drop(_) => dropped = true,
+                    Fault::FlipBit { bit, .. } => byte ^= 1 << (bit % 8),
+                    Fault::Duplicate(_) => duplicated = true,
+                }
+            }
+
+            if dropped {
+                continue;
+            }
+
+            if duplicated {
+                self.duplicate = Some(byte);
+            }
+
+            return Ok(byte);
+        }
+    }
+}
+
+impl<Serial> Write for FaultySerial<'_, Serial>
+where
+    Serial: Write,
+{
+    fn write(&mut self, word: u8) -> nb::Result<(), Self::Error> {
+        self.serial.write(word)
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        self.serial.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::counting::Counting;
+    use crate::paired_serial::PairedSerial;
+    use crate::statistics::CountingStatistics;
+
+    use super::{Fault, FaultySerial};
+
+    /// Sends 10 `u16` packets of 4 bytes through faulty serial, returns lost packets, CRC errors and desyncs
+    fn run(faults: &[Fault]) -> (usize, usize, usize) {
+        let paired = PairedSerial::<64>::new();
+        let (a, b) = paired.split();
+
+        let new_counting = |serial| {
+            Counting::<_, u16>::new(
+                serial,
+                CountingStatistics::default(),
+                CountingStatistics::default(),
+                CountingStatistics::default(),
+            )
+        };
+        let mut client = new_counting(FaultySerial::new(a, &[]));
+        let mut server = new_counting(FaultySerial::new(b, faults));
+
+        for _ in 0..10 * 4 {
+            client.send_nb().unwrap();
+        }
+        while server.recv_nb().is_ok() {}
+
+        (
+            server.loss_stats().failed(),
+            server.crc_errors(),
+            server.desync_count(),
+        )
+    }
+
+    #[test]
+    fn no_faults() {
+        assert_eq!(run(&[]), (0, 0, 0));
+    }
+
+    #[test]
+    fn dropped_byte() {
+        // The second byte of count in the second packet
+        assert_eq!(run(&[Fault::Drop(5)]), (1, 0, 0));
+    }
+
+    #[test]
+    fn flipped_bit() {
+        assert_eq!(run(&[Fault::FlipBit { index: 4, bit: 3 }]), (1, 1, 0));
+        // Out of range bit wraps instead of overflowing the shift
+        assert_eq!(
+            run(&[Fault::FlipBit { index: 4, bit: 11 }]),
+            run(&[Fault::FlipBit { index: 4, bit: 3 }])
+        );
+    }
+
+    #[test]
+    fn duplicated_byte() {
+        // Checksum of the second packet is received as count of the third one
+        assert_eq!(run(&[Fault::Duplicate(7)]), (1, 0, 0));
+    }
+
+    #[test]
+    fn lost_separators_desync() {
+        // Separators of the second to fourth packets are corrupted, overflowing the packet buffer.
+        // The fifth packet is lost too, its bytes are received after the overflow.
+        let faults = [6, 10, 14].map(|index| Fault::FlipBit { index, bit: 0 });
+        assert_eq!(run(&faults), (4, 0, 1));
+    }
+}
@@ -0,0 +1,49 @@
+//!
+//! Flushing the serial automatically every N bytes written
+//!
+//! Only the byte cadence is supported: the tests don't own a clock, so flush every T milliseconds from the loop
+//! owning the clock instead, e.g. with `Counting::timed_flush` which also measures the flush latency.
+//!
+
+use embedded_hal_nb::nb;
+use embedded_hal_nb::serial::Write;
+
+/// Counts bytes written since the last flush, the flush is due every `every` bytes. Disabled if `every` is 0
+#[derive(Debug, Default, Clone)]
+pub(crate) struct FlushCadence {
+    every: usize,
+    unflushed: usize,
+}
+
+impl FlushCadence {
+    pub fn new(every: usize) -> Self {
+        Self {
+            every,
+            unflushed: 0,
+        }
+    }
+
+    pub fn on_written(&mut self, bytes: usize) {
+        self.unflushed = self.unflushed.saturating_add(bytes);
+    }
+
+    /// Returns `true` if enough bytes are written since the last flush
+    pub fn is_due(&self) -> bool {
+        self.every != 0 && self.unflushed >= self.every
+    }
+
+    pub fn on_flushed(&mut self) {
+        self.unflushed = 0;
+    }
+
+    /// Flushes `serial` if enough bytes are written since the last flush
+    pub fn flush_if_due<S: Write>(&mut self, serial: &mut S) -> nb::Result<(), S::Error> {
+        if !self.is_due() {
+            return Ok(());
+        }
+
+        serial.flush()?;
+        self.on_flushed();
+        Ok(())
+    }
+}
@@ -0,0 +1,123 @@
+//!
+//! Adapter of `std::io` serial ports, e.g. from `serialport` crate, to `embedded_hal_nb` serial traits
+//!
+
+use std::io::ErrorKind;
+
+use embedded_hal_nb::nb;
+use embedded_hal_nb::serial::{self, ErrorType, Read, Write};
+
+/// Error of the `std::io` serial port
+#[derive(Debug)]
+pub struct IoSerialError(pub std::io::Error);
+
+impl serial::Error for IoSerialError {
+    fn kind(&self) -> serial::ErrorKind {
+        serial::ErrorKind::Other
+    }
+}
+
+impl core::fmt::Display for IoSerialError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "serial error: {}", self.0)
+    }
+}
+
+impl std::error::Error for IoSerialError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+/// Wrapper of `std::io::Read + std::io::Write` serial port implementing `embedded_hal_nb` serial traits
+///
+/// Bytes are read and written one by one. `WouldBlock`, `TimedOut` and `Interrupted` errors as well as
+/// zero sized reads and writes are reported as `nb::Error::WouldBlock`, so set a short timeout on the port
+/// to keep `loop_nb` of the tests responsive.
+pub struct IoSerial<T> {
+    inner: T,
+}
+
+impl<T> IoSerial<T> {
+    pub fn new(inner: T) -> Self {
+        Self { inner }
+    }
+
+    /// Returns reference to the underlying port
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Returns mutable reference to the underlying port
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Destroys the adapter returning the underlying port
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+fn map_io_error(error: std::io::Error) -> nb::Error<IoSerialError> {
+    match error.kind() {
+        ErrorKind::WouldBlock | ErrorKind::TimedOut | ErrorKind::Interrupted => {
+            nb::Error::WouldBlock
+        }
+        _ => nb::Error::Other(IoSerialError(error)),
+    }
+}
+
+impl<T> ErrorType for IoSerial<T> {
+    type Error = IoSerialError;
+}
+
+impl<T> Read for IoSerial<T>
+where
+    T: std::io::Read,
+{
+    fn read(&mut self) -> nb::Result<u8, Self::Error> {
+        let mut buf = [0];
+        match self.inner.read(&mut buf).map_err(map_io_error)? {
+            0 => Err(nb::Error::WouldBlock),
+            _ => Ok(buf[0]),
+        }
+    }
+}
+
+impl<T> Write for IoSerial<T>
+where
+    T: std::io::Write,
+{
+    fn write(&mut self, word: u8) -> nb::Result<(), Self::Error> {
+        match self.inner.write(&[word]).map_err(map_io_error)? {
+            0 => Err(nb::Error::WouldBlock),
+            _ => Ok(()),
+        }
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        self.inner.flush().map_err(map_io_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use embedded_hal_nb::nb;
+    use embedded_hal_nb::serial::{Read, Write};
+
+    use super::IoSerial;
+
+    #[test]
+    fn bytes_and_would_block() {
+        let mut serial = IoSerial::new(VecDeque::<u8>::new());
+        assert!(matches!(serial.read(), Err(nb::Error::WouldBlock)));
+
+        serial.write(0x42).unwrap();
+        serial.flush().unwrap();
+        assert_eq!(serial.read().unwrap(), 0x42);
+        assert!(matches!(serial.read(), Err(nb::Error::WouldBlock)));
+    }
+}
@@ -1,9 +1,26 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
 pub mod byte_rate;
 pub mod clock;
+#[cfg(any(test, feature = "test-util"))]
+pub mod faulty_serial;
+mod flush_cadence;
+#[cfg(feature = "std")]
+pub mod io_serial;
+#[cfg(any(test, feature = "test-util"))]
+pub mod paired_serial;
+pub mod report;
 pub mod statistics;
+#[cfg(any(feature = "async", feature = "embedded-io"))]
+pub mod stream_error;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_serial;
+pub mod timeout_serial;
 
 // Tests
 pub mod counting;
 pub mod loopback;
+pub mod pattern;
@@ -0,0 +1,158 @@
+use embedded_io_async::{Read, Write};
+
+use crate::statistics::Statistics;
+use crate::stream_error::StreamError;
+
+use super::{Loopback, State};
+
+impl<Serial, TxStats, RxStats> Loopback<Serial, TxStats, RxStats>
+where
+    Serial: Read,
+    TxStats: Statistics,
+    RxStats: Statistics,
+{
+    /// Receives byte from the serial port. Waits until byte is received.
+    ///
+    /// Returns `StreamError::Closed` if the serial reached end of file, it's counted as failed receive.
+    pub async fn recv_async(&mut self) -> Result<(), StreamError<Serial::Error>> {
+        let mut byte_read = [0];
+        match self.serial.read(&mut byte_read).await {
+            Ok(0) => {
+                self.rx_stats.add_failed(1);
+                Err(StreamError::Closed)
+            }
+            Ok(_) => {
+                self.on_byte_received(byte_read[0]);
+                Ok(())
+            }
+            Err(e) => {
+                self.rx_stats.add_failed(1);
+                Err(StreamError::Serial(e))
+            }
+        }
+    }
+}
+
+impl<Serial, TxStats, RxStats> Loopback<Serial, TxStats, RxStats>
+where
+    Serial: Write,
+    TxStats: Statistics,
+    RxStats: Statistics,
+{
+    /// Sends received byte back. Waits until byte is accepted by serial.
+    ///
+    /// Does nothing if there is no byte to send. Returns `StreamError::Closed` if the serial accepted no bytes, it's
+    /// counted as failed send. Flushes the serial every N bytes if configured with `with_flush_every`.
+    pub async fn send_async(&mut self) -> Result<(), StreamError<Serial::Error>> {
+        let Some(byte_to_send) = self.byte_to_send() else {
+            return Ok(());
+        };
+
+        match self.serial.write(&[byte_to_send]).await {
+            Ok(0) => {
+                self.tx_stats.add_failed(1);
+                Err(StreamError::Closed)
+            }
+            Ok(_) => {
+                self.on_byte_sent();
+                self.flush_cadence.on_written(1);
+                if self.flush_cadence.is_due() {
+                    self.serial.flush().await.map_err(StreamError::Serial)?;
+                    self.flush_cadence.on_flushed();
+                }
+                Ok(())
+            }
+            Err(e) => {
+                self.tx_stats.add_failed(1);
+                Err(StreamError::Serial(e))
+            }
+        }
+    }
+
+    /// Flushes serial port, waits until all data is sent.
+    ///
+    /// # Warning
+    /// The error happened here will not affect tx_state
+    pub async fn flush_async(&mut self) -> Result<(), Serial::Error> {
+        self.serial.flush().await
+    }
+}
+
+impl<Serial, TxStats, RxStats> Loopback<Serial, TxStats, RxStats>
+where
+    Serial: Write + Read,
+    TxStats: Statistics,
+    RxStats: Statistics,
+{
+    pub async fn loop_async(&mut self) -> Result<(), StreamError<Serial::Error>> {
+        match self.state {
+            State::Receiving => self.recv_async().await,
+            State::Transfer(_) => self.send_async().await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::counting::Counting;
+    use crate::paired_serial::PairedSerial;
+    use crate::statistics::CountingStatistics;
+    use crate::stream_error::StreamError;
+    use crate::test_serial::{block_on, ClosedSerial};
+
+    use super::Loopback;
+
+    #[test]
+    fn loop_async_echo() {
+        let paired = PairedSerial::<16>::new();
+        let (counting_serial, loopback_serial) = paired.split();
+
+        let mut counting = Counting::<_, u16>::new(
+            counting_serial,
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+        let mut loopback = Loopback::new(
+            loopback_serial,
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+
+        // Loopback receives and sends back each byte right after it is sent
+        for _ in 0..400 {
+            counting.loop_nb().ok();
+            block_on(loopback.loop_async()).unwrap();
+            block_on(loopback.loop_async()).unwrap();
+        }
+        counting.recv_nb().unwrap();
+
+        assert_eq!(loopback.rx_stats().successful(), 400);
+        assert_eq!(loopback.tx_stats().successful(), 400);
+        assert_eq!(counting.rx_stats().successful(), 400);
+        assert_eq!(counting.loss_stats().failed(), 0);
+        assert_eq!(counting.loss_stats().successful(), 100);
+    }
+
+    #[test]
+    fn closed_serial() {
+        let mut loopback = Loopback::new_transmitting(
+            ClosedSerial,
+            0x42,
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+
+        assert!(matches!(
+            block_on(loopback.send_async()),
+            Err(StreamError::Closed)
+        ));
+        assert!(matches!(
+            block_on(loopback.recv_async()),
+            Err(StreamError::Closed)
+        ));
+
+        assert_eq!(loopback.tx_stats().failed(), 1);
+        assert_eq!(loopback.rx_stats().failed(), 1);
+    }
+}
@@ -0,0 +1,132 @@
+use embedded_io_async::{Read, ReadReady, Write};
+
+use crate::statistics::Statistics;
+use crate::stream_error::StreamError;
+
+use super::BufferedLoopback;
+
+impl<Serial, const N: usize, TxStats, RxStats> BufferedLoopback<Serial, N, TxStats, RxStats>
+where
+    Serial: Read,
+    TxStats: Statistics,
+    RxStats: Statistics,
+{
+    /// Receives bytes from the serial port up to free place in buffer. Waits until any byte is received.
+    ///
+    /// Does nothing if the buffer is full. Returns `StreamError::Closed` if the serial reached end of file, it's
+    /// counted as failed receive.
+    pub async fn recv_async(&mut self) -> Result<(), StreamError<Serial::Error>> {
+        let mut bytes_read = [0; N];
+        let free = N - self.buffer.len();
+        if free == 0 {
+            return Ok(());
+        }
+
+        match self.serial.read(&mut bytes_read[..free]).await {
+            Ok(0) => {
+                self.rx_stats.add_failed(1);
+                Err(StreamError::Closed)
+            }
+            Ok(read) => {
+                for byte in &bytes_read[..read] {
+                    self.on_byte_received(*byte);
+                }
+                Ok(())
+            }
+            Err(e) => {
+                self.rx_stats.add_failed(1);
+                Err(StreamError::Serial(e))
+            }
+        }
+    }
+}
+
+impl<Serial, const N: usize, TxStats, RxStats> BufferedLoopback<Serial, N, TxStats, RxStats>
+where
+    Serial: Write,
+    TxStats: Statistics,
+    RxStats: Statistics,
+{
+    /// Sends the oldest buffered byte back. Waits until byte is accepted by serial.
+    ///
+    /// Does nothing if there is no byte to send. Returns `StreamError::Closed` if the serial accepted no bytes, it's
+    /// counted as failed send. Flushes the serial every N bytes if configured with `with_flush_every`.
+    pub async fn send_async(&mut self) -> Result<(), StreamError<Serial::Error>> {
+        let Some(byte_to_send) = self.byte_to_send() else {
+            return Ok(());
+        };
+
+        match self.serial.write(&[byte_to_send]).await {
+            Ok(0) => {
+                self.tx_stats.add_failed(1);
+                Err(StreamError::Closed)
+            }
+            Ok(_) => {
+                self.on_byte_sent();
+                self.flush_cadence.on_written(1);
+                if self.flush_cadence.is_due() {
+                    self.serial.flush().await.map_err(StreamError::Serial)?;
+                    self.flush_cadence.on_flushed();
+                }
+                Ok(())
+            }
+            Err(e) => {
+                self.tx_stats.add_failed(1);
+                Err(StreamError::Serial(e))
+            }
+        }
+    }
+
+    /// Flushes serial port, waits until all data is sent.
+    pub async fn flush_async(&mut self) -> Result<(), Serial::Error> {
+        self.serial.flush().await
+    }
+}
+
+impl<Serial, const N: usize, TxStats, RxStats> BufferedLoopback<Serial, N, TxStats, RxStats>
+where
+    Serial: Write + Read + ReadReady,
+    TxStats: Statistics,
+    RxStats: Statistics,
+{
+    /// Receives bytes if any ready and there is place in buffer, then sends buffered byte.
+    ///
+    /// Waits for new bytes only if buffer is empty.
+    pub async fn loop_async(&mut self) -> Result<(), StreamError<Serial::Error>> {
+        if self.buffer.is_empty()
+            || (!self.buffer.is_full() && self.serial.read_ready().map_err(StreamError::Serial)?)
+        {
+            self.recv_async().await?;
+        }
+
+        self.send_async().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::statistics::CountingStatistics;
+    use crate::stream_error::StreamError;
+    use crate::test_serial::{block_on, ClosedSerial};
+
+    use super::BufferedLoopback;
+
+    #[test]
+    fn closed_serial() {
+        let mut loopback = BufferedLoopback::<_, 4>::new(
+            ClosedSerial,
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+
+        assert!(matches!(
+            block_on(loopback.recv_async()),
+            Err(StreamError::Closed)
+        ));
+        // Nothing is received, so nothing to send
+        assert!(block_on(loopback.send_async()).is_ok());
+
+        assert_eq!(loopback.rx_stats().failed(), 1);
+        assert_eq!(loopback.tx_stats().total(), 0);
+    }
+}
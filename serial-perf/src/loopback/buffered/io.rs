@@ -0,0 +1,161 @@
+use embedded_io::{Read, ReadReady, Write};
+
+use crate::statistics::Statistics;
+use crate::stream_error::StreamError;
+
+use super::BufferedLoopback;
+
+/// Size of buffer used to write bytes in one call
+const WRITE_BUFFER_SIZE: usize = 64;
+
+impl<Serial, const N: usize, TxStats, RxStats> BufferedLoopback<Serial, N, TxStats, RxStats>
+where
+    Serial: Read,
+    TxStats: Statistics,
+    RxStats: Statistics,
+{
+    /// Receives bytes from the serial port up to free place in buffer. Blocks until any byte is received.
+    ///
+    /// Does nothing if the buffer is full. Returns `StreamError::Closed` if the serial reached end of file, it's
+    /// counted as failed receive.
+    pub fn recv_io(&mut self) -> Result<(), StreamError<Serial::Error>> {
+        let mut bytes_read = [0; N];
+        let free = N - self.buffer.len();
+        if free == 0 {
+            return Ok(());
+        }
+
+        match self.serial.read(&mut bytes_read[..free]) {
+            Ok(0) => {
+                self.rx_stats.add_failed(1);
+                Err(StreamError::Closed)
+            }
+            Ok(read) => {
+                for byte in &bytes_read[..read] {
+                    self.on_byte_received(*byte);
+                }
+                Ok(())
+            }
+            Err(e) => {
+                self.rx_stats.add_failed(1);
+                Err(StreamError::Serial(e))
+            }
+        }
+    }
+}
+
+impl<Serial, const N: usize, TxStats, RxStats> BufferedLoopback<Serial, N, TxStats, RxStats>
+where
+    Serial: Write,
+    TxStats: Statistics,
+    RxStats: Statistics,
+{
+    /// Sends buffered bytes back. Blocks until any byte is accepted by serial.
+    ///
+    /// Does nothing if there is no byte to send. Returns `StreamError::Closed` if the serial accepted no bytes, it's
+    /// counted as failed send. Flushes the serial every N bytes if configured with `with_flush_every`.
+    pub fn send_io(&mut self) -> Result<(), StreamError<Serial::Error>> {
+        let mut bytes_to_send = [0; WRITE_BUFFER_SIZE];
+        let mut len = 0;
+        for (slot, byte) in bytes_to_send.iter_mut().zip(self.buffer.iter()) {
+            *slot = self.transform.apply(*byte);
+            len += 1;
+        }
+
+        if len == 0 {
+            return Ok(());
+        }
+
+        match self.serial.write(&bytes_to_send[..len]) {
+            Ok(0) => {
+                self.tx_stats.add_failed(1);
+                Err(StreamError::Closed)
+            }
+            Ok(written) => {
+                for _ in 0..written {
+                    self.on_byte_sent();
+                }
+                self.flush_cadence.on_written(written);
+                if self.flush_cadence.is_due() {
+                    self.serial.flush().map_err(StreamError::Serial)?;
+                    self.flush_cadence.on_flushed();
+                }
+                Ok(())
+            }
+            Err(e) => {
+                self.tx_stats.add_failed(1);
+                Err(StreamError::Serial(e))
+            }
+        }
+    }
+
+    /// Flushes serial port, blocks until all data is sent.
+    pub fn flush_io(&mut self) -> Result<(), Serial::Error> {
+        self.serial.flush()
+    }
+}
+
+impl<Serial, const N: usize, TxStats, RxStats> BufferedLoopback<Serial, N, TxStats, RxStats>
+where
+    Serial: Write + Read + ReadReady,
+    TxStats: Statistics,
+    RxStats: Statistics,
+{
+    /// Receives bytes if any ready and there is place in buffer, then sends buffered bytes.
+    ///
+    /// Blocks for new bytes only if buffer is empty.
+    pub fn loop_io(&mut self) -> Result<(), StreamError<Serial::Error>> {
+        if self.buffer.is_empty()
+            || (!self.buffer.is_full() && self.serial.read_ready().map_err(StreamError::Serial)?)
+        {
+            self.recv_io()?;
+        }
+
+        self.send_io()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::counting::Counting;
+    use crate::paired_serial::PairedSerial;
+    use crate::statistics::CountingStatistics;
+
+    use super::BufferedLoopback;
+
+    #[test]
+    fn loop_io_echo() {
+        let paired = PairedSerial::<16>::new();
+        let (counting_serial, loopback_serial) = paired.split();
+
+        let mut counting = Counting::<_, u16>::new(
+            counting_serial,
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+        let mut loopback = BufferedLoopback::<_, 16>::new(
+            loopback_serial,
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+
+        // Loopback receives and sends back a burst of two packets at once
+        for _ in 0..50 {
+            for _ in 0..8 {
+                counting.send_nb().unwrap();
+            }
+            loopback.loop_io().unwrap();
+            assert_eq!(loopback.buffered(), 0);
+            for _ in 0..8 {
+                counting.recv_nb().unwrap();
+            }
+        }
+
+        assert_eq!(loopback.rx_stats().successful(), 400);
+        assert_eq!(loopback.tx_stats().successful(), 400);
+        assert_eq!(counting.rx_stats().successful(), 400);
+        assert_eq!(counting.loss_stats().failed(), 0);
+        assert_eq!(counting.loss_stats().successful(), 100);
+    }
+}
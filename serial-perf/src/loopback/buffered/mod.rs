@@ -0,0 +1,165 @@
+use crate::flush_cadence::FlushCadence;
+use crate::statistics::{CountingStatistics, Statistics};
+
+use super::Transform;
+
+#[cfg(feature = "async")]
+mod asynch;
+#[cfg(feature = "embedded-io")]
+mod io;
+mod nb;
+
+/// A wrapper around serial that sends data it's received, buffering up to `N` bytes
+///
+/// Unlike `Loopback` it can receive a burst of bytes while previous bytes are not sent yet.
+///
+/// # Note
+/// A byte received when the buffer is full is dropped and counted as TX error.
+pub struct BufferedLoopback<
+    Serial,
+    const N: usize,
+    TxStats = CountingStatistics,
+    RxStats = CountingStatistics,
+> {
+    serial: Serial,
+    buffer: heapless::Deque<u8, N>,
+    transform: Transform,
+
+    tx_stats: TxStats,
+    rx_stats: RxStats,
+
+    flush_cadence: FlushCadence,
+}
+
+impl<Serial, const N: usize, TxStats, RxStats> BufferedLoopback<Serial, N, TxStats, RxStats>
+where
+    TxStats: Statistics,
+    RxStats: Statistics,
+{
+    /// Create a new loopback instance using provided serial and statistics.
+    ///
+    /// # Note
+    /// The provided statistics will not reset upon creation, so you may want to call `reset` after creation if desired.
+    pub fn new(serial: Serial, tx_stats: TxStats, rx_stats: RxStats) -> Self {
+        Self {
+            serial,
+            buffer: heapless::Deque::new(),
+            transform: Transform::Echo,
+            tx_stats,
+            rx_stats,
+            flush_cadence: FlushCadence::default(),
+        }
+    }
+
+    /// Sets the transformation applied to bytes before sending them back
+    pub fn with_transform(mut self, transform: Transform) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    /// Flushes the serial automatically every `bytes` bytes sent, 0 disables it.
+    ///
+    /// Applied by `send_nb`, `send_io` and `send_async`, if the flush would block, the next `send_nb` completes it
+    /// before sending.
+    pub fn with_flush_every(mut self, bytes: usize) -> Self {
+        self.flush_cadence = FlushCadence::new(bytes);
+        self
+    }
+
+    pub fn tx_stats(&self) -> &TxStats {
+        &self.tx_stats
+    }
+
+    pub fn rx_stats(&self) -> &RxStats {
+        &self.rx_stats
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.tx_stats.reset();
+        self.rx_stats.reset();
+    }
+
+    /// Returns amount of bytes received but not sent back yet
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Returns reference to the underlying serial
+    pub fn serial(&self) -> &Serial {
+        &self.serial
+    }
+
+    /// Returns mutable reference to the underlying serial
+    pub fn serial_mut(&mut self) -> &mut Serial {
+        &mut self.serial
+    }
+
+    /// Destroys the wrapper returning the underlying serial
+    pub fn into_inner(self) -> Serial {
+        self.serial
+    }
+
+    fn on_byte_received(&mut self, byte: u8) {
+        if self.buffer.push_back(byte).is_err() {
+            // No place to store the byte, it will never be sent back -> add Tx Error
+            self.tx_stats.add_failed(1);
+        }
+
+        self.rx_stats.add_successful(1);
+    }
+
+    fn on_byte_sent(&mut self) {
+        self.buffer.pop_front();
+        self.tx_stats.add_successful(1);
+    }
+
+    fn byte_to_send(&self) -> Option<u8> {
+        self.buffer.front().map(|byte| self.transform.apply(*byte))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::statistics::CountingStatistics;
+
+    use super::BufferedLoopback;
+
+    #[test]
+    fn burst_buffered_in_order() {
+        let mut loopback = BufferedLoopback::<(), 4>::new(
+            (),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+
+        for byte in 1..=3 {
+            loopback.on_byte_received(byte);
+        }
+
+        for byte in 1..=3 {
+            assert_eq!(loopback.byte_to_send(), Some(byte));
+            loopback.on_byte_sent();
+        }
+
+        assert_eq!(loopback.byte_to_send(), None);
+        assert_eq!(loopback.tx_stats().successful(), 3);
+        assert_eq!(loopback.tx_stats().failed(), 0);
+    }
+
+    #[test]
+    fn overflow_counted_as_tx_error() {
+        let mut loopback = BufferedLoopback::<(), 2>::new(
+            (),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+
+        for byte in 0..3 {
+            loopback.on_byte_received(byte);
+        }
+
+        assert_eq!(loopback.buffered(), 2);
+        assert_eq!(loopback.rx_stats().successful(), 3);
+        assert_eq!(loopback.tx_stats().failed(), 1);
+    }
+}
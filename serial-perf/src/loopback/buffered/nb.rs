@@ -0,0 +1,195 @@
+use embedded_hal_nb::nb::{Error, Result};
+use embedded_hal_nb::serial::{Read, Write};
+
+use crate::statistics::Statistics;
+
+use super::BufferedLoopback;
+
+impl<Serial, const N: usize, TxStats, RxStats> BufferedLoopback<Serial, N, TxStats, RxStats>
+where
+    Serial: Read,
+    TxStats: Statistics,
+    RxStats: Statistics,
+{
+    pub fn recv_nb(&mut self) -> Result<(), Serial::Error> {
+        let byte_read = match self.serial.read() {
+            Ok(b) => b,
+            Err(Error::WouldBlock) => return Err(Error::WouldBlock),
+            Err(e) => {
+                self.rx_stats.add_failed(1);
+                return Err(e);
+            }
+        };
+
+        self.on_byte_received(byte_read);
+
+        Ok(())
+    }
+}
+
+impl<Serial, const N: usize, TxStats, RxStats> BufferedLoopback<Serial, N, TxStats, RxStats>
+where
+    Serial: Write,
+    TxStats: Statistics,
+    RxStats: Statistics,
+{
+    /// Sends the oldest buffered byte using non blocking API
+    ///
+    /// Flushes the serial every N bytes if configured with `with_flush_every`.
+    pub fn send_nb(&mut self) -> Result<(), Serial::Error> {
+        // Complete the flush blocked after the previous byte
+        if let Err(e) = self.flush_cadence.flush_if_due(&mut self.serial) {
+            if let Error::WouldBlock = e {
+                self.tx_stats.add_blocked(1);
+            }
+            return Err(e);
+        }
+
+        let byte_to_send = self.byte_to_send().ok_or(Error::WouldBlock)?;
+
+        match self.serial.write(byte_to_send) {
+            Ok(_) => {
+                self.on_byte_sent();
+                self.flush_cadence.on_written(1);
+                match self.flush_cadence.flush_if_due(&mut self.serial) {
+                    Ok(()) | Err(Error::WouldBlock) => Ok(()),
+                    Err(e) => Err(e),
+                }
+            }
+            Err(Error::WouldBlock) => {
+                self.tx_stats.add_blocked(1);
+                Err(Error::WouldBlock)
+            }
+            Err(e) => {
+                self.tx_stats.add_failed(1);
+                Err(e)
+            }
+        }
+    }
+
+    /// Flushes serial port using non blocking API
+    pub fn flush_nb(&mut self) -> Result<(), Serial::Error> {
+        self.serial.flush()
+    }
+}
+
+impl<Serial, const N: usize, TxStats, RxStats> BufferedLoopback<Serial, N, TxStats, RxStats>
+where
+    Serial: Write + Read,
+    TxStats: Statistics,
+    RxStats: Statistics,
+{
+    /// Receives byte if there is place in buffer and nothing else to do, otherwise sends buffered byte
+    pub fn loop_nb(&mut self) -> Result<(), Serial::Error> {
+        if !self.buffer.is_full() {
+            match self.recv_nb() {
+                Err(Error::WouldBlock) => (),
+                other => return other,
+            }
+        }
+
+        self.send_nb()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_hal_nb::nb::Error;
+    use embedded_hal_nb::serial::{Read, Write};
+
+    use crate::paired_serial::PairedSerial;
+    use crate::statistics::{BlockedStatistics, CountingStatistics};
+    use crate::test_serial::ScriptedSerial;
+
+    use super::BufferedLoopback;
+
+    #[test]
+    fn flush_every() {
+        let mut loopback = BufferedLoopback::<_, 16, _>::new(
+            ScriptedSerial::new().reading(0x42).blocking_flushes(2),
+            BlockedStatistics::new(CountingStatistics::default()),
+            CountingStatistics::default(),
+        )
+        .with_flush_every(4);
+
+        for _ in 0..11 {
+            loopback.recv_nb().unwrap();
+        }
+        for _ in 0..4 {
+            loopback.send_nb().unwrap();
+        }
+
+        // The flush after the 4th byte is blocked, the next send completes it first
+        assert!(matches!(loopback.send_nb(), Err(Error::WouldBlock)));
+        assert_eq!(loopback.tx_stats().blocked(), 1);
+        assert_eq!(loopback.buffered(), 7);
+
+        while loopback.buffered() > 0 {
+            loopback.send_nb().unwrap();
+        }
+
+        assert_eq!(loopback.tx_stats().inner().successful(), 11);
+        assert_eq!(loopback.serial().flushes(), 2);
+    }
+
+    #[test]
+    fn loop_nb_echoes_burst_in_order() {
+        let paired = PairedSerial::<16>::new();
+        let (mut peer, serial) = paired.split();
+        let mut loopback = BufferedLoopback::<_, 4>::new(
+            serial,
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+
+        // The burst is larger than the buffer, loop_nb receives only while there is place
+        for byte in 1..=10 {
+            peer.write(byte).unwrap();
+        }
+
+        let mut echo = heapless::Vec::<u8, 10>::new();
+        while echo.len() < 10 {
+            loopback.loop_nb().unwrap();
+            assert!(loopback.buffered() <= 4);
+            if let Ok(byte) = peer.read() {
+                echo.push(byte).unwrap();
+            }
+        }
+
+        assert_eq!(echo, [1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+        assert_eq!(loopback.rx_stats().successful(), 10);
+        assert_eq!(loopback.tx_stats().successful(), 10);
+        assert_eq!(loopback.tx_stats().failed(), 0);
+    }
+
+    #[test]
+    fn overflow_counted_as_failed_send() {
+        let paired = PairedSerial::<16>::new();
+        let (mut peer, serial) = paired.split();
+        let mut loopback = BufferedLoopback::<_, 4>::new(
+            serial,
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+
+        for byte in 1..=6 {
+            peer.write(byte).unwrap();
+        }
+        for _ in 0..6 {
+            loopback.recv_nb().unwrap();
+        }
+
+        // Bytes received into the full buffer are never sent back
+        assert_eq!(loopback.buffered(), 4);
+        assert_eq!(loopback.rx_stats().successful(), 6);
+        assert_eq!(loopback.tx_stats().failed(), 2);
+
+        while loopback.send_nb().is_ok() {}
+
+        for byte in 1..=4 {
+            assert_eq!(peer.read(), Ok(byte));
+        }
+        assert!(matches!(peer.read(), Err(Error::WouldBlock)));
+        assert_eq!(loopback.tx_stats().successful(), 4);
+    }
+}
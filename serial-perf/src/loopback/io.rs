@@ -0,0 +1,188 @@
+use embedded_io::{Read, Write};
+
+use crate::statistics::Statistics;
+use crate::stream_error::StreamError;
+
+use super::{Loopback, State};
+
+/// Size of buffer used to receive and send back bytes in one call
+const BUFFER_SIZE: usize = 64;
+
+impl<Serial, TxStats, RxStats> Loopback<Serial, TxStats, RxStats>
+where
+    Serial: Write,
+    TxStats: Statistics,
+    RxStats: Statistics,
+{
+    /// Sends the byte left to send by `new_transmitting` or `recv_nb` back. Blocks until byte is accepted by serial.
+    ///
+    /// Does nothing if there is no byte to send. Returns `StreamError::Closed` if the serial accepted no bytes, it's
+    /// counted as failed send. Flushes the serial every N bytes if configured with `with_flush_every`.
+    pub fn send_io(&mut self) -> Result<(), StreamError<Serial::Error>> {
+        let Some(byte_to_send) = self.byte_to_send() else {
+            return Ok(());
+        };
+
+        self.write_all_io(&[byte_to_send])
+    }
+
+    /// Flushes serial port, blocks until all data is sent.
+    ///
+    /// # Warning
+    /// The error happened here will not affect tx_state
+    pub fn flush_io(&mut self) -> Result<(), Serial::Error> {
+        self.serial.flush()
+    }
+
+    /// Writes all `bytes`, bytes not accepted by serial are lost as the loopback holds only one byte
+    fn write_all_io(&mut self, bytes: &[u8]) -> Result<(), StreamError<Serial::Error>> {
+        self.state = State::Receiving;
+
+        let mut sent = 0;
+        while sent < bytes.len() {
+            let error = match self.serial.write(&bytes[sent..]) {
+                Ok(0) => StreamError::Closed,
+                Ok(written) => {
+                    sent += written;
+                    self.tx_stats.add_successful(written);
+                    self.flush_cadence.on_written(written);
+                    continue;
+                }
+                Err(e) => StreamError::Serial(e),
+            };
+
+            self.tx_stats.add_failed(bytes.len() - sent);
+            return Err(error);
+        }
+
+        if self.flush_cadence.is_due() {
+            self.serial.flush().map_err(StreamError::Serial)?;
+            self.flush_cadence.on_flushed();
+        }
+        Ok(())
+    }
+}
+
+impl<Serial, TxStats, RxStats> Loopback<Serial, TxStats, RxStats>
+where
+    Serial: Write + Read,
+    TxStats: Statistics,
+    RxStats: Statistics,
+{
+    /// Receives bytes from the serial port and sends them back. Blocks until any byte is received and all received
+    /// bytes are accepted by serial.
+    ///
+    /// Reads and writes slices of up to 64 bytes, the loopback holds only one byte, so the received bytes are sent
+    /// back within the call. The byte left to send by `new_transmitting` or `recv_nb` is sent first.
+    ///
+    /// Returns `StreamError::Closed` if the serial reached end of file or accepted no bytes, it's counted as failed
+    /// receive or failed send of every byte not sent back.
+    pub fn recv_io(&mut self) -> Result<(), StreamError<Serial::Error>> {
+        self.send_io()?;
+
+        let mut bytes = [0; BUFFER_SIZE];
+        let read = match self.serial.read(&mut bytes) {
+            Ok(0) => {
+                self.rx_stats.add_failed(1);
+                return Err(StreamError::Closed);
+            }
+            Ok(read) => read,
+            Err(e) => {
+                self.rx_stats.add_failed(1);
+                return Err(StreamError::Serial(e));
+            }
+        };
+
+        self.rx_stats.add_successful(read);
+        for byte in &mut bytes[..read] {
+            *byte = self.transform.apply(*byte);
+        }
+
+        self.write_all_io(&bytes[..read])
+    }
+
+    /// Receives bytes and sends them back, see `recv_io`.
+    pub fn loop_io(&mut self) -> Result<(), StreamError<Serial::Error>> {
+        self.recv_io()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::counting::Counting;
+    use crate::paired_serial::PairedSerial;
+    use crate::statistics::CountingStatistics;
+    use crate::stream_error::StreamError;
+
+    use super::Loopback;
+
+    #[test]
+    fn loop_io_echo() {
+        let paired = PairedSerial::<16>::new();
+        let (counting_serial, loopback_serial) = paired.split();
+
+        let mut counting = Counting::<_, u16>::new(
+            counting_serial,
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+        let mut loopback = Loopback::new(
+            loopback_serial,
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+
+        // Loopback receives and sends back a burst of two packets at once
+        for _ in 0..50 {
+            for _ in 0..8 {
+                counting.send_nb().unwrap();
+            }
+            loopback.loop_io().unwrap();
+            for _ in 0..8 {
+                counting.recv_nb().unwrap();
+            }
+        }
+
+        assert_eq!(loopback.rx_stats().successful(), 400);
+        assert_eq!(loopback.tx_stats().successful(), 400);
+        assert_eq!(counting.rx_stats().successful(), 400);
+        assert_eq!(counting.loss_stats().failed(), 0);
+        assert_eq!(counting.loss_stats().successful(), 100);
+    }
+
+    #[test]
+    fn transmitting_byte_sent_first() {
+        let paired = PairedSerial::<16>::new();
+        let (mut peer, serial) = paired.split();
+        let mut loopback = Loopback::new_transmitting(
+            serial,
+            0x42,
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+
+        embedded_io::Write::write(&mut peer, &[1, 2, 3]).unwrap();
+        loopback.loop_io().unwrap();
+
+        let mut echo = [0; 8];
+        assert_eq!(embedded_io::Read::read(&mut peer, &mut echo), Ok(4));
+        assert_eq!(echo[..4], [0x42, 1, 2, 3]);
+        assert_eq!(loopback.tx_stats().successful(), 4);
+        assert_eq!(loopback.rx_stats().successful(), 3);
+    }
+
+    #[test]
+    fn closed_serial() {
+        let paired = PairedSerial::<16>::new();
+        let (_peer, serial) = paired.split();
+        let mut loopback = Loopback::new(
+            serial,
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+
+        assert!(matches!(loopback.loop_io(), Err(StreamError::Closed)));
+        assert_eq!(loopback.rx_stats().failed(), 1);
+    }
+}
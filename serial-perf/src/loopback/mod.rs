@@ -2,10 +2,24 @@
 //! Loopback is a simple utility that send back bytes it's received
 //!
 
+use crate::flush_cadence::FlushCadence;
 use crate::statistics::{CountingStatistics, Statistics};
 
+#[cfg(feature = "async")]
+mod asynch;
+#[cfg(feature = "embedded-io")]
+mod io;
 mod nb;
 
+mod buffered;
+pub use buffered::BufferedLoopback;
+
+mod sink;
+pub use sink::Sink;
+
+mod transform;
+pub use transform::Transform;
+
 enum State {
     Receiving,
     Transfer(u8),
@@ -15,9 +29,12 @@ enum State {
 pub struct Loopback<Serial, TxStats = CountingStatistics, RxStats = CountingStatistics> {
     serial: Serial,
     state: State,
+    transform: Transform,
 
     tx_stats: TxStats,
     rx_stats: RxStats,
+
+    flush_cadence: FlushCadence,
 }
 
 impl<Serial, TxStats, RxStats> Loopback<Serial, TxStats, RxStats>
@@ -33,11 +50,43 @@ where
         Self {
             serial,
             state: State::Receiving,
+            transform: Transform::Echo,
             tx_stats,
             rx_stats,
+            flush_cadence: FlushCadence::default(),
         }
     }
 
+    /// Create a new loopback instance that sends `byte` first, before receiving anything.
+    ///
+    /// Lets this side initiate the exchange, e.g. ping-pong between two loopbacks, instead of both waiting to receive.
+    /// The `byte` is sent with the transformation applied, as any received byte.
+    pub fn new_transmitting(
+        serial: Serial,
+        byte: u8,
+        tx_stats: TxStats,
+        rx_stats: RxStats,
+    ) -> Self {
+        let mut out = Self::new(serial, tx_stats, rx_stats);
+        out.state = State::Transfer(byte);
+        out
+    }
+
+    /// Sets the transformation applied to bytes before sending them back
+    pub fn with_transform(mut self, transform: Transform) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    /// Flushes the serial automatically every `bytes` bytes sent, 0 disables it.
+    ///
+    /// Applied by `send_nb`, `send_io` and `send_async`, if the flush would block, the next `send_nb` completes it
+    /// before sending.
+    pub fn with_flush_every(mut self, bytes: usize) -> Self {
+        self.flush_cadence = FlushCadence::new(bytes);
+        self
+    }
+
     pub fn tx_stats(&self) -> &TxStats {
         &self.tx_stats
     }
@@ -51,6 +100,26 @@ where
         self.rx_stats.reset();
     }
 
+    /// Returns reference to the underlying serial
+    pub fn serial(&self) -> &Serial {
+        &self.serial
+    }
+
+    /// Returns mutable reference to the underlying serial
+    pub fn serial_mut(&mut self) -> &mut Serial {
+        &mut self.serial
+    }
+
+    /// Destroys the wrapper returning the underlying serial
+    pub fn into_inner(self) -> Serial {
+        self.serial
+    }
+
+    /// Converts the loopback into a `Sink`, dropping TX statistics and any byte pending to be sent back
+    pub fn into_sink(self) -> Sink<Serial, RxStats> {
+        Sink::new(self.serial, self.rx_stats)
+    }
+
     fn on_byte_received(&mut self, byte: u8) {
         match self.state {
             State::Receiving => (),
@@ -72,7 +141,43 @@ where
     fn byte_to_send(&mut self) -> Option<u8> {
         match self.state {
             State::Receiving => None,
-            State::Transfer(byte) => Some(byte),
+            State::Transfer(byte) => Some(self.transform.apply(byte)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::paired_serial::PairedSerial;
+    use crate::statistics::CountingStatistics;
+
+    use super::Loopback;
+
+    #[test]
+    fn ping_pong() {
+        let paired = PairedSerial::<4>::new();
+        let (a, b) = paired.split();
+
+        let mut ping = Loopback::new_transmitting(
+            a,
+            0x42,
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+        let mut pong = Loopback::new(
+            b,
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+
+        // Ping sends, pong receives and sends back, ping receives
+        for _ in 0..3 {
+            ping.loop_nb().ok();
+            pong.loop_nb().ok();
         }
+
+        assert_eq!(ping.tx_stats().successful(), 1);
+        assert_eq!(pong.tx_stats().successful(), 1);
+        assert_eq!(ping.rx_stats().successful(), 1);
     }
 }
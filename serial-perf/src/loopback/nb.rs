@@ -1,6 +1,9 @@
+use core::time::Duration;
+
 use embedded_hal_nb::nb::{Error, Result};
 use embedded_hal_nb::serial::{Read, Write};
 
+use crate::clock::{Clock, ClockExt};
 use crate::statistics::Statistics;
 
 use super::{Loopback, State};
@@ -25,6 +28,27 @@ where
 
         Ok(())
     }
+
+    /// Receives and discards stale bytes for `duration`, then resets the statistics.
+    ///
+    /// Returns early on serial error.
+    pub fn warm_up<Clk: Clock>(
+        &mut self,
+        clock: &Clk,
+        duration: Duration,
+    ) -> core::result::Result<(), Serial::Error> {
+        let start = clock.now();
+        while clock.elapsed_since(start) < duration {
+            match self.serial.read() {
+                Ok(_) | Err(Error::WouldBlock) => (),
+                Err(Error::Other(e)) => return Err(e),
+            }
+        }
+
+        self.state = State::Receiving;
+        self.reset_stats();
+        Ok(())
+    }
 }
 
 impl<Serial, TxStats, RxStats> Loopback<Serial, TxStats, RxStats>
@@ -34,15 +58,32 @@ where
     RxStats: Statistics,
 {
     /// Sends next byte using non blocking API
+    ///
+    /// Flushes the serial every N bytes if configured with `with_flush_every`.
     pub fn send_nb(&mut self) -> Result<(), Serial::Error> {
+        // Complete the flush blocked after the previous byte
+        if let Err(e) = self.flush_cadence.flush_if_due(&mut self.serial) {
+            if let Error::WouldBlock = e {
+                self.tx_stats.add_blocked(1);
+            }
+            return Err(e);
+        }
+
         let byte_to_send = self.byte_to_send().ok_or(Error::WouldBlock)?;
 
         match self.serial.write(byte_to_send) {
             Ok(_) => {
                 self.on_byte_sent();
-                Ok(())
+                self.flush_cadence.on_written(1);
+                match self.flush_cadence.flush_if_due(&mut self.serial) {
+                    Ok(()) | Err(Error::WouldBlock) => Ok(()),
+                    Err(e) => Err(e),
+                }
+            }
+            Err(Error::WouldBlock) => {
+                self.tx_stats.add_blocked(1);
+                Err(Error::WouldBlock)
             }
-            Err(Error::WouldBlock) => Err(Error::WouldBlock),
             Err(e) => {
                 self.tx_stats.add_failed(1);
                 Err(e)
@@ -72,3 +113,29 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::statistics::CountingStatistics;
+    use crate::test_serial::ScriptedSerial;
+
+    use super::Loopback;
+
+    #[test]
+    fn flush_every() {
+        let mut loopback = Loopback::new(
+            ScriptedSerial::new().reading(0x42),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        )
+        .with_flush_every(4);
+
+        // Receive and send back 11 bytes
+        for _ in 0..22 {
+            loopback.loop_nb().unwrap();
+        }
+
+        assert_eq!(loopback.tx_stats().successful(), 11);
+        assert_eq!(loopback.serial().flushes(), 2);
+    }
+}
@@ -0,0 +1,156 @@
+use crate::statistics::{CountingStatistics, Statistics};
+#[cfg(any(feature = "async", feature = "embedded-io"))]
+use crate::stream_error::StreamError;
+
+/// A wrapper around serial that only receives data, measuring it with `RxStats`
+///
+/// Unlike `Loopback` nothing is ever sent back, so the serial is never written to.
+pub struct Sink<Serial, RxStats = CountingStatistics> {
+    serial: Serial,
+    rx_stats: RxStats,
+}
+
+impl<Serial, RxStats> Sink<Serial, RxStats>
+where
+    RxStats: Statistics,
+{
+    /// Create a new sink instance using provided serial and statistics.
+    ///
+    /// # Note
+    /// The provided statistics will not reset upon creation, so you may want to call `reset_stats` after creation if desired.
+    pub fn new(serial: Serial, rx_stats: RxStats) -> Self {
+        Self { serial, rx_stats }
+    }
+
+    pub fn rx_stats(&self) -> &RxStats {
+        &self.rx_stats
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.rx_stats.reset();
+    }
+
+    /// Returns reference to the underlying serial
+    pub fn serial(&self) -> &Serial {
+        &self.serial
+    }
+
+    /// Returns mutable reference to the underlying serial
+    pub fn serial_mut(&mut self) -> &mut Serial {
+        &mut self.serial
+    }
+
+    /// Destroys the wrapper returning the underlying serial
+    pub fn into_inner(self) -> Serial {
+        self.serial
+    }
+}
+
+impl<Serial, RxStats> Sink<Serial, RxStats>
+where
+    Serial: embedded_hal_nb::serial::Read,
+    RxStats: Statistics,
+{
+    /// Receives and discards a byte using non blocking API
+    pub fn recv_nb(&mut self) -> embedded_hal_nb::nb::Result<(), Serial::Error> {
+        match self.serial.read() {
+            Ok(_) => {
+                self.rx_stats.add_successful(1);
+                Ok(())
+            }
+            Err(embedded_hal_nb::nb::Error::WouldBlock) => {
+                Err(embedded_hal_nb::nb::Error::WouldBlock)
+            }
+            Err(e) => {
+                self.rx_stats.add_failed(1);
+                Err(e)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "embedded-io")]
+impl<Serial, RxStats> Sink<Serial, RxStats>
+where
+    Serial: embedded_io::Read,
+    RxStats: Statistics,
+{
+    /// Receives and discards bytes from the serial port. Blocks until bytes are received.
+    ///
+    /// Returns `StreamError::Closed` if the serial reached end of file, it's counted as failed receive.
+    pub fn recv_io(&mut self) -> Result<(), StreamError<Serial::Error>> {
+        let mut buf = [0; 16];
+        match self.serial.read(&mut buf) {
+            Ok(0) => {
+                self.rx_stats.add_failed(1);
+                Err(StreamError::Closed)
+            }
+            Ok(read) => {
+                self.rx_stats.add_successful(read);
+                Ok(())
+            }
+            Err(e) => {
+                self.rx_stats.add_failed(1);
+                Err(StreamError::Serial(e))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<Serial, RxStats> Sink<Serial, RxStats>
+where
+    Serial: embedded_io_async::Read,
+    RxStats: Statistics,
+{
+    /// Receives and discards bytes from the serial port. Waits until bytes are received.
+    ///
+    /// Returns `StreamError::Closed` if the serial reached end of file, it's counted as failed receive.
+    pub async fn recv_async(&mut self) -> Result<(), StreamError<Serial::Error>> {
+        let mut buf = [0; 16];
+        match self.serial.read(&mut buf).await {
+            Ok(0) => {
+                self.rx_stats.add_failed(1);
+                Err(StreamError::Closed)
+            }
+            Ok(read) => {
+                self.rx_stats.add_successful(read);
+                Ok(())
+            }
+            Err(e) => {
+                self.rx_stats.add_failed(1);
+                Err(StreamError::Serial(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_hal_nb::serial::Write;
+
+    use crate::loopback::Loopback;
+    use crate::paired_serial::PairedSerial;
+    use crate::statistics::{CountingStatistics, DummyStatistics};
+
+    #[test]
+    fn nothing_sent_back() {
+        let paired = PairedSerial::<8>::new();
+        let (mut remote, local) = paired.split();
+
+        let loopback = Loopback::new(local, DummyStatistics, CountingStatistics::default());
+        let mut sink = loopback.into_sink();
+
+        for byte in 0..5 {
+            remote.write(byte).unwrap();
+        }
+        while sink.recv_nb().is_ok() {}
+
+        assert_eq!(sink.rx_stats().successful(), 5);
+        assert_eq!(sink.serial().in_flight(), 0);
+        assert_eq!(remote.in_flight(), 0);
+
+        sink.reset_stats();
+        assert_eq!(sink.rx_stats().successful(), 0);
+    }
+}
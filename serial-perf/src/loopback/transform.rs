@@ -0,0 +1,36 @@
+/// Transformation applied by loopback to bytes before sending them back
+///
+/// Lets the other side distinguish genuine loopback from a stuck driver that returns bytes as is.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Transform {
+    /// Send bytes back unchanged
+    #[default]
+    Echo,
+    /// XOR bytes with the constant
+    Xor(u8),
+    /// Add the constant to bytes with wrapping, `Add(1)` increments bytes
+    Add(u8),
+}
+
+impl Transform {
+    /// Returns the transformed byte
+    pub fn apply(&self, byte: u8) -> u8 {
+        match self {
+            Self::Echo => byte,
+            Self::Xor(mask) => byte ^ mask,
+            Self::Add(value) => byte.wrapping_add(*value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Transform;
+
+    #[test]
+    fn apply() {
+        assert_eq!(Transform::Echo.apply(0x5A), 0x5A);
+        assert_eq!(Transform::Xor(0xFF).apply(0x5A), 0xA5);
+        assert_eq!(Transform::Add(1).apply(0xFF), 0x00);
+    }
+}
@@ -0,0 +1,266 @@
+//!
+//! In-memory pair of connected serials for testing without a real port
+//!
+
+use core::cell::RefCell;
+use core::convert::Infallible;
+#[cfg(feature = "async")]
+use core::task::Poll;
+
+use embedded_hal_nb::nb;
+use embedded_hal_nb::serial::{ErrorType, Read, Write};
+#[cfg(feature = "embedded-io")]
+use embedded_io as io;
+#[cfg(all(feature = "async", not(feature = "embedded-io")))]
+use embedded_io_async as io;
+
+/// Two queues of bytes connecting a pair of serial endpoints, each queue holds up to `N` bytes
+///
+/// Bytes written to one endpoint are read from the other one. Writing to a full queue and reading
+/// from an empty one return `WouldBlock`, like a real serial does.
+#[derive(Debug, Default)]
+pub struct PairedSerial<const N: usize> {
+    a_to_b: RefCell<heapless::Deque<u8, N>>,
+    b_to_a: RefCell<heapless::Deque<u8, N>>,
+}
+
+impl<const N: usize> PairedSerial<N> {
+    pub fn new() -> Self {
+        Self {
+            a_to_b: RefCell::new(heapless::Deque::new()),
+            b_to_a: RefCell::new(heapless::Deque::new()),
+        }
+    }
+
+    /// Returns both connected endpoints
+    pub fn split(&self) -> (PairedSerialEndpoint<'_, N>, PairedSerialEndpoint<'_, N>) {
+        let a = PairedSerialEndpoint {
+            tx: &self.a_to_b,
+            rx: &self.b_to_a,
+        };
+        let b = PairedSerialEndpoint {
+            tx: &self.b_to_a,
+            rx: &self.a_to_b,
+        };
+
+        (a, b)
+    }
+
+    /// Returns an endpoint receiving everything written to it, like a serial with TX wired to RX
+    pub fn echo(&self) -> PairedSerialEndpoint<'_, N> {
+        PairedSerialEndpoint {
+            tx: &self.a_to_b,
+            rx: &self.a_to_b,
+        }
+    }
+}
+
+/// One endpoint of `PairedSerial`
+#[derive(Debug)]
+pub struct PairedSerialEndpoint<'a, const N: usize> {
+    tx: &'a RefCell<heapless::Deque<u8, N>>,
+    rx: &'a RefCell<heapless::Deque<u8, N>>,
+}
+
+impl<const N: usize> PairedSerialEndpoint<'_, N> {
+    /// Returns amount of bytes written to this endpoint and not read by the other one yet
+    pub fn in_flight(&self) -> usize {
+        self.tx.borrow().len()
+    }
+
+    /// Drops a byte written to this endpoint before the other one reads it, simulating a loss on the line.
+    ///
+    /// Returns the dropped byte, `None` if nothing is in flight.
+    pub fn drop_in_flight(&self) -> Option<u8> {
+        self.tx.borrow_mut().pop_back()
+    }
+}
+
+impl<const N: usize> ErrorType for PairedSerialEndpoint<'_, N> {
+    type Error = Infallible;
+}
+
+impl<const N: usize> Read for PairedSerialEndpoint<'_, N> {
+    fn read(&mut self) -> nb::Result<u8, Self::Error> {
+        self.rx
+            .borrow_mut()
+            .pop_front()
+            .ok_or(nb::Error::WouldBlock)
+    }
+}
+
+impl<const N: usize> Write for PairedSerialEndpoint<'_, N> {
+    fn write(&mut self, word: u8) -> nb::Result<(), Self::Error> {
+        self.tx
+            .borrow_mut()
+            .push_back(word)
+            .map_err(|_| nb::Error::WouldBlock)
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[cfg(any(feature = "embedded-io", feature = "async"))]
+impl<const N: usize> io::ErrorType for PairedSerialEndpoint<'_, N> {
+    type Error = Infallible;
+}
+
+#[cfg(any(feature = "embedded-io", feature = "async"))]
+impl<const N: usize> io::ReadReady for PairedSerialEndpoint<'_, N> {
+    fn read_ready(&mut self) -> Result<bool, Self::Error> {
+        Ok(!self.rx.borrow().is_empty())
+    }
+}
+
+/// Reads available bytes. Returns 0 from an empty queue instead of blocking, the other endpoint
+/// can't send anything while this one is blocked in single threaded test.
+#[cfg(feature = "embedded-io")]
+impl<const N: usize> embedded_io::Read for PairedSerialEndpoint<'_, N> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        let mut rx = self.rx.borrow_mut();
+        let mut read = 0;
+        for slot in buf.iter_mut() {
+            let Some(byte) = rx.pop_front() else {
+                break;
+            };
+            *slot = byte;
+            read += 1;
+        }
+
+        Ok(read)
+    }
+}
+
+/// Writes bytes up to free place in queue. Returns 0 for a full queue instead of blocking, like `Read`.
+#[cfg(feature = "embedded-io")]
+impl<const N: usize> embedded_io::Write for PairedSerialEndpoint<'_, N> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        let mut tx = self.tx.borrow_mut();
+        let mut written = 0;
+        for byte in buf {
+            if tx.push_back(*byte).is_err() {
+                break;
+            }
+            written += 1;
+        }
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Waits for at least one byte, then reads available bytes
+#[cfg(feature = "async")]
+impl<const N: usize> embedded_io_async::Read for PairedSerialEndpoint<'_, N> {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let first = core::future::poll_fn(|cx| match self.rx.borrow_mut().pop_front() {
+            Some(byte) => Poll::Ready(byte),
+            None => {
+                // The other endpoint doesn't wake anyone, so ask to be polled again
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        })
+        .await;
+        buf[0] = first;
+
+        let mut rx = self.rx.borrow_mut();
+        let mut read = 1;
+        for slot in buf[1..].iter_mut() {
+            let Some(byte) = rx.pop_front() else {
+                break;
+            };
+            *slot = byte;
+            read += 1;
+        }
+
+        Ok(read)
+    }
+}
+
+/// Waits for free place in queue, then writes bytes up to free place
+#[cfg(feature = "async")]
+impl<const N: usize> embedded_io_async::Write for PairedSerialEndpoint<'_, N> {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        core::future::poll_fn(|cx| {
+            if self.tx.borrow().is_full() {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            } else {
+                Poll::Ready(())
+            }
+        })
+        .await;
+
+        let mut tx = self.tx.borrow_mut();
+        let mut written = 0;
+        for byte in buf {
+            if tx.push_back(*byte).is_err() {
+                break;
+            }
+            written += 1;
+        }
+
+        Ok(written)
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::counting::Counting;
+    use crate::statistics::CountingStatistics;
+
+    use super::PairedSerial;
+
+    #[test]
+    fn client_server() {
+        let paired = PairedSerial::<16>::new();
+        let (client_serial, server_serial) = paired.split();
+
+        let new_counting = |serial| {
+            Counting::<_, u16>::new(
+                serial,
+                CountingStatistics::default(),
+                CountingStatistics::default(),
+                CountingStatistics::default(),
+            )
+        };
+        let mut client = new_counting(client_serial);
+        let mut server = new_counting(server_serial);
+
+        for i in 0..400 {
+            client.loop_nb().ok();
+
+            // Lose a byte on the line from client to server
+            if i == 200 {
+                assert!(client.serial().drop_in_flight().is_some());
+            }
+
+            server.loop_nb().ok();
+        }
+        client.finish_nb().ok();
+        server.finish_nb().ok();
+
+        assert_eq!(client.loss_stats().failed(), 0);
+        assert_eq!(client.loss_stats().successful(), 100);
+        assert_eq!(server.loss_stats().failed(), 1);
+        assert_eq!(server.loss_stats().successful(), 99);
+    }
+}
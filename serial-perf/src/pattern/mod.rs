@@ -0,0 +1,92 @@
+//!
+//! Pattern echo test that sends pseudorandom bytes to a remote `Loopback` and verifies the echo bit by bit.
+//!
+
+use crate::statistics::{CountingStatistics, Statistics};
+
+mod nb;
+mod prbs;
+pub use prbs::{Prbs, PrbsGenerator};
+
+/// Bit error rate test sending PRBS to a remote `Loopback` and comparing the echo with the same sequence.
+///
+/// TX statistics count bytes sent. RX statistics count bits received: matching bits are successful,
+/// mismatched bits and bits of bytes failed to read are failed, so `CountingStatistics::failure_ratio`
+/// is the bit error rate.
+///
+/// # Warning
+/// The remote `Loopback` must echo bytes unchanged. A lost or inserted byte shifts the sequence, so
+/// all bytes after it are counted as errors, call `reset` to start over.
+pub struct PatternEcho<Serial, TxStats = CountingStatistics, RxStats = CountingStatistics> {
+    serial: Serial,
+    tx_generator: PrbsGenerator,
+    rx_generator: PrbsGenerator,
+
+    tx_stats: TxStats,
+    rx_stats: RxStats,
+}
+
+impl<Serial, TxStats, RxStats> PatternEcho<Serial, TxStats, RxStats>
+where
+    TxStats: Statistics,
+    RxStats: Statistics,
+{
+    pub fn new(serial: Serial, prbs: Prbs, tx_stats: TxStats, rx_stats: RxStats) -> Self {
+        Self {
+            serial,
+            tx_generator: PrbsGenerator::new(prbs),
+            rx_generator: PrbsGenerator::new(prbs),
+            tx_stats,
+            rx_stats,
+        }
+    }
+
+    /// Restarts the sequence on both TX and RX sides and resets statistics
+    pub fn reset(&mut self) {
+        self.tx_generator.reset();
+        self.rx_generator.reset();
+        self.tx_stats.reset();
+        self.rx_stats.reset();
+    }
+
+    pub fn tx_stats(&self) -> &TxStats {
+        &self.tx_stats
+    }
+
+    pub fn rx_stats(&self) -> &RxStats {
+        &self.rx_stats
+    }
+
+    /// Returns reference to the underlying serial
+    pub fn serial(&self) -> &Serial {
+        &self.serial
+    }
+
+    /// Returns mutable reference to the underlying serial
+    pub fn serial_mut(&mut self) -> &mut Serial {
+        &mut self.serial
+    }
+
+    /// Destroys the test returning the underlying serial
+    pub fn into_inner(self) -> Serial {
+        self.serial
+    }
+
+    fn on_byte_received(&mut self, byte: u8) {
+        let expected = self.rx_generator.next_byte();
+        let errors = (byte ^ expected).count_ones() as usize;
+
+        self.rx_stats.add_successful(8 - errors);
+        self.rx_stats.add_failed(errors);
+    }
+
+    fn on_byte_sent(&mut self) {
+        self.tx_generator.next_byte();
+        self.tx_stats.add_successful(1);
+    }
+
+    fn byte_to_send(&self) -> u8 {
+        // Peek the next byte, the generator advances only when it is sent
+        self.tx_generator.clone().next_byte()
+    }
+}
@@ -0,0 +1,145 @@
+use embedded_hal_nb::nb::{block, Error, Result};
+use embedded_hal_nb::serial::{Read, Write};
+
+use crate::statistics::Statistics;
+
+use super::PatternEcho;
+
+impl<Serial, TxStats, RxStats> PatternEcho<Serial, TxStats, RxStats>
+where
+    Serial: Read,
+    TxStats: Statistics,
+    RxStats: Statistics,
+{
+    /// Receive byte from the serial port and verify it. Non-blocking.
+    pub fn recv_nb(&mut self) -> Result<(), Serial::Error> {
+        let byte_read = match self.serial.read() {
+            Ok(b) => b,
+            Err(Error::WouldBlock) => return Err(Error::WouldBlock),
+            Err(e) => {
+                // The byte is skipped in the sequence, all its bits are lost
+                self.rx_generator.next_byte();
+                self.rx_stats.add_failed(8);
+                return Err(e);
+            }
+        };
+
+        self.on_byte_received(byte_read);
+
+        Ok(())
+    }
+
+    /// Receive byte from the serial port and verify it. Blocks until byte is received.
+    pub fn recv(&mut self) -> core::result::Result<(), Serial::Error> {
+        block!(self.recv_nb())
+    }
+}
+
+impl<Serial, TxStats, RxStats> PatternEcho<Serial, TxStats, RxStats>
+where
+    Serial: Write,
+    TxStats: Statistics,
+    RxStats: Statistics,
+{
+    /// Sends next byte using non blocking API
+    pub fn send_nb(&mut self) -> Result<(), Serial::Error> {
+        match self.serial.write(self.byte_to_send()) {
+            Ok(_) => {
+                self.on_byte_sent();
+                Ok(())
+            }
+            Err(Error::WouldBlock) => {
+                self.tx_stats.add_blocked(1);
+                Err(Error::WouldBlock)
+            }
+            Err(e) => {
+                self.tx_stats.add_failed(1);
+                Err(e)
+            }
+        }
+    }
+
+    /// Sends next byte. Blocks until byte is accepted by serial.
+    pub fn send(&mut self) -> core::result::Result<(), Serial::Error> {
+        block!(self.send_nb())
+    }
+}
+
+impl<Serial, TxStats, RxStats> PatternEcho<Serial, TxStats, RxStats>
+where
+    Serial: Write + Read,
+    TxStats: Statistics,
+    RxStats: Statistics,
+{
+    pub fn loop_nb(&mut self) -> Result<(), Serial::Error> {
+        let (recv_res, send_res) = (self.recv_nb(), self.send_nb());
+
+        match (recv_res, send_res) {
+            // All good, both sides sent and received something
+            (Ok(_), Ok(_)) => Ok(()),
+            // Both is blocked
+            (Err(Error::WouldBlock), Err(Error::WouldBlock)) => Err(Error::WouldBlock),
+            // One of is blocked so client can call again to try to send or receive something
+            (Err(Error::WouldBlock), _) | (_, Err(Error::WouldBlock)) => Ok(()),
+            // One of the sides has an error
+            (Err(e), _) | (_, Err(e)) => Err(e),
+        }
+    }
+
+    /// Runs `loop_nb` once, blocking while both sending and receiving are blocked.
+    pub fn run_loop(&mut self) -> core::result::Result<(), Serial::Error> {
+        block!(self.loop_nb())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::faulty_serial::{Fault, FaultySerial};
+    use crate::paired_serial::PairedSerial;
+    use crate::pattern::{PatternEcho, Prbs};
+    use crate::statistics::{BlockedStatistics, CountingStatistics};
+    use crate::test_serial::ScriptedSerial;
+
+    #[test]
+    fn bit_errors_counted() {
+        // Flip a bit of every 10th byte sent back
+        let faults: [Fault; 10] = core::array::from_fn(|i| Fault::FlipBit {
+            index: i * 10 + 9,
+            bit: 4,
+        });
+        let paired = PairedSerial::<4>::new();
+        let serial = FaultySerial::new(paired.echo(), &faults);
+        let mut test = PatternEcho::new(
+            serial,
+            Prbs::Prbs15,
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+
+        for _ in 0..100 {
+            test.send().unwrap();
+            test.recv().unwrap();
+        }
+
+        assert_eq!(test.tx_stats().successful(), 100);
+        assert_eq!(test.rx_stats().total(), 100 * 8);
+        assert_eq!(test.rx_stats().failed(), 10);
+    }
+
+    #[test]
+    fn blocked_writes_counted() {
+        let mut test = PatternEcho::new(
+            ScriptedSerial::new().blocking_writes(2),
+            Prbs::Prbs7,
+            BlockedStatistics::new(CountingStatistics::default()),
+            CountingStatistics::default(),
+        );
+
+        for _ in 0..5 {
+            test.send().unwrap();
+        }
+
+        assert_eq!(test.tx_stats().inner().successful(), 5);
+        assert_eq!(test.tx_stats().blocked(), 5);
+    }
+}
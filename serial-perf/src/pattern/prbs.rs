@@ -0,0 +1,92 @@
+/// Pseudorandom binary sequence generated by LFSR
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Prbs {
+    /// PRBS-7, polynomial x^7 + x^6 + 1, repeats every 127 bits
+    #[default]
+    Prbs7,
+    /// PRBS-15, polynomial x^15 + x^14 + 1, repeats every 32767 bits
+    Prbs15,
+}
+
+impl Prbs {
+    /// Returns degree of the polynomial, the size of LFSR in bits
+    fn degree(&self) -> u32 {
+        match self {
+            Self::Prbs7 => 7,
+            Self::Prbs15 => 15,
+        }
+    }
+}
+
+/// Generator of PRBS bytes, the most significant bit first
+#[derive(Debug, Clone)]
+pub struct PrbsGenerator {
+    prbs: Prbs,
+    state: u16,
+}
+
+impl PrbsGenerator {
+    /// Creates generator with LFSR seeded with ones
+    pub fn new(prbs: Prbs) -> Self {
+        let mut out = Self { prbs, state: 0 };
+        out.reset();
+        out
+    }
+
+    /// Restarts the sequence from the beginning
+    pub fn reset(&mut self) {
+        self.state = (1 << self.prbs.degree()) - 1;
+    }
+
+    /// Returns the next 8 bits of the sequence
+    pub fn next_byte(&mut self) -> u8 {
+        let degree = self.prbs.degree();
+        let mask = (1 << degree) - 1;
+
+        let mut out = 0;
+        for _ in 0..8 {
+            // Taps are the two highest bits for both polynomials
+            let bit = ((self.state >> (degree - 1)) ^ (self.state >> (degree - 2))) & 1;
+            self.state = ((self.state << 1) | bit) & mask;
+            out = (out << 1) | bit as u8;
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Prbs, PrbsGenerator};
+
+    fn period(prbs: Prbs) -> usize {
+        let mut generator = PrbsGenerator::new(prbs);
+        let start = generator.state;
+
+        let mut bits = 0;
+        loop {
+            generator.next_byte();
+            bits += 8;
+            if generator.state == start {
+                return bits;
+            }
+        }
+    }
+
+    #[test]
+    fn maximal_length() {
+        // The state is checked every byte, so the first repeat is at the least common multiple of period and 8
+        assert_eq!(period(Prbs::Prbs7), 127 * 8);
+        assert_eq!(period(Prbs::Prbs15), 32767 * 8);
+    }
+
+    #[test]
+    fn reset_restarts_sequence() {
+        let mut generator = PrbsGenerator::new(Prbs::Prbs15);
+        let first = generator.next_byte();
+        generator.next_byte();
+
+        generator.reset();
+        assert_eq!(generator.next_byte(), first);
+    }
+}
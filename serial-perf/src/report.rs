@@ -0,0 +1,158 @@
+//!
+//! Report of the test results with console formatting
+//!
+
+use core::fmt::{Display, Formatter, Result};
+use core::time::Duration;
+
+use crate::byte_rate::rate::ByteRate;
+use crate::counting::{Counter, Counting};
+use crate::statistics::CountingStatistics;
+
+/// Results of the counting test, printed in human readable format by `Display`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Report {
+    /// Bytes sent
+    pub tx: CountingStatistics,
+    /// Bytes received
+    pub rx: CountingStatistics,
+    /// Packets received and lost
+    pub loss: CountingStatistics,
+    /// Amount of packets rejected by checksum
+    pub crc_errors: usize,
+    /// Duration of the test, if known. Required for rates
+    pub duration: Option<Duration>,
+}
+
+impl Report {
+    /// Sets duration of the test used to calculate rates
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    /// Returns average rate of bytes sent, if duration is known
+    pub fn tx_rate(&self) -> Option<ByteRate> {
+        Some(ByteRate::new(self.tx.successful(), self.duration?))
+    }
+
+    /// Returns average rate of bytes received, if duration is known
+    pub fn rx_rate(&self) -> Option<ByteRate> {
+        Some(ByteRate::new(self.rx.successful(), self.duration?))
+    }
+}
+
+impl<Serial, Number, const MAX_PACKET_SIZE: usize>
+    From<
+        &Counting<
+            Serial,
+            Number,
+            CountingStatistics,
+            CountingStatistics,
+            CountingStatistics,
+            MAX_PACKET_SIZE,
+        >,
+    > for Report
+where
+    Number: Counter,
+{
+    fn from(
+        counting: &Counting<
+            Serial,
+            Number,
+            CountingStatistics,
+            CountingStatistics,
+            CountingStatistics,
+            MAX_PACKET_SIZE,
+        >,
+    ) -> Self {
+        Self {
+            tx: counting.tx_stats().clone(),
+            rx: counting.rx_stats().clone(),
+            loss: counting.loss_stats().clone(),
+            crc_errors: counting.crc_errors(),
+            duration: None,
+        }
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(
+            f,
+            "TX(bytes): sent: {}, errors: {}",
+            self.tx.successful(),
+            self.tx.failed()
+        )?;
+        if let Some(rate) = self.tx_rate().and_then(|r| r.bytes_per_second()) {
+            write!(f, ", rate: {} B/s", rate)?;
+        }
+        writeln!(f)?;
+
+        write!(
+            f,
+            "RX(bytes): total: {}, errors: {}",
+            self.rx.total(),
+            self.rx.failed()
+        )?;
+        if let Some(rate) = self.rx_rate().and_then(|r| r.bytes_per_second()) {
+            write!(f, ", rate: {} B/s", rate)?;
+        }
+        writeln!(f)?;
+
+        if self.loss.total() != 0 {
+            writeln!(
+                f,
+                "RX(packet): loss: {}, total: {}, {:.02}%, crc errors: {}",
+                self.loss.loss_count(),
+                self.loss.total(),
+                self.loss.loss_ratio() * 100.0,
+                self.crc_errors
+            )?;
+        }
+
+        if let Some(duration) = self.duration {
+            writeln!(f, "Duration: {:.03}s", duration.as_secs_f32())?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::fmt::Write;
+    use core::time::Duration;
+
+    use crate::counting::Counting;
+    use crate::statistics::CountingStatistics;
+
+    use super::Report;
+
+    #[test]
+    fn display() {
+        let mut counting = Counting::<_, u8>::new(
+            (),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+
+        let mut buf = [0; 3 * 4];
+        counting.fill_send_slice(&mut buf);
+        counting.recv_slice(&buf[..3]);
+        counting.recv_slice(&buf[3 * 2..]);
+
+        let report = Report::from(&counting).with_duration(Duration::from_secs(2));
+
+        let mut out = heapless::String::<256>::new();
+        write!(out, "{}", report).unwrap();
+        assert_eq!(
+            out,
+            "TX(bytes): sent: 12, errors: 0, rate: 6 B/s\n\
+             RX(bytes): total: 9, errors: 0, rate: 4 B/s\n\
+             RX(packet): loss: 1, total: 4, 25.00%, crc errors: 0\n\
+             Duration: 2.000s\n"
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use crate::byte_rate::{measure::AverageByteRateMeasurer, rate::ByteRate};
 
-use super::Statistics;
+use super::{RateStatistics, Statistics};
 
 /// Statistics that count average byte rate instead counting number of bytes.
 pub struct AvgRateStatistics<'clk, Clk>
@@ -22,12 +22,13 @@ where
         }
     }
 
-    // pub fn total_rate(&self) -> Option<ByteRate> {
-    //     let success_rate = self.successful_rate.byte_rate()?;
-    //     let failed_rate = self.failed_rate.byte_rate()?;
+    /// Returns sum of successful and failed byte rates, if measuring started
+    pub fn total_rate(&self) -> Option<ByteRate> {
+        let success_rate = self.successful_rate.byte_rate()?;
+        let failed_rate = self.failed_rate.byte_rate()?;
 
-    //     Some(success_rate + failed_rate)
-    // }
+        Some(success_rate + failed_rate)
+    }
 
     pub fn success_rate(&self) -> Option<ByteRate> {
         self.successful_rate.byte_rate()
@@ -61,3 +62,12 @@ where
         self.failed_rate.start();
     }
 }
+
+impl<'clk, Clk> RateStatistics for AvgRateStatistics<'clk, Clk>
+where
+    Clk: crate::clock::Clock,
+{
+    fn success_rate(&self) -> Option<ByteRate> {
+        self.successful_rate.byte_rate()
+    }
+}
@@ -0,0 +1,109 @@
+use super::{LossEvent, Statistics};
+
+/// Statistics that counts attempts blocked by the serial in addition to the wrapped statistics
+///
+/// Use it as TX statistics to tell a saturated or flow-controlled link from hard errors counted as failed.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BlockedStatistics<Inner> {
+    inner: Inner,
+    blocked: usize,
+}
+
+impl<Inner> BlockedStatistics<Inner>
+where
+    Inner: Statistics,
+{
+    pub fn new(inner: Inner) -> Self {
+        Self { inner, blocked: 0 }
+    }
+
+    /// Returns the wrapped statistics
+    pub fn inner(&self) -> &Inner {
+        &self.inner
+    }
+
+    /// Returns the number of attempts blocked by the serial
+    pub fn blocked(&self) -> usize {
+        self.blocked
+    }
+}
+
+impl<Inner> Statistics for BlockedStatistics<Inner>
+where
+    Inner: Statistics,
+{
+    fn add_successful(&mut self, count: usize) {
+        self.inner.add_successful(count);
+    }
+
+    fn add_failed(&mut self, count: usize) {
+        self.inner.add_failed(count);
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+        self.blocked = 0;
+    }
+
+    fn add_loss_event(&mut self, event: LossEvent) {
+        self.inner.add_loss_event(event);
+    }
+
+    fn add_blocked(&mut self, count: usize) {
+        self.blocked = self.blocked.saturating_add(count);
+        self.inner.add_blocked(count);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::counting::Counting;
+    use crate::loopback::Loopback;
+    use crate::statistics::{BlockedStatistics, CountingStatistics};
+    use crate::test_serial::ScriptedSerial;
+
+    /// Serial blocking every other byte, starting from the first one. Always has a byte to read
+    fn saturated_serial() -> ScriptedSerial {
+        ScriptedSerial::new().reading(0xAA).blocking_writes(2)
+    }
+
+    #[test]
+    fn counting_blocked() {
+        let mut counting = Counting::<_, u8, _>::new(
+            saturated_serial(),
+            BlockedStatistics::new(CountingStatistics::default()),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        );
+
+        for _ in 0..10 {
+            counting.send().unwrap();
+        }
+
+        assert_eq!(counting.tx_stats().inner().successful(), 10);
+        assert_eq!(counting.tx_stats().inner().failed(), 0);
+        assert_eq!(counting.tx_stats().blocked(), 10);
+    }
+
+    #[test]
+    fn loopback_blocked() {
+        let mut loopback = Loopback::new(
+            saturated_serial(),
+            BlockedStatistics::new(CountingStatistics::default()),
+            CountingStatistics::default(),
+        );
+
+        // Nothing received, so nothing to send: not blocked by the serial
+        assert!(loopback.send_nb().is_err());
+        assert_eq!(loopback.tx_stats().blocked(), 0);
+
+        for _ in 0..5 {
+            loopback.recv_nb().unwrap();
+            embedded_hal_nb::nb::block!(loopback.send_nb()).unwrap();
+        }
+
+        assert_eq!(loopback.tx_stats().inner().successful(), 5);
+        assert_eq!(loopback.tx_stats().blocked(), 5);
+    }
+}
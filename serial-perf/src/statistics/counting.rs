@@ -1,7 +1,9 @@
 use super::Statistics;
 
 /// Counting is a simple statistics for count the number of packets sent/received successfully, failed.
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct CountingStatistics {
     /// Number of packets that were successfully sent/received
     successful: usize,
@@ -25,6 +27,36 @@ impl CountingStatistics {
     pub fn failed(&self) -> usize {
         self.failed
     }
+
+    /// Returns the number of lost packets when used as loss statistics, same as `failed`
+    pub fn loss_count(&self) -> usize {
+        self.failed
+    }
+
+    /// Returns ratio of failed packets to total in `[0.0, 1.0]`, 0.0 if nothing counted yet
+    pub fn loss_ratio(&self) -> f32 {
+        self.failure_ratio()
+    }
+
+    /// Returns ratio of successful packets to total in `[0.0, 1.0]`, 0.0 if nothing counted yet
+    pub fn success_ratio(&self) -> f32 {
+        self.ratio_of(self.successful)
+    }
+
+    /// Returns ratio of failed packets to total in `[0.0, 1.0]`, 0.0 if nothing counted yet
+    pub fn failure_ratio(&self) -> f32 {
+        self.ratio_of(self.failed)
+    }
+
+    fn ratio_of(&self, count: usize) -> f32 {
+        let total = self.total();
+        if total == 0 {
+            return 0.0;
+        }
+
+        // Both counts are exact integers, only the final division is rounded
+        (count as f64 / total as f64) as f32
+    }
 }
 
 impl Statistics for CountingStatistics {
@@ -41,3 +73,61 @@ impl Statistics for CountingStatistics {
         self.successful = 0;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loss_ratio() {
+        let mut stats = CountingStatistics::default();
+        assert_eq!(stats.loss_ratio(), 0.0);
+
+        stats.add_successful(3);
+        stats.add_failed(1);
+        assert_eq!(stats.loss_count(), 1);
+        assert_eq!(stats.loss_ratio(), 0.25);
+    }
+
+    #[test]
+    fn success_and_failure_ratio() {
+        let mut stats = CountingStatistics::default();
+        assert_eq!(stats.success_ratio(), 0.0);
+        assert_eq!(stats.failure_ratio(), 0.0);
+
+        stats.add_successful(1);
+        stats.add_failed(3);
+        assert_eq!(stats.success_ratio(), 0.25);
+        assert_eq!(stats.failure_ratio(), 0.75);
+    }
+
+    #[test]
+    fn snapshot_reset() {
+        let mut stats = CountingStatistics::default();
+        stats.add_successful(5);
+        stats.add_failed(2);
+
+        let expected = stats.clone();
+        let snapshot = stats.snapshot_reset();
+
+        assert_eq!(snapshot, expected);
+        assert_eq!(snapshot.successful(), 5);
+        assert_eq!(snapshot.failed(), 2);
+        assert_eq!(stats.total(), 0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_roundtrip() {
+        let mut stats = CountingStatistics::default();
+        stats.add_successful(7);
+        stats.add_failed(3);
+
+        let json = serde_json::to_string(&stats).unwrap();
+        assert_eq!(json, r#"{"successful":7,"failed":3}"#);
+
+        let restored: CountingStatistics = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.successful(), 7);
+        assert_eq!(restored.failed(), 3);
+    }
+}
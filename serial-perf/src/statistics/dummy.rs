@@ -1,7 +1,7 @@
 use super::Statistics;
 
 /// Dummy statistics used to disable the statistics for the path
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct DummyStatistics;
 
 impl Statistics for DummyStatistics {
@@ -0,0 +1,124 @@
+use super::Statistics;
+
+/// Statistics that collects distribution of failed counts into `N` power of two buckets.
+///
+/// Bucket `i` counts `add_failed` calls with count in `[2^i, 2^(i+1))`, the last bucket also holds all bigger counts.
+/// Zero counts are not collected. When used as loss statistics of `Counting` it shows the distribution of loss burst lengths.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct HistogramStatistics<const N: usize> {
+    buckets: [usize; N],
+
+    /// Number of packets that were successfully sent/received
+    successful: usize,
+
+    /// Number of packets that were not sent/received
+    failed: usize,
+}
+
+impl<const N: usize> Default for HistogramStatistics<N> {
+    fn default() -> Self {
+        Self {
+            buckets: [0; N],
+            successful: 0,
+            failed: 0,
+        }
+    }
+}
+
+impl<const N: usize> HistogramStatistics<N> {
+    /// Returns amount of failures with count that falls into bucket `index`, zero if there is no such bucket
+    pub fn bucket(&self, index: usize) -> usize {
+        self.buckets.get(index).copied().unwrap_or(0)
+    }
+
+    /// Returns amount of buckets
+    pub fn bucket_count(&self) -> usize {
+        N
+    }
+
+    /// Returns the smallest count that falls into bucket `index`
+    pub fn bucket_start(&self, index: usize) -> usize {
+        1usize.checked_shl(index as u32).unwrap_or(usize::MAX)
+    }
+
+    /// Returns the total number of packets sent/received
+    pub fn total(&self) -> usize {
+        self.successful.saturating_add(self.failed)
+    }
+
+    /// Returns the number of packets that were successfully sent/received
+    pub fn successful(&self) -> usize {
+        self.successful
+    }
+
+    /// Returns the number of packets that were not sent/received or rejected when received
+    pub fn failed(&self) -> usize {
+        self.failed
+    }
+
+    fn bucket_index(count: usize) -> usize {
+        let index = (usize::BITS - 1 - count.leading_zeros()) as usize;
+        index.min(N - 1)
+    }
+}
+
+impl<const N: usize> Statistics for HistogramStatistics<N> {
+    fn add_failed(&mut self, count: usize) {
+        self.failed = self.failed.saturating_add(count);
+
+        if count == 0 || N == 0 {
+            return;
+        }
+
+        let bucket = &mut self.buckets[Self::bucket_index(count)];
+        *bucket = bucket.saturating_add(1);
+    }
+
+    fn add_successful(&mut self, count: usize) {
+        self.successful = self.successful.saturating_add(count);
+    }
+
+    fn reset(&mut self) {
+        self.buckets = [0; N];
+        self.failed = 0;
+        self.successful = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn power_of_two_buckets() {
+        let mut stats = HistogramStatistics::<4>::default();
+
+        stats.add_failed(0);
+        stats.add_failed(1);
+        stats.add_failed(2);
+        stats.add_failed(3);
+        stats.add_failed(7);
+        stats.add_failed(1000);
+        stats.add_successful(10);
+
+        assert_eq!(stats.bucket(0), 1);
+        assert_eq!(stats.bucket(1), 2);
+        assert_eq!(stats.bucket(2), 1);
+        assert_eq!(stats.bucket(3), 1);
+        assert_eq!(stats.bucket(4), 0);
+        assert_eq!(stats.bucket_start(3), 8);
+        assert_eq!(stats.failed(), 1013);
+        assert_eq!(stats.successful(), 10);
+    }
+
+    #[test]
+    fn reset() {
+        let mut stats = HistogramStatistics::<4>::default();
+        stats.add_failed(5);
+        stats.reset();
+
+        assert_eq!(stats.bucket(2), 0);
+        assert_eq!(stats.total(), 0);
+    }
+}
@@ -2,7 +2,7 @@ use core::time::Duration;
 
 use crate::byte_rate::{measure::IntervalByteRateMeasurer, rate::ByteRate};
 
-use super::Statistics;
+use super::{RateStatistics, Statistics};
 
 /// Statistics that count average byte rate instead counting number of bytes.
 pub struct IntervalRateStatistics<'clk, Clk>
@@ -11,6 +11,9 @@ where
 {
     successful_rate: IntervalByteRateMeasurer<'clk, Clk>,
     failed_rate: IntervalByteRateMeasurer<'clk, Clk>,
+
+    min_success_rate: Option<ByteRate>,
+    max_success_rate: Option<ByteRate>,
 }
 
 impl<'clk, Clk> IntervalRateStatistics<'clk, Clk>
@@ -21,15 +24,38 @@ where
         Self {
             successful_rate: IntervalByteRateMeasurer::new(clk, interval),
             failed_rate: IntervalByteRateMeasurer::new(clk, interval),
+            min_success_rate: None,
+            max_success_rate: None,
         }
     }
 
-    // pub fn total_rate(&self) -> Option<ByteRate> {
-    //     let success_rate = self.successful_rate.byte_rate()?;
-    //     let failed_rate = self.failed_rate.byte_rate()?;
+    /// Creates statistics with intervals aligned to `anchor`, see `IntervalByteRateMeasurer::new_anchored`.
+    ///
+    /// Pass the same `anchor` to TX and RX statistics to measure their rates over the same time windows.
+    pub fn new_anchored(clk: &'clk Clk, interval: Duration, anchor: Clk::Instant) -> Self {
+        Self {
+            successful_rate: IntervalByteRateMeasurer::new_anchored(clk, interval, anchor),
+            failed_rate: IntervalByteRateMeasurer::new_anchored(clk, interval, anchor),
+            min_success_rate: None,
+            max_success_rate: None,
+        }
+    }
 
-    //     Some(success_rate + failed_rate)
-    // }
+    /// Changes interval of rate measurement, resetting the statistics
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.successful_rate.set_interval(interval);
+        self.failed_rate.set_interval(interval);
+        self.min_success_rate = None;
+        self.max_success_rate = None;
+    }
+
+    /// Returns sum of successful and failed byte rates
+    pub fn total_rate(&self) -> ByteRate {
+        let success_rate = self.successful_rate.byte_rate().clone();
+        let failed_rate = self.failed_rate.byte_rate().clone();
+
+        success_rate + failed_rate
+    }
 
     pub fn success_rate(&self) -> &ByteRate {
         self.successful_rate.byte_rate()
@@ -38,6 +64,36 @@ where
     pub fn failed_rate(&self) -> &ByteRate {
         self.failed_rate.byte_rate()
     }
+
+    /// Returns the lowest success rate of finished intervals, if any interval finished
+    pub fn min_success_rate(&self) -> Option<&ByteRate> {
+        self.min_success_rate.as_ref()
+    }
+
+    /// Returns the highest success rate of finished intervals, if any interval finished
+    pub fn max_success_rate(&self) -> Option<&ByteRate> {
+        self.max_success_rate.as_ref()
+    }
+
+    fn update_success_extremes(&mut self) {
+        let rate = self.successful_rate.byte_rate();
+
+        if self
+            .min_success_rate
+            .as_ref()
+            .is_none_or(|min| rate.bytes() < min.bytes())
+        {
+            self.min_success_rate = Some(rate.clone());
+        }
+
+        if self
+            .max_success_rate
+            .as_ref()
+            .is_none_or(|max| rate.bytes() > max.bytes())
+        {
+            self.max_success_rate = Some(rate.clone());
+        }
+    }
 }
 
 impl<'clk, Clk> Statistics for IntervalRateStatistics<'clk, Clk>
@@ -45,7 +101,9 @@ where
     Clk: crate::clock::Clock,
 {
     fn add_successful(&mut self, count: usize) {
-        self.successful_rate.on_byte(count);
+        if self.successful_rate.on_byte(count) {
+            self.update_success_extremes();
+        }
     }
 
     fn add_failed(&mut self, count: usize) {
@@ -55,5 +113,47 @@ where
     fn reset(&mut self) {
         self.successful_rate.reset();
         self.failed_rate.reset();
+        self.min_success_rate = None;
+        self.max_success_rate = None;
+    }
+}
+
+impl<'clk, Clk> RateStatistics for IntervalRateStatistics<'clk, Clk>
+where
+    Clk: crate::clock::Clock,
+{
+    fn success_rate(&self) -> Option<ByteRate> {
+        Some(self.successful_rate.byte_rate().clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use crate::clock::MockClock;
+    use crate::statistics::Statistics;
+
+    use super::IntervalRateStatistics;
+
+    #[test]
+    fn success_extremes() {
+        let clock = MockClock::new();
+        let mut stats = IntervalRateStatistics::new(&clock, Duration::from_millis(50));
+
+        stats.add_successful(10);
+        assert!(stats.max_success_rate().is_none());
+
+        clock.advance(Duration::from_millis(60));
+        stats.add_successful(3);
+        clock.advance(Duration::from_millis(60));
+        stats.add_successful(0);
+
+        assert_eq!(stats.min_success_rate().unwrap().bytes(), 3);
+        assert_eq!(stats.max_success_rate().unwrap().bytes(), 10);
+
+        stats.reset();
+        assert!(stats.min_success_rate().is_none());
+        assert!(stats.max_success_rate().is_none());
     }
 }
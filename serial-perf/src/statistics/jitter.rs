@@ -0,0 +1,197 @@
+use core::time::Duration;
+
+use crate::clock::{Clock, Instant};
+
+use super::Statistics;
+
+/// Statistics of gaps between successful events, e.g. bytes received
+///
+/// Each `add_successful` call is timestamped and the gap to the previous call is collected.
+/// Mean and standard deviation are calculated over all gaps, percentiles over the last `N` gaps.
+pub struct JitterStatistics<'clk, Clk, const N: usize>
+where
+    Clk: Clock,
+{
+    clock: &'clk Clk,
+    last_event: Option<Clk::Instant>,
+
+    count: u64,
+    sum_ns: u128,
+    sum_squares_ns: u128,
+
+    /// Last `N` gaps, ring buffer
+    recent: [Duration; N],
+    recent_len: usize,
+    recent_next: usize,
+}
+
+impl<'clk, Clk, const N: usize> JitterStatistics<'clk, Clk, N>
+where
+    Clk: Clock,
+{
+    const NON_EMPTY: () = assert!(N > 0, "jitter statistics must keep at least one gap");
+
+    pub fn new(clk: &'clk Clk) -> Self {
+        let () = Self::NON_EMPTY;
+
+        Self {
+            clock: clk,
+            last_event: None,
+            count: 0,
+            sum_ns: 0,
+            sum_squares_ns: 0,
+            recent: [Duration::ZERO; N],
+            recent_len: 0,
+            recent_next: 0,
+        }
+    }
+
+    /// Returns amount of gaps collected
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns average gap between events
+    pub fn mean(&self) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+
+        Some(duration_from_nanos(self.sum_ns / self.count as u128))
+    }
+
+    /// Returns standard deviation of gaps between events
+    pub fn std_dev(&self) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+
+        let count = self.count as u128;
+        let mean = self.sum_ns / count;
+        let variance = (self.sum_squares_ns / count).saturating_sub(mean.saturating_mul(mean));
+
+        Some(duration_from_nanos(variance.isqrt()))
+    }
+
+    /// Returns gap not exceeded by `percent` of the last `N` gaps, `percent` is clamped to 100
+    pub fn percentile(&self, percent: u8) -> Option<Duration> {
+        if self.recent_len == 0 {
+            return None;
+        }
+
+        let mut sorted = self.recent;
+        let sorted = &mut sorted[..self.recent_len];
+        sorted.sort_unstable();
+
+        let percent = usize::from(percent.min(100));
+        let index = (percent * self.recent_len).div_ceil(100).saturating_sub(1);
+
+        Some(sorted[index])
+    }
+
+    /// Returns median of the last `N` gaps
+    pub fn p50(&self) -> Option<Duration> {
+        self.percentile(50)
+    }
+
+    /// Returns 95th percentile of the last `N` gaps
+    pub fn p95(&self) -> Option<Duration> {
+        self.percentile(95)
+    }
+
+    /// Returns 99th percentile of the last `N` gaps
+    pub fn p99(&self) -> Option<Duration> {
+        self.percentile(99)
+    }
+
+    fn on_event(&mut self) {
+        let now = self.clock.now();
+        let last_event = self.last_event.replace(now);
+
+        let Some(gap) = last_event.and_then(|last| now.checked_duration_since(last)) else {
+            return;
+        };
+
+        let gap_ns = gap.as_nanos();
+        self.count = self.count.saturating_add(1);
+        self.sum_ns = self.sum_ns.saturating_add(gap_ns);
+        self.sum_squares_ns = self
+            .sum_squares_ns
+            .saturating_add(gap_ns.saturating_mul(gap_ns));
+
+        self.recent[self.recent_next] = gap;
+        self.recent_next = (self.recent_next + 1) % N;
+        self.recent_len = (self.recent_len + 1).min(N);
+    }
+}
+
+impl<'clk, Clk, const N: usize> Statistics for JitterStatistics<'clk, Clk, N>
+where
+    Clk: Clock,
+{
+    fn add_successful(&mut self, count: usize) {
+        if count > 0 {
+            self.on_event();
+        }
+    }
+
+    fn add_failed(&mut self, _count: usize) {
+        // Only successful events are timed
+    }
+
+    fn reset(&mut self) {
+        self.last_event = None;
+        self.count = 0;
+        self.sum_ns = 0;
+        self.sum_squares_ns = 0;
+        self.recent_len = 0;
+        self.recent_next = 0;
+    }
+}
+
+fn duration_from_nanos(nanos: u128) -> Duration {
+    Duration::from_nanos(u64::try_from(nanos).unwrap_or(u64::MAX))
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use crate::clock::MockClock;
+    use crate::statistics::Statistics;
+
+    use super::JitterStatistics;
+
+    #[test]
+    fn regular_gaps_have_no_jitter() {
+        let clock = MockClock::new();
+        let mut stats = JitterStatistics::<_, 8>::new(&clock);
+
+        for _ in 0..5 {
+            stats.add_successful(1);
+            clock.advance(Duration::from_millis(10));
+        }
+
+        assert_eq!(stats.count(), 4);
+        assert_eq!(stats.mean(), Some(Duration::from_millis(10)));
+        assert_eq!(stats.std_dev(), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn percentiles() {
+        let clock = MockClock::new();
+        let mut stats = JitterStatistics::<_, 4>::new(&clock);
+        assert!(stats.p50().is_none());
+
+        stats.add_successful(1);
+        for gap_ms in [1, 2, 3, 4, 100] {
+            clock.advance(Duration::from_millis(gap_ms));
+            stats.add_successful(1);
+        }
+
+        // The first gap left the window
+        assert_eq!(stats.p50(), Some(Duration::from_millis(3)));
+        assert_eq!(stats.p99(), Some(Duration::from_millis(100)));
+        assert_eq!(stats.std_dev().unwrap(), Duration::from_nanos(39_012_818));
+    }
+}
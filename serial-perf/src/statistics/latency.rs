@@ -0,0 +1,238 @@
+use core::cell::RefCell;
+use core::time::Duration;
+
+use crate::clock::{Clock, Instant};
+
+use super::Statistics;
+
+/// Measures roundtrip latency of bytes sent to the remote `Loopback` and received back
+///
+/// The statistics are shared between TX and RX paths: pass `tx()` as the TX statistics and `rx()`
+/// as the RX statistics of the client (`Counting` for example). Each sent byte is timestamped and
+/// the next received byte is matched with the oldest timestamp, so up to `N` bytes can be in flight.
+///
+/// # Note
+/// If more than `N` bytes are in flight the oldest timestamps are dropped, assuming these bytes were lost.
+pub struct LatencyStatistics<'clk, Clk, const N: usize>
+where
+    Clk: Clock,
+{
+    clock: &'clk Clk,
+    state: RefCell<LatencyState<Clk::Instant, N>>,
+}
+
+struct LatencyState<I, const N: usize> {
+    in_flight: heapless::Deque<I, N>,
+
+    count: usize,
+    total: Duration,
+    min: Option<Duration>,
+    max: Option<Duration>,
+}
+
+impl<I, const N: usize> LatencyState<I, N> {
+    fn reset_latency(&mut self) {
+        self.count = 0;
+        self.total = Duration::ZERO;
+        self.min = None;
+        self.max = None;
+    }
+
+    fn record(&mut self, latency: Duration) {
+        self.count = self.count.saturating_add(1);
+        self.total = self.total.saturating_add(latency);
+        self.min = Some(self.min.map_or(latency, |min| min.min(latency)));
+        self.max = Some(self.max.map_or(latency, |max| max.max(latency)));
+    }
+}
+
+impl<'clk, Clk, const N: usize> LatencyStatistics<'clk, Clk, N>
+where
+    Clk: Clock,
+{
+    pub fn new(clk: &'clk Clk) -> Self {
+        Self {
+            clock: clk,
+            state: RefCell::new(LatencyState {
+                in_flight: heapless::Deque::new(),
+                count: 0,
+                total: Duration::ZERO,
+                min: None,
+                max: None,
+            }),
+        }
+    }
+
+    /// Returns statistics to be used for TX path
+    pub fn tx(&self) -> LatencyTxStatistics<'_, 'clk, Clk, N> {
+        LatencyTxStatistics { latency: self }
+    }
+
+    /// Returns statistics to be used for RX path
+    pub fn rx(&self) -> LatencyRxStatistics<'_, 'clk, Clk, N> {
+        LatencyRxStatistics { latency: self }
+    }
+
+    /// Returns the smallest roundtrip time measured
+    pub fn min(&self) -> Option<Duration> {
+        self.state.borrow().min
+    }
+
+    /// Returns the biggest roundtrip time measured
+    pub fn max(&self) -> Option<Duration> {
+        self.state.borrow().max
+    }
+
+    /// Returns the average roundtrip time measured
+    pub fn average(&self) -> Option<Duration> {
+        let state = self.state.borrow();
+        if state.count == 0 {
+            return None;
+        }
+
+        let average_ns = state.total.as_nanos() / state.count as u128;
+        Some(Duration::from_nanos(
+            u64::try_from(average_ns).unwrap_or(u64::MAX),
+        ))
+    }
+
+    /// Returns amount of roundtrips measured
+    pub fn count(&self) -> usize {
+        self.state.borrow().count
+    }
+
+    /// Resets measured latencies and forgets bytes in flight
+    pub fn reset(&self) {
+        let mut state = self.state.borrow_mut();
+        state.in_flight.clear();
+        state.reset_latency();
+    }
+
+    fn on_sent(&self, count: usize) {
+        let now = self.clock.now();
+        let mut state = self.state.borrow_mut();
+
+        for _ in 0..count {
+            if state.in_flight.is_full() {
+                state.in_flight.pop_front();
+            }
+            state.in_flight.push_back(now).ok();
+        }
+    }
+
+    fn on_received(&self, count: usize) {
+        let now = self.clock.now();
+        let mut state = self.state.borrow_mut();
+
+        for _ in 0..count {
+            let Some(sent_at) = state.in_flight.pop_front() else {
+                break;
+            };
+
+            if let Some(latency) = now.checked_duration_since(sent_at) {
+                state.record(latency);
+            }
+        }
+    }
+}
+
+/// TX side of `LatencyStatistics`, timestamps sent bytes
+pub struct LatencyTxStatistics<'a, 'clk, Clk, const N: usize>
+where
+    Clk: Clock,
+{
+    latency: &'a LatencyStatistics<'clk, Clk, N>,
+}
+
+impl<'a, 'clk, Clk, const N: usize> Statistics for LatencyTxStatistics<'a, 'clk, Clk, N>
+where
+    Clk: Clock,
+{
+    fn add_successful(&mut self, count: usize) {
+        self.latency.on_sent(count);
+    }
+
+    fn add_failed(&mut self, _count: usize) {
+        // Byte was not sent, nothing to wait for
+    }
+
+    fn reset(&mut self) {
+        self.latency.state.borrow_mut().in_flight.clear();
+    }
+}
+
+/// RX side of `LatencyStatistics`, measures roundtrip time of received bytes
+pub struct LatencyRxStatistics<'a, 'clk, Clk, const N: usize>
+where
+    Clk: Clock,
+{
+    latency: &'a LatencyStatistics<'clk, Clk, N>,
+}
+
+impl<'a, 'clk, Clk, const N: usize> Statistics for LatencyRxStatistics<'a, 'clk, Clk, N>
+where
+    Clk: Clock,
+{
+    fn add_successful(&mut self, count: usize) {
+        self.latency.on_received(count);
+    }
+
+    fn add_failed(&mut self, _count: usize) {
+        // Byte was not received, the echo may still come
+    }
+
+    fn reset(&mut self) {
+        self.latency.state.borrow_mut().reset_latency();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use crate::clock::MockClock;
+    use crate::statistics::Statistics;
+
+    use super::LatencyStatistics;
+
+    #[test]
+    fn roundtrip_latency() {
+        let clock = MockClock::new();
+        let latency = LatencyStatistics::<_, 4>::new(&clock);
+        let mut tx = latency.tx();
+        let mut rx = latency.rx();
+
+        assert!(latency.average().is_none());
+
+        tx.add_successful(1);
+        clock.advance(Duration::from_millis(2));
+        tx.add_successful(1);
+        clock.advance(Duration::from_millis(2));
+        rx.add_successful(2);
+
+        assert_eq!(latency.count(), 2);
+        assert_eq!(latency.min(), Some(Duration::from_millis(2)));
+        assert_eq!(latency.max(), Some(Duration::from_millis(4)));
+        assert_eq!(latency.average(), Some(Duration::from_millis(3)));
+
+        // Nothing in flight, received byte is not measured
+        rx.add_successful(1);
+        assert_eq!(latency.count(), 2);
+    }
+
+    #[test]
+    fn oldest_dropped_when_full() {
+        let clock = MockClock::new();
+        let latency = LatencyStatistics::<_, 1>::new(&clock);
+        let mut tx = latency.tx();
+        let mut rx = latency.rx();
+
+        tx.add_successful(1);
+        clock.advance(Duration::from_millis(5));
+        tx.add_successful(1);
+        clock.advance(Duration::from_millis(1));
+        rx.add_successful(1);
+
+        assert_eq!(latency.max(), Some(Duration::from_millis(1)));
+    }
+}
@@ -0,0 +1,83 @@
+use super::{CountingStatistics, LossEvent, Statistics};
+
+/// Statistics that keeps lifetime totals in addition to the wrapped statistics
+///
+/// `reset` clears only the wrapped statistics, so both a resettable view, e.g. rate over the last interval,
+/// and totals since creation are available from one object.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LifetimeStatistics<Inner> {
+    inner: Inner,
+    lifetime: CountingStatistics,
+}
+
+impl<Inner> LifetimeStatistics<Inner>
+where
+    Inner: Statistics,
+{
+    pub fn new(inner: Inner) -> Self {
+        Self {
+            inner,
+            lifetime: CountingStatistics::default(),
+        }
+    }
+
+    /// Returns the wrapped statistics
+    pub fn inner(&self) -> &Inner {
+        &self.inner
+    }
+
+    /// Returns totals since creation, not cleared by `reset`
+    pub fn lifetime(&self) -> &CountingStatistics {
+        &self.lifetime
+    }
+}
+
+impl<Inner> Statistics for LifetimeStatistics<Inner>
+where
+    Inner: Statistics,
+{
+    fn add_successful(&mut self, count: usize) {
+        self.inner.add_successful(count);
+        self.lifetime.add_successful(count);
+    }
+
+    fn add_failed(&mut self, count: usize) {
+        self.inner.add_failed(count);
+        self.lifetime.add_failed(count);
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+
+    fn add_loss_event(&mut self, event: LossEvent) {
+        self.inner.add_loss_event(event);
+    }
+
+    fn add_blocked(&mut self, count: usize) {
+        self.inner.add_blocked(count);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::statistics::{CountingStatistics, Statistics};
+
+    use super::LifetimeStatistics;
+
+    #[test]
+    fn lifetime_survives_reset() {
+        let mut stats = LifetimeStatistics::new(CountingStatistics::default());
+
+        stats.add_successful(10);
+        stats.add_failed(2);
+        stats.reset();
+        stats.add_successful(5);
+
+        assert_eq!(stats.inner().successful(), 5);
+        assert_eq!(stats.inner().failed(), 0);
+        assert_eq!(stats.lifetime().successful(), 15);
+        assert_eq!(stats.lifetime().failed(), 2);
+    }
+}
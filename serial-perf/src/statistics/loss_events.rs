@@ -0,0 +1,134 @@
+use super::{LossEvent, LossLogger, Statistics};
+
+/// Statistics that keeps the last `N` loss events in addition to the wrapped statistics
+///
+/// Use it as loss statistics of `Counting` and take the events periodically with `take_events`.
+/// The oldest events are dropped when more than `N` events collected.
+///
+/// Each event is passed to `Logger` too, see `with_logger` and `CountingBuilder::loss_logger`.
+#[derive(Debug, Default)]
+pub struct LossEventStatistics<Inner, const N: usize, Logger = ()> {
+    inner: Inner,
+    events: heapless::Deque<LossEvent, N>,
+    logger: Logger,
+}
+
+impl<Inner, const N: usize> LossEventStatistics<Inner, N>
+where
+    Inner: Statistics,
+{
+    pub fn new(inner: Inner) -> Self {
+        Self::with_logger(inner, ())
+    }
+}
+
+impl<Inner, const N: usize, Logger> LossEventStatistics<Inner, N, Logger>
+where
+    Inner: Statistics,
+    Logger: LossLogger,
+{
+    /// Creates statistics passing each loss event to `logger`
+    pub fn with_logger(inner: Inner, logger: Logger) -> Self {
+        Self {
+            inner,
+            events: heapless::Deque::new(),
+            logger,
+        }
+    }
+
+    /// Returns the logger
+    pub fn logger(&self) -> &Logger {
+        &self.logger
+    }
+
+    /// Returns the logger as mutable
+    pub fn logger_mut(&mut self) -> &mut Logger {
+        &mut self.logger
+    }
+
+    /// Returns the wrapped statistics
+    pub fn inner(&self) -> &Inner {
+        &self.inner
+    }
+
+    /// Returns iterator over collected events from the oldest to the newest
+    pub fn events(&self) -> impl Iterator<Item = &LossEvent> {
+        self.events.iter()
+    }
+
+    /// Returns collected events from the oldest to the newest and clears them
+    pub fn take_events(&mut self) -> heapless::Deque<LossEvent, N> {
+        core::mem::take(&mut self.events)
+    }
+}
+
+impl<Inner, const N: usize, Logger> Statistics for LossEventStatistics<Inner, N, Logger>
+where
+    Inner: Statistics,
+    Logger: LossLogger,
+{
+    fn add_successful(&mut self, count: usize) {
+        self.inner.add_successful(count);
+    }
+
+    fn add_failed(&mut self, count: usize) {
+        self.inner.add_failed(count);
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+        self.events.clear();
+    }
+
+    fn add_blocked(&mut self, count: usize) {
+        self.inner.add_blocked(count);
+    }
+
+    fn add_loss_event(&mut self, event: LossEvent) {
+        if self.events.is_full() {
+            self.events.pop_front();
+        }
+        self.events.push_back(event).ok();
+
+        self.inner.add_loss_event(event);
+        self.logger.log_loss(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::counting::Counting;
+    use crate::statistics::{CountingStatistics, LossEvent, LossEventStatistics};
+
+    #[test]
+    fn events_collected() {
+        let mut counting = Counting::<_, u8, _, _, _>::new(
+            (),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            LossEventStatistics::<_, 1>::new(CountingStatistics::default()),
+        );
+
+        let mut packet = [0; 3];
+        for number in 0..10 {
+            counting.fill_send_slice(&mut packet);
+            // Drop packets 3, 6, 7
+            if ![3, 6, 7].contains(&number) {
+                counting.recv_slice(&packet);
+            }
+        }
+
+        assert_eq!(counting.loss_stats().inner().failed(), 3);
+
+        let events = counting.loss_stats_mut().take_events();
+        assert_eq!(
+            events.iter().collect::<heapless::Vec<_, 1>>(),
+            [&LossEvent {
+                from: 5,
+                to: 8,
+                lost: 2
+            }]
+        );
+        assert_eq!(counting.loss_stats().events().count(), 0);
+    }
+}
@@ -0,0 +1,128 @@
+use alloc::vec::Vec;
+
+use crate::counting::{Counter, Counting};
+
+use super::{LossEvent, Statistics};
+
+/// Statistics that keeps every loss event in addition to the wrapped statistics
+///
+/// Unlike `LossEventStatistics` the events are stored in a growable `Vec`, so no event is dropped
+/// and the complete history of a long run is available via `loss_history`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct LossHistoryStatistics<Inner> {
+    inner: Inner,
+    events: Vec<LossEvent>,
+}
+
+impl<Inner> LossHistoryStatistics<Inner>
+where
+    Inner: Statistics,
+{
+    pub fn new(inner: Inner) -> Self {
+        Self {
+            inner,
+            events: Vec::new(),
+        }
+    }
+
+    /// Returns the wrapped statistics
+    pub fn inner(&self) -> &Inner {
+        &self.inner
+    }
+
+    /// Returns all loss events from the oldest to the newest
+    pub fn loss_history(&self) -> &[LossEvent] {
+        &self.events
+    }
+
+    /// Returns all loss events from the oldest to the newest and clears them
+    pub fn take_events(&mut self) -> Vec<LossEvent> {
+        core::mem::take(&mut self.events)
+    }
+}
+
+impl<Inner> Statistics for LossHistoryStatistics<Inner>
+where
+    Inner: Statistics,
+{
+    fn add_successful(&mut self, count: usize) {
+        self.inner.add_successful(count);
+    }
+
+    fn add_failed(&mut self, count: usize) {
+        self.inner.add_failed(count);
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+        self.events.clear();
+    }
+
+    fn add_blocked(&mut self, count: usize) {
+        self.inner.add_blocked(count);
+    }
+
+    fn add_loss_event(&mut self, event: LossEvent) {
+        self.events.push(event);
+        self.inner.add_loss_event(event);
+    }
+}
+
+impl<Serial, Number, TxStats, RxStats, Inner, const MAX_PACKET_SIZE: usize>
+    Counting<Serial, Number, TxStats, RxStats, LossHistoryStatistics<Inner>, MAX_PACKET_SIZE>
+where
+    Number: Counter,
+    TxStats: Statistics,
+    RxStats: Statistics,
+    Inner: Statistics,
+{
+    /// Returns all loss events detected since the last reset, from the oldest to the newest
+    pub fn loss_history(&self) -> &[LossEvent] {
+        self.loss_stats().loss_history()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::counting::Counting;
+    use crate::statistics::{CountingStatistics, LossEvent, LossHistoryStatistics};
+
+    #[test]
+    fn history_kept() {
+        let mut counting = Counting::<_, u8, _, _, _>::new(
+            (),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            LossHistoryStatistics::new(CountingStatistics::default()),
+        );
+
+        let mut packet = [0; 3];
+        for number in 0..10 {
+            counting.fill_send_slice(&mut packet);
+            // Drop packets 3, 6, 7
+            if ![3, 6, 7].contains(&number) {
+                counting.recv_slice(&packet);
+            }
+        }
+
+        assert_eq!(counting.loss_stats().inner().failed(), 3);
+        assert_eq!(
+            counting.loss_history(),
+            [
+                LossEvent {
+                    from: 2,
+                    to: 4,
+                    lost: 1
+                },
+                LossEvent {
+                    from: 5,
+                    to: 8,
+                    lost: 2
+                }
+            ]
+        );
+
+        counting.reset();
+        assert!(counting.loss_history().is_empty());
+    }
+}
@@ -0,0 +1,53 @@
+use super::LossEvent;
+
+/// Sink of loss events, e.g. logging them with defmt, sending to a channel or counting
+pub trait LossLogger {
+    /// Handles a single loss event
+    fn log_loss(&mut self, event: LossEvent);
+}
+
+/// No-op logger
+impl LossLogger for () {
+    fn log_loss(&mut self, _event: LossEvent) {
+        // Do nothing
+    }
+}
+
+impl<F> LossLogger for F
+where
+    F: FnMut(LossEvent),
+{
+    fn log_loss(&mut self, event: LossEvent) {
+        self(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::counting::Counting;
+    use crate::statistics::{CountingStatistics, LossEvent};
+
+    #[test]
+    fn logger_called_on_loss() {
+        let mut lost = 0;
+        let logger = |event: LossEvent| lost += event.lost;
+
+        let mut counting = Counting::<_, u8, _, _, _>::builder(
+            (),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+            CountingStatistics::default(),
+        )
+        .loss_logger(logger)
+        .build();
+
+        let mut buf = [0; 3 * 10];
+        counting.fill_send_slice(&mut buf);
+        counting.recv_slice(&buf[..3]);
+        counting.recv_slice(&buf[3 * 4..]);
+
+        assert_eq!(counting.loss_stats().inner().failed(), 3);
+        drop(counting);
+        assert_eq!(lost, 3);
+    }
+}
@@ -0,0 +1,83 @@
+use core::time::Duration;
+
+use crate::byte_rate::{measure::IntervalByteRateMeasurer, rate::ByteRate};
+
+use super::Statistics;
+
+/// Loss statistics that measure rate of lost packets per interval instead of counting them.
+///
+/// The rates count packets, not bytes, so `ByteRate::bytes_per_second` returns packets per second.
+/// Comparable between links with different baud rates.
+pub struct LossRateStatistics<'clk, Clk>
+where
+    Clk: crate::clock::Clock,
+{
+    received_rate: IntervalByteRateMeasurer<'clk, Clk>,
+    lost_rate: IntervalByteRateMeasurer<'clk, Clk>,
+}
+
+impl<'clk, Clk> LossRateStatistics<'clk, Clk>
+where
+    Clk: crate::clock::Clock,
+{
+    pub fn new(clk: &'clk Clk, interval: Duration) -> Self {
+        Self {
+            received_rate: IntervalByteRateMeasurer::new(clk, interval),
+            lost_rate: IntervalByteRateMeasurer::new(clk, interval),
+        }
+    }
+
+    /// Returns rate of lost packets in the last finished interval
+    pub fn loss_rate(&self) -> &ByteRate {
+        self.lost_rate.byte_rate()
+    }
+
+    /// Returns rate of received packets in the last finished interval
+    pub fn received_rate(&self) -> &ByteRate {
+        self.received_rate.byte_rate()
+    }
+}
+
+impl<'clk, Clk> Statistics for LossRateStatistics<'clk, Clk>
+where
+    Clk: crate::clock::Clock,
+{
+    fn add_successful(&mut self, count: usize) {
+        self.received_rate.on_byte(count);
+    }
+
+    fn add_failed(&mut self, count: usize) {
+        self.lost_rate.on_byte(count);
+    }
+
+    fn reset(&mut self) {
+        self.received_rate.reset();
+        self.lost_rate.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use crate::clock::MockClock;
+    use crate::statistics::Statistics;
+
+    use super::LossRateStatistics;
+
+    #[test]
+    fn lost_packets_per_second() {
+        let clock = MockClock::new();
+        let mut stats = LossRateStatistics::new(&clock, Duration::from_secs(2));
+
+        stats.add_failed(6);
+        stats.add_successful(10);
+
+        clock.advance(Duration::from_secs(2));
+        stats.add_failed(0);
+        stats.add_successful(1);
+
+        assert_eq!(stats.loss_rate().bytes_per_second(), Some(3));
+        assert_eq!(stats.received_rate().bytes_per_second(), Some(5));
+    }
+}
@@ -2,18 +2,67 @@
 //! Struct for storing the statistics of a TX/RX paths(total bytes, errors, etc)
 //!
 
+use crate::byte_rate::rate::ByteRate;
+
+mod blocked;
+pub use blocked::BlockedStatistics;
+
 mod dummy;
 pub use dummy::DummyStatistics;
 
 mod counting;
 pub use counting::CountingStatistics;
 
+mod lifetime;
+pub use lifetime::LifetimeStatistics;
+
 mod avg_rate;
 pub use avg_rate::AvgRateStatistics;
 
 mod interval_rate;
 pub use interval_rate::IntervalRateStatistics;
 
+mod loss_rate;
+pub use loss_rate::LossRateStatistics;
+
+mod histogram;
+pub use histogram::HistogramStatistics;
+
+mod jitter;
+pub use jitter::JitterStatistics;
+
+mod loss_events;
+pub use loss_events::LossEventStatistics;
+
+#[cfg(feature = "alloc")]
+mod loss_history;
+#[cfg(feature = "alloc")]
+pub use loss_history::LossHistoryStatistics;
+
+mod recent_loss;
+pub use recent_loss::RecentLossStatistics;
+
+mod loss_logger;
+pub use loss_logger::LossLogger;
+
+mod latency;
+pub use latency::{LatencyRxStatistics, LatencyStatistics, LatencyTxStatistics};
+
+mod tee;
+pub use tee::Tee;
+
+/// Loss of packets detected by receiver
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LossEvent {
+    /// Normalized number of the last packet received before loss
+    pub from: u64,
+    /// Normalized number of the first packet received after loss
+    pub to: u64,
+    /// Amount of packets lost
+    pub lost: usize,
+}
+
 /// Trait for capturing statistics,
 pub trait Statistics {
     /// Adds `count` successful packets to the statistics
@@ -24,4 +73,32 @@ pub trait Statistics {
 
     /// Resets all stats in this struct.
     fn reset(&mut self);
+
+    /// Handles a single loss event, called on loss statistics in addition to `add_failed`.
+    ///
+    /// Does nothing by default.
+    fn add_loss_event(&mut self, _event: LossEvent) {}
+
+    /// Adds `count` attempts blocked by the serial, e.g. saturated or flow-controlled link.
+    ///
+    /// Blocked attempts are neither successful nor failed, they are retried later. Does nothing by default.
+    fn add_blocked(&mut self, _count: usize) {}
+
+    /// Returns stats accumulated so far and resets this struct in one call.
+    ///
+    /// Useful for interval reporting: no packet added between reading and resetting can be lost.
+    fn snapshot_reset(&mut self) -> Self
+    where
+        Self: Sized + Clone,
+    {
+        let snapshot = self.clone();
+        self.reset();
+        snapshot
+    }
+}
+
+/// Statistics that measure byte rate of successful packets
+pub trait RateStatistics: Statistics {
+    /// Returns byte rate of successful packets, if measured
+    fn success_rate(&self) -> Option<ByteRate>;
 }
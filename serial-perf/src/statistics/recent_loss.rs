@@ -0,0 +1,129 @@
+use crate::counting::{Counter, Counting};
+
+use super::Statistics;
+
+/// Packets counted in one bucket of `RecentLossStatistics`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct Bucket {
+    successful: usize,
+    failed: usize,
+}
+
+impl Bucket {
+    fn total(&self) -> usize {
+        self.successful.saturating_add(self.failed)
+    }
+}
+
+/// Loss statistics over a sliding window of the recent packets, reflecting the present link condition
+///
+/// Packets are counted in buckets of `bucket_packets` packets, the window keeps up to `N` finished buckets
+/// and the current one. Old buckets are dropped as new ones are finished, so the loss seen at start of
+/// a test stops affecting `recent_loss_ratio` unlike the cumulative totals of `CountingStatistics`.
+#[derive(Debug, Clone)]
+pub struct RecentLossStatistics<const N: usize> {
+    bucket_packets: usize,
+    current: Bucket,
+    buckets: heapless::Deque<Bucket, N>,
+}
+
+impl<const N: usize> RecentLossStatistics<N> {
+    /// Creates statistics with window of `N` buckets of `bucket_packets` packets each
+    pub fn new(bucket_packets: usize) -> Self {
+        Self {
+            bucket_packets: bucket_packets.max(1),
+            current: Bucket::default(),
+            buckets: heapless::Deque::new(),
+        }
+    }
+
+    /// Returns ratio of lost packets to all packets in the window, from 0.0 to 1.0.
+    ///
+    /// Returns 0.0 if no packets are in the window.
+    pub fn recent_loss_ratio(&self) -> f32 {
+        let window = self
+            .buckets
+            .iter()
+            .fold(self.current, |sum, bucket| Bucket {
+                successful: sum.successful.saturating_add(bucket.successful),
+                failed: sum.failed.saturating_add(bucket.failed),
+            });
+
+        match window.total() {
+            0 => 0.0,
+            total => window.failed as f32 / total as f32,
+        }
+    }
+
+    /// Finishes current bucket if it's full, dropping the oldest one if the window is full
+    fn rotate(&mut self) {
+        if self.current.total() < self.bucket_packets {
+            return;
+        }
+
+        if self.buckets.is_full() {
+            self.buckets.pop_front();
+        }
+
+        // Never fails with window of at least one bucket
+        self.buckets.push_back(self.current).ok();
+        self.current = Bucket::default();
+    }
+}
+
+impl<const N: usize> Statistics for RecentLossStatistics<N> {
+    fn add_successful(&mut self, count: usize) {
+        self.current.successful = self.current.successful.saturating_add(count);
+        self.rotate();
+    }
+
+    fn add_failed(&mut self, count: usize) {
+        self.current.failed = self.current.failed.saturating_add(count);
+        self.rotate();
+    }
+
+    fn reset(&mut self) {
+        self.current = Bucket::default();
+        self.buckets.clear();
+    }
+}
+
+impl<Serial, Number, TxStats, RxStats, const N: usize, const MAX_PACKET_SIZE: usize>
+    Counting<Serial, Number, TxStats, RxStats, RecentLossStatistics<N>, MAX_PACKET_SIZE>
+where
+    Number: Counter,
+    TxStats: Statistics,
+    RxStats: Statistics,
+{
+    /// Returns ratio of lost packets in the recent window, see `RecentLossStatistics::recent_loss_ratio`
+    pub fn recent_loss_ratio(&self) -> f32 {
+        self.loss_stats().recent_loss_ratio()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::statistics::Statistics;
+
+    use super::RecentLossStatistics;
+
+    #[test]
+    fn old_loss_dropped() {
+        let mut stats = RecentLossStatistics::<2>::new(10);
+
+        stats.add_failed(5);
+        stats.add_successful(5);
+        assert_eq!(stats.recent_loss_ratio(), 0.5);
+
+        stats.add_successful(10);
+        assert_eq!(stats.recent_loss_ratio(), 0.25);
+
+        // The bucket with loss is the oldest one now and is dropped
+        stats.add_successful(10);
+        assert_eq!(stats.recent_loss_ratio(), 0.0);
+
+        stats.add_failed(1);
+        stats.reset();
+        assert_eq!(stats.recent_loss_ratio(), 0.0);
+    }
+}
@@ -0,0 +1,107 @@
+use super::{LossEvent, Statistics};
+
+/// Statistics forwarding every event to two inner statistics
+///
+/// Allows to collect different statistics for the same path, e.g. totals and live rate.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Tee<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A, B> Tee<A, B>
+where
+    A: Statistics,
+    B: Statistics,
+{
+    pub fn new(first: A, second: B) -> Self {
+        Self { first, second }
+    }
+
+    /// Returns the first inner statistics
+    pub fn first(&self) -> &A {
+        &self.first
+    }
+
+    /// Returns the second inner statistics
+    pub fn second(&self) -> &B {
+        &self.second
+    }
+
+    /// Returns the first inner statistics as mutable
+    pub fn first_mut(&mut self) -> &mut A {
+        &mut self.first
+    }
+
+    /// Returns the second inner statistics as mutable
+    pub fn second_mut(&mut self) -> &mut B {
+        &mut self.second
+    }
+
+    /// Returns both inner statistics
+    pub fn into_inner(self) -> (A, B) {
+        (self.first, self.second)
+    }
+}
+
+impl<A, B> Statistics for Tee<A, B>
+where
+    A: Statistics,
+    B: Statistics,
+{
+    fn add_successful(&mut self, count: usize) {
+        self.first.add_successful(count);
+        self.second.add_successful(count);
+    }
+
+    fn add_failed(&mut self, count: usize) {
+        self.first.add_failed(count);
+        self.second.add_failed(count);
+    }
+
+    fn reset(&mut self) {
+        self.first.reset();
+        self.second.reset();
+    }
+
+    fn add_loss_event(&mut self, event: LossEvent) {
+        self.first.add_loss_event(event);
+        self.second.add_loss_event(event);
+    }
+
+    fn add_blocked(&mut self, count: usize) {
+        self.first.add_blocked(count);
+        self.second.add_blocked(count);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::statistics::{CountingStatistics, LossEvent, LossEventStatistics, Statistics};
+
+    use super::Tee;
+
+    #[test]
+    fn forwards_to_both() {
+        let mut tee = Tee::new(
+            CountingStatistics::default(),
+            LossEventStatistics::<CountingStatistics, 4>::new(CountingStatistics::default()),
+        );
+
+        tee.add_successful(3);
+        tee.add_failed(1);
+        tee.add_loss_event(LossEvent {
+            from: 1,
+            to: 3,
+            lost: 1,
+        });
+
+        assert_eq!(tee.first().successful(), 3);
+        assert_eq!(tee.second().inner().failed(), 1);
+        assert_eq!(tee.second().events().count(), 1);
+
+        tee.reset();
+        assert_eq!(tee.first().total(), 0);
+        assert_eq!(tee.second().inner().total(), 0);
+    }
+}
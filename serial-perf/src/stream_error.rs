@@ -0,0 +1,28 @@
+//!
+//! Error of the tests running over `embedded-io` and `embedded-io-async` streams
+//!
+
+/// Error of the io and async methods of tests
+#[derive(Debug)]
+pub enum StreamError<E> {
+    /// Error of the underlying serial
+    Serial(E),
+    /// The serial read or wrote zero bytes, i.e. it reached end of file or is closed.
+    /// Retrying won't help, the serial must be reopened.
+    Closed,
+}
+
+impl<E> core::fmt::Display for StreamError<E>
+where
+    E: core::fmt::Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Serial(e) => write!(f, "serial error: {e:?}"),
+            Self::Closed => write!(f, "serial is closed"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E> std::error::Error for StreamError<E> where E: core::fmt::Debug {}
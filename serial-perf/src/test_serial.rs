@@ -0,0 +1,262 @@
+//!
+//! Scriptable serials for tests: a serial with fixed behavior and a wrapper moving `MockClock`
+//!
+//! Also `block_on` running async tests over these serials and `PairedSerial` without an executor.
+//!
+
+use core::convert::Infallible;
+use core::time::Duration;
+
+use embedded_hal_nb::nb;
+use embedded_hal_nb::serial::{ErrorType, Read, Write};
+
+use crate::clock::MockClock;
+
+/// Serial with scripted behavior counting calls made to it
+///
+/// By default it accepts and drops every written byte, flushes at once and blocks on read.
+#[derive(Debug, Default)]
+pub struct ScriptedSerial {
+    read_byte: Option<u8>,
+    block_writes_every: usize,
+    blocked_flushes: usize,
+
+    reads: usize,
+    write_calls: usize,
+    written: usize,
+    flushes: usize,
+}
+
+impl ScriptedSerial {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serial which never receives and never accepts a byte
+    pub fn blocked() -> Self {
+        Self::new().blocking_writes(1)
+    }
+
+    /// Every read returns `byte` instead of blocking
+    pub fn reading(mut self, byte: u8) -> Self {
+        self.read_byte = Some(byte);
+        self
+    }
+
+    /// Blocks every `every`-th write starting from the first one, 1 blocks all writes
+    pub fn blocking_writes(mut self, every: usize) -> Self {
+        self.block_writes_every = every;
+        self
+    }
+
+    /// Blocks the next `count` flushes
+    pub fn blocking_flushes(mut self, count: usize) -> Self {
+        self.blocked_flushes = count;
+        self
+    }
+
+    /// Returns amount of read calls, including blocked ones
+    pub fn reads(&self) -> usize {
+        self.reads
+    }
+
+    /// Returns amount of accepted bytes
+    pub fn written(&self) -> usize {
+        self.written
+    }
+
+    /// Returns amount of completed flushes
+    pub fn flushes(&self) -> usize {
+        self.flushes
+    }
+
+    fn write_blocked(&mut self) -> bool {
+        let call = self.write_calls;
+        self.write_calls += 1;
+        self.block_writes_every != 0 && call.is_multiple_of(self.block_writes_every)
+    }
+
+    fn flush_blocked(&mut self) -> bool {
+        if self.blocked_flushes == 0 {
+            self.flushes += 1;
+            false
+        } else {
+            self.blocked_flushes -= 1;
+            true
+        }
+    }
+}
+
+impl ErrorType for ScriptedSerial {
+    type Error = Infallible;
+}
+
+impl Read for ScriptedSerial {
+    fn read(&mut self) -> nb::Result<u8, Self::Error> {
+        self.reads += 1;
+        self.read_byte.ok_or(nb::Error::WouldBlock)
+    }
+}
+
+impl Write for ScriptedSerial {
+    fn write(&mut self, _word: u8) -> nb::Result<(), Self::Error> {
+        if self.write_blocked() {
+            return Err(nb::Error::WouldBlock);
+        }
+        self.written += 1;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        if self.flush_blocked() {
+            return Err(nb::Error::WouldBlock);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "embedded-io")]
+impl embedded_io::ErrorType for ScriptedSerial {
+    type Error = Infallible;
+}
+
+#[cfg(all(feature = "async", not(feature = "embedded-io")))]
+impl embedded_io_async::ErrorType for ScriptedSerial {
+    type Error = Infallible;
+}
+
+/// Accepts the whole buffer, blocked writes and flushes are not scripted for slices
+#[cfg(feature = "embedded-io")]
+impl embedded_io::Write for ScriptedSerial {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        self.written += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.flushes += 1;
+        Ok(())
+    }
+}
+
+/// Accepts the whole buffer, blocked writes and flushes are not scripted for slices
+#[cfg(feature = "async")]
+impl embedded_io_async::Write for ScriptedSerial {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        self.written += buf.len();
+        Ok(buf.len())
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        self.flushes += 1;
+        Ok(())
+    }
+}
+
+/// Wraps a serial and moves `MockClock` forward on its reads or writes, simulating time the line takes
+#[derive(Debug)]
+pub struct ClockedSerial<'clock, Serial> {
+    serial: Serial,
+    clock: &'clock MockClock,
+    read_step: Duration,
+    write_step: Duration,
+    flush_step: Duration,
+}
+
+impl<'clock, Serial> ClockedSerial<'clock, Serial> {
+    pub fn new(serial: Serial, clock: &'clock MockClock) -> Self {
+        Self {
+            serial,
+            clock,
+            read_step: Duration::ZERO,
+            write_step: Duration::ZERO,
+            flush_step: Duration::ZERO,
+        }
+    }
+
+    /// Moves the clock by `step` on every read call, including blocked ones
+    pub fn advance_on_read(mut self, step: Duration) -> Self {
+        self.read_step = step;
+        self
+    }
+
+    /// Moves the clock by `step` on every write call, including blocked ones
+    pub fn advance_on_write(mut self, step: Duration) -> Self {
+        self.write_step = step;
+        self
+    }
+
+    /// Moves the clock by `step` on every flush call, including blocked ones
+    pub fn advance_on_flush(mut self, step: Duration) -> Self {
+        self.flush_step = step;
+        self
+    }
+
+    /// Returns wrapped serial
+    pub fn serial(&self) -> &Serial {
+        &self.serial
+    }
+}
+
+impl<Serial: ErrorType> ErrorType for ClockedSerial<'_, Serial> {
+    type Error = Serial::Error;
+}
+
+impl<Serial: Read> Read for ClockedSerial<'_, Serial> {
+    fn read(&mut self) -> nb::Result<u8, Self::Error> {
+        self.clock.advance(self.read_step);
+        self.serial.read()
+    }
+}
+
+impl<Serial: Write> Write for ClockedSerial<'_, Serial> {
+    fn write(&mut self, word: u8) -> nb::Result<(), Self::Error> {
+        self.clock.advance(self.write_step);
+        self.serial.write(word)
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        self.clock.advance(self.flush_step);
+        self.serial.flush()
+    }
+}
+
+/// Serial that is closed, async reads and writes transfer no bytes
+#[cfg(feature = "async")]
+#[derive(Debug, Default)]
+pub struct ClosedSerial;
+
+#[cfg(feature = "async")]
+impl embedded_io_async::ErrorType for ClosedSerial {
+    type Error = Infallible;
+}
+
+#[cfg(feature = "async")]
+impl embedded_io_async::Read for ClosedSerial {
+    async fn read(&mut self, _buf: &mut [u8]) -> Result<usize, Self::Error> {
+        Ok(0)
+    }
+}
+
+#[cfg(feature = "async")]
+impl embedded_io_async::Write for ClosedSerial {
+    async fn write(&mut self, _buf: &[u8]) -> Result<usize, Self::Error> {
+        Ok(0)
+    }
+}
+
+/// Polls `future` once and returns its output
+///
+/// Test serials never wait for the other side, `PairedSerial` asks to be polled again instead.
+///
+/// # Panics
+/// Panics if the future is not ready after the first poll
+#[cfg(feature = "async")]
+pub fn block_on<F: core::future::Future>(future: F) -> F::Output {
+    let mut future = core::pin::pin!(future);
+    let mut cx = core::task::Context::from_waker(core::task::Waker::noop());
+    match future.as_mut().poll(&mut cx) {
+        core::task::Poll::Ready(output) => output,
+        core::task::Poll::Pending => panic!("test serial must not wait"),
+    }
+}
@@ -0,0 +1,165 @@
+//!
+//! Serial wrapper detecting stalled receiving, e.g. a dead link
+//!
+
+use core::time::Duration;
+
+use embedded_hal_nb::nb;
+use embedded_hal_nb::serial::{self, ErrorType, Read, Write};
+
+use crate::clock::{Clock, ClockExt};
+
+/// Error of reading from `TimeoutSerial` with timeout
+#[derive(Debug)]
+pub enum TimeoutSerialError<E> {
+    /// Error of the underlying serial
+    Serial(E),
+    /// No byte received within the timeout since the last successful read
+    Timeout,
+}
+
+impl<E> core::fmt::Display for TimeoutSerialError<E>
+where
+    E: core::fmt::Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Serial(e) => write!(f, "serial error: {e:?}"),
+            Self::Timeout => write!(f, "no data received within timeout"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E> std::error::Error for TimeoutSerialError<E> where E: core::fmt::Debug {}
+
+/// A wrapper around embedded-hal serial that tracks the time of the last successful read
+///
+/// Reading with `read_timeout` returns `TimeoutSerialError::Timeout` if no byte arrived within the timeout,
+/// so a dead link can be told from a slow one. The `Read` implementation is not affected and returns
+/// `WouldBlock` as the underlying serial does. The timeout is counted from creation until the first byte.
+pub struct TimeoutSerial<'clk, Serial, Clk>
+where
+    Clk: Clock,
+{
+    serial: Serial,
+    clock: &'clk Clk,
+    timeout: Duration,
+    last_read: Clk::Instant,
+}
+
+impl<'clk, Serial, Clk> TimeoutSerial<'clk, Serial, Clk>
+where
+    Clk: Clock,
+{
+    pub fn new(serial: Serial, clock: &'clk Clk, timeout: Duration) -> Self {
+        Self {
+            serial,
+            clock,
+            timeout,
+            last_read: clock.now(),
+        }
+    }
+
+    /// Returns the time since the last successful read
+    pub fn idle_time(&self) -> Duration {
+        self.clock.elapsed_since(self.last_read)
+    }
+
+    /// Returns `true` if no byte has been received within the timeout
+    pub fn is_timed_out(&self) -> bool {
+        self.idle_time() > self.timeout
+    }
+
+    /// Returns the timeout of receiving
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Sets the timeout of receiving
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Restarts the timeout from now, e.g. after the link is restored
+    pub fn restart(&mut self) {
+        self.last_read = self.clock.now();
+    }
+
+    /// Returns reference to the underlying serial
+    pub fn serial(&self) -> &Serial {
+        &self.serial
+    }
+
+    /// Returns mutable reference to the underlying serial
+    pub fn serial_mut(&mut self) -> &mut Serial {
+        &mut self.serial
+    }
+
+    /// Destroys the wrapper returning the underlying serial
+    pub fn into_inner(self) -> Serial {
+        self.serial
+    }
+}
+
+impl<Serial, Clk> TimeoutSerial<'_, Serial, Clk>
+where
+    Serial: Read,
+    Clk: Clock,
+{
+    /// Reads a byte, returns `TimeoutSerialError::Timeout` instead of `WouldBlock` if the timeout is passed
+    pub fn read_timeout(&mut self) -> nb::Result<u8, TimeoutSerialError<Serial::Error>> {
+        match self.read() {
+            Err(nb::Error::WouldBlock) if self.is_timed_out() => {
+                Err(nb::Error::Other(TimeoutSerialError::Timeout))
+            }
+            res => res.map_err(|e| e.map(TimeoutSerialError::Serial)),
+        }
+    }
+}
+
+impl<Serial, Clk> ErrorType for TimeoutSerial<'_, Serial, Clk>
+where
+    Serial: ErrorType,
+    Clk: Clock,
+{
+    type Error = Serial::Error;
+}
+
+impl<Serial, Clk> Read for TimeoutSerial<'_, Serial, Clk>
+where
+    Serial: Read,
+    Clk: Clock,
+{
+    fn read(&mut self) -> nb::Result<u8, Self::Error> {
+        let byte = self.serial.read()?;
+        self.last_read = self.clock.now();
+        Ok(byte)
+    }
+}
+
+impl<Serial, Clk> Write for TimeoutSerial<'_, Serial, Clk>
+where
+    Serial: Write,
+    Clk: Clock,
+{
+    fn write(&mut self, word: u8) -> nb::Result<(), Self::Error> {
+        self.serial.write(word)
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        self.serial.flush()
+    }
+}
+
+impl<E> serial::Error for TimeoutSerialError<E>
+where
+    E: serial::Error,
+{
+    fn kind(&self) -> serial::ErrorKind {
+        match self {
+            Self::Serial(e) => e.kind(),
+            Self::Timeout => serial::ErrorKind::Other,
+        }
+    }
+}